@@ -1,60 +1,91 @@
+use lexer_with_dynamic_state_machine::LineCounter;
+use lexer_with_dynamic_state_machine::Span;
+
 fn main() {
-    let code = "<= <> >= = <= >= < >";
+    let code = "<= <> >= = <= >= < > !? <!";
     let characters: Vec<char> = code.chars().collect();
 
-    // Not needed because we never need the lexem.
-    // let mut lexem_begin = 0;
-    // let mut forward = 0;
+    // The lexem is never needed, only where it begins for the span of the token.
+    let mut lexem_begin = 0;
+    let mut forward = 0;
     let mut state = 0;
+    let mut line_counter = LineCounter::new();
 
-    let mut tokens = Vec::new();
-    for i in 0..characters.len() + 1 {
+    let mut tokens: Vec<(&str, Span)> = Vec::new();
+    while forward <= characters.len() {
         let input;
-        if i == characters.len() {
+        if forward == characters.len() {
             input = '\n'; // This should be eof
         } else {
-            input = characters[i];
+            input = characters[forward];
         }
-        // forward += 1;
 
         match state {
             0 => match input {
-                '<' => state = 1,
+                '<' => {
+                    lexem_begin = forward;
+                    state = 1;
+                },
                 '=' => {
-                    tokens.push("EQUALS");
+                    tokens.push(("EQUALS", line_counter.get_span(code, forward, forward + 1)));
+                    state = 0;
+                },
+                '>' => {
+                    lexem_begin = forward;
+                    state = 6;
+                },
+                ' ' | '\t' | '\r' | '\n' => state = 0,
+                // Unexpected characters become error tokens, lexing goes on with the next
+                // character.
+                _ => {
+                    let span = line_counter.get_span(code, forward, forward + 1);
+                    match tokens.last_mut() {
+                        // Neighbouring unexpected characters form a single error token.
+                        Some(("ERROR", last_span)) if last_span.end == forward => last_span.end = span.end,
+                        _ => tokens.push(("ERROR", span)),
+                    }
                     state = 0;
                 },
-                '>' => state = 6,
-                _ => state = 0,
             },
             1 => match input {
                 '=' => {
-                    tokens.push("LESS_EQUALS");
+                    tokens.push(("LESS_EQUALS", line_counter.get_span(code, lexem_begin, forward + 1)));
                     state = 0;
                 },
                 '>' => {
-                    tokens.push("NOT_EQUALS");
+                    tokens.push(("NOT_EQUALS", line_counter.get_span(code, lexem_begin, forward + 1)));
                     state = 0;
                 },
                 _ => {
-                    tokens.push("LESS_THAN");
+                    tokens.push(("LESS_THAN", line_counter.get_span(code, lexem_begin, forward)));
+                    // The character after the token may start the next token.
+                    forward -= 1;
                     state = 0;
                 },
             }, 
             6 => match input {
                 '=' => {
-                    tokens.push("GREATER_EQUALS");
+                    tokens.push(("GREATER_EQUALS", line_counter.get_span(code, lexem_begin, forward + 1)));
                     state = 0;
                 },
                 _ => {
-                    tokens.push("GREATER_THAN");
+                    tokens.push(("GREATER_THAN", line_counter.get_span(code, lexem_begin, forward)));
+                    forward -= 1;
                     state = 0;
                 },
             },
             _ => state = 0,
         };
+
+        forward += 1;
     }
 
     println!("{}", code);
-    println!("{:#?}", tokens);
+    for (token, span) in tokens {
+        if token == "ERROR" {
+            println!("{} ERROR - \"{}\"", span, span.as_str(code));
+        } else {
+            println!("{} {}", span, token);
+        }
+    }
 }
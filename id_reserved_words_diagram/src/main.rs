@@ -1,26 +1,31 @@
-#[derive(Debug)]
-struct Symbol<'a> {
-    pub id: &'a str,
-    pub is_keyword: bool,
-}
+use lexer_with_dynamic_state_machine::is_identifier_continue;
+use lexer_with_dynamic_state_machine::is_identifier_start;
+use lexer_with_dynamic_state_machine::normalize_identifier;
+use lexer_with_dynamic_state_machine::LineCounter;
+use lexer_with_dynamic_state_machine::Span;
+use lexer_with_dynamic_state_machine::SymbolId;
+use lexer_with_dynamic_state_machine::SymbolTable;
 
 fn main() {
     // Reserved keywords: if, then, else
-    let code = "if hello then hi else bye - ifHello thenHello elseHello the ele";
-    let characters: Vec<char> = code.chars().collect();
+    let code = "if hello then hi else bye - if- ifHello thenHello elseHello the ele größe";
+    let characters: Vec<(usize, char)> = code.char_indices().collect();
+    // Lexemes are sliced by byte offsets, while forward counts characters.
+    let offset = |index: usize| characters.get(index).map_or(code.len(), |(offset, _)| *offset);
 
     let mut lexem_begin = 0;
     let mut forward = 0;
     let mut state = 0;
-    let mut symbols: Vec<Symbol> = Vec::new();
+    let mut symbols = SymbolTable::new();
+    let mut line_counter = LineCounter::new();
 
-    let mut tokens: Vec<(&str, i32)> = Vec::new();
+    let mut tokens: Vec<(&str, Option<SymbolId>, Span)> = Vec::new();
     loop {
         let input;
         if forward == characters.len() {
             input = '\n'; // This should be eof
         } else if forward < characters.len() {
-            input = characters[forward];
+            input = characters[forward].1;
         } else {
             break;
         }
@@ -30,10 +35,19 @@ fn main() {
                 'i' => state = 12,
                 't' => state = 14,
                 'e' => state = 18,
-                'a'..='z' | 'A'..='Z' => {
+                _ if is_identifier_start(input) => {
                     state = 10;
                 },
                 _ => {
+                    // Unexpected characters become error tokens instead of being skipped.
+                    if !input.is_whitespace() {
+                        let span = line_counter.get_span(code, offset(forward), offset(forward + 1));
+                        match tokens.last_mut() {
+                            // Neighbouring unexpected characters form a single error token.
+                            Some(("ERROR", _, last_span)) if last_span.end == offset(forward) => last_span.end = span.end,
+                            _ => tokens.push(("ERROR", None, span)),
+                        }
+                    }
                     state = 0;
                     // +1 because the character forward is pointing does not lead to a new knot
                     // therefore it is not the start of a lexem.
@@ -43,28 +57,15 @@ fn main() {
                 },
             },
             10 => match input {
-                'a'..='z' | 'A'..='Z' | '0'..='9' => {},
+                _ if is_identifier_continue(input) => {},
                 _ => {
                     forward -= 1;
                     // lexem_begin + 1 since the index will be included
-                    let lexem = &code[lexem_begin..forward+1];
+                    let lexem = &code[offset(lexem_begin)..offset(forward + 1)];
+                    let span = line_counter.get_span(code, offset(lexem_begin), offset(forward + 1));
                     // println!("{} - \"{}\"", forward, lexem);
 
-                    let mut index = None;
-                    for i in 0..symbols.len() {
-                        let symbol = &symbols[i];
-                        if symbol.id == lexem {
-                            index = Some(i);
-                        }
-                    }
-
-                    match index {
-                        Some(i) => tokens.push(("ID", i as i32)),
-                        None => {
-                            tokens.push(("ID", symbols.len() as i32));
-                            symbols.push(Symbol{ id: lexem, is_keyword: false });
-                        },
-                    }
+                    tokens.push(("ID", Some(symbols.intern(&normalize_identifier(lexem))), span));
 
                     lexem_begin = forward + 1;
                     state = 0;
@@ -78,13 +79,15 @@ fn main() {
                 },
             },
             13 => match input {
-                'a'..='z' | 'A'..='Z' | '0'..='9' => {
+                _ if is_identifier_continue(input) => {
                     forward -= 1;
                     state = 10;
                 },
                 _ => {
-                    tokens.push(("IF", -1));
-
+                    // The keyword ends before the character forward is pointing at.
+                    tokens.push(("IF", None, line_counter.get_span(code, offset(lexem_begin), offset(forward))));
+                    // The character after the keyword may start the next token.
+                    forward -= 1;
                     lexem_begin = forward + 1;
                     state = 0;
                 },
@@ -111,13 +114,13 @@ fn main() {
                 },
             },
             17 => match input {
-                'a'..='z' | 'A'..='Z' | '0'..='9' => {
+                _ if is_identifier_continue(input) => {
                     forward -= 1;
                     state = 10;
                 },
                 _ => {
-                    tokens.push(("THEN", -1));
-
+                    tokens.push(("THEN", None, line_counter.get_span(code, offset(lexem_begin), offset(forward))));
+                    forward -= 1;
                     lexem_begin = forward + 1;
                     state = 0;
                 },
@@ -144,13 +147,13 @@ fn main() {
                 },
             },
             21 => match input {
-                'a'..='z' | 'A'..='Z' | '0'..='9' => {
+                _ if is_identifier_continue(input) => {
                     forward -= 1;
                     state = 10;
                 },
                 _ => {
-                    tokens.push(("ELSE", -1));
-
+                    tokens.push(("ELSE", None, line_counter.get_span(code, offset(lexem_begin), offset(forward))));
+                    forward -= 1;
                     lexem_begin = forward + 1;
                     state = 0;
                 },
@@ -167,9 +170,11 @@ fn main() {
     println!("{}", code);
     for token in tokens {
         if token.0 == "ID" {
-            println!("ID - \"{}\"", symbols[token.1 as usize].id);
+            println!("{} ID - \"{}\"", token.2, symbols.get_name(token.1.unwrap()));
+        } else if token.0 == "ERROR" {
+            println!("{} ERROR - \"{}\"", token.2, token.2.as_str(code));
         } else {
-            println!("{}", token.0);
+            println!("{} {}", token.2, token.0);
         }
     }
 }
@@ -1,40 +1,50 @@
-#[derive(Debug)]
-struct Symbol<'a> {
-    pub id: &'a str,
-    pub is_keyword: bool,
-}
+use lexer_with_dynamic_state_machine::is_identifier_continue;
+use lexer_with_dynamic_state_machine::is_identifier_start;
+use lexer_with_dynamic_state_machine::normalize_identifier;
+use lexer_with_dynamic_state_machine::LineCounter;
+use lexer_with_dynamic_state_machine::Span;
+use lexer_with_dynamic_state_machine::SymbolId;
+use lexer_with_dynamic_state_machine::SymbolTable;
 
 fn main() {
     // Reserved keywords: if, then, else
-    let code = "if hello then hi else bye";
-    let characters: Vec<char> = code.chars().collect();
+    let code = "if hello then hi else bye 42 hello größe cafe\u{301} caf\u{e9}";
+    let characters: Vec<(usize, char)> = code.char_indices().collect();
+    // Lexemes are sliced by byte offsets, while forward counts characters.
+    let offset = |index: usize| characters.get(index).map_or(code.len(), |(offset, _)| *offset);
 
     let mut lexem_begin = 0;
     let mut forward = 0;
     let mut state = 0;
-    let mut symbols = vec![
-        Symbol { id: "if", is_keyword: true },
-        Symbol { id: "then", is_keyword: true },
-        Symbol { id: "else", is_keyword: true },
-    ];
+    let mut symbols = SymbolTable::with_keywords(&["if", "then", "else"]);
+    let mut line_counter = LineCounter::new();
 
-    let mut tokens: Vec<(&str, i32)> = Vec::new();
+    let mut tokens: Vec<(&str, Option<SymbolId>, Span)> = Vec::new();
     loop {
         let input;
         if forward == characters.len() {
             input = '\n'; // This should be eof
         } else if forward < characters.len() {
-            input = characters[forward];
+            input = characters[forward].1;
         } else {
             break;
         }
 
         match state {
             0 => match input {
-                'a'..='z' | 'A'..='Z' => {
+                _ if is_identifier_start(input) => {
                     state = 10;
                 },
                 _ => {
+                    // Unexpected characters become error tokens instead of being skipped.
+                    if !input.is_whitespace() {
+                        let span = line_counter.get_span(code, offset(forward), offset(forward + 1));
+                        match tokens.last_mut() {
+                            // Neighbouring unexpected characters form a single error token.
+                            Some(("ERROR", _, last_span)) if last_span.end == offset(forward) => last_span.end = span.end,
+                            _ => tokens.push(("ERROR", None, span)),
+                        }
+                    }
                     state = 0;
                     // +1 because the character forward is pointing does not lead to a new knot
                     // therefore it is not the start of a lexem.
@@ -44,32 +54,24 @@ fn main() {
                 },
             },
             10 => match input {
-                'a'..='z' | 'A'..='Z' | '0'..='9' => {},
+                _ if is_identifier_continue(input) => {},
                 _ => {
                     forward -= 1;
                     // lexem_begin + 1 since the index will be included
-                    let lexem = &code[lexem_begin..forward+1];
+                    let lexem = &code[offset(lexem_begin)..offset(forward + 1)];
+                    let span = line_counter.get_span(code, offset(lexem_begin), offset(forward + 1));
                     // println!("{} - \"{}\"", forward, lexem);
 
-                    let mut index = None;
-                    for i in 0..symbols.len() {
-                        let symbol = &symbols[i];
-                        if symbol.id == lexem {
-                            index = Some(i);
-                        }
-                    }
-
-                    match index {
-                        Some(i) => match symbols[i].id {
-                            "if" => tokens.push(("IF", -1)),
-                            "then" => tokens.push(("THEN", -1)),
-                            "else" => tokens.push(("ELSE", -1)),
+                    let id = symbols.intern(&normalize_identifier(lexem));
+                    if symbols.is_keyword(id) {
+                        match symbols.get_name(id) {
+                            "if" => tokens.push(("IF", None, span)),
+                            "then" => tokens.push(("THEN", None, span)),
+                            "else" => tokens.push(("ELSE", None, span)),
                             _ => {},
-                        },
-                        None => {
-                            tokens.push(("ID", symbols.len() as i32));
-                            symbols.push(Symbol{ id: lexem, is_keyword: false });
-                        },
+                        }
+                    } else {
+                        tokens.push(("ID", Some(id), span));
                     }
 
                     lexem_begin = forward;
@@ -88,9 +90,11 @@ fn main() {
     println!("{}", code);
     for token in tokens {
         if token.0 == "ID" {
-            println!("ID - \"{}\"", symbols[token.1 as usize].id);
+            println!("{} ID - \"{}\"", token.2, symbols.get_name(token.1.unwrap()));
+        } else if token.0 == "ERROR" {
+            println!("{} ERROR - \"{}\"", token.2, token.2.as_str(code));
         } else {
-            println!("{}", token.0);
+            println!("{} {}", token.2, token.0);
         }
     }
 }
@@ -0,0 +1,149 @@
+//! Compares the keyword strategies of `LexerSpec::identifiers` on the same input. The input is
+//! the file given as first argument, or generated C-like code if no file is given.
+//!
+//! `cargo run --release --bin keyword_benchmark -- [FILE]`
+
+use lexer_with_dynamic_state_machine::KeywordStrategy;
+use lexer_with_dynamic_state_machine::Lexer;
+use lexer_with_dynamic_state_machine::LexerSpec;
+use std::env;
+use std::fs;
+use std::process;
+use std::time::Instant;
+
+const KEYWORDS: [&str; 32] = [
+    "auto", "break", "case", "char", "const", "continue", "default", "do", "double", "else",
+    "enum", "extern", "float", "for", "goto", "if", "int", "long", "register", "return", "short",
+    "signed", "sizeof", "static", "struct", "switch", "typedef", "union", "unsigned", "void",
+    "volatile", "while",
+];
+
+const IDENTIFIERS: [&str; 8] = [
+    "i", "count", "index", "buffer", "length", "do_work", "iffy", "returned",
+];
+
+const GENERATED_TOKEN_COUNT: usize = 1_000_000;
+const RUNS: usize = 5;
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Keyword,
+    Id,
+    Number,
+    Punctuation,
+    Whitespace,
+    Unknown,
+}
+
+fn main() {
+    let text = match env::args().nth(1) {
+        Some(path) => match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(error) => {
+                eprintln!("Could not read {}: {}", path, error);
+                process::exit(1);
+            }
+        },
+        None => generate_input(GENERATED_TOKEN_COUNT),
+    };
+
+    println!(
+        "{} keywords, {} bytes of input, automatic strategy: {:?}",
+        KEYWORDS.len(),
+        text.len(),
+        KeywordStrategy::Automatic.resolve(&KEYWORDS)
+    );
+    println!(
+        "{:<14} {:>8} {:>12} {:>10} {:>10} {:>10}",
+        "strategy", "states", "transitions", "build ms", "tokens", "MB/s"
+    );
+
+    for strategy in &[KeywordStrategy::StateMachine, KeywordStrategy::Lookup] {
+        let build_start = Instant::now();
+        let lexer = build_lexer(*strategy);
+        let build_time = build_start.elapsed();
+
+        // The fastest run is the least disturbed by the rest of the system.
+        let mut token_count = 0;
+        let mut fastest_run = None;
+        for _ in 0..RUNS {
+            let start = Instant::now();
+            token_count = count_tokens(&lexer, &text);
+            let run_time = start.elapsed();
+
+            fastest_run = match fastest_run {
+                Some(fastest_run) if fastest_run < run_time => Some(fastest_run),
+                _ => Some(run_time),
+            };
+        }
+
+        let seconds = fastest_run.unwrap().as_secs_f64();
+        println!(
+            "{:<14} {:>8} {:>12} {:>10.1} {:>10} {:>10.1}",
+            format!("{:?}", strategy),
+            lexer.get_state_count(),
+            lexer.get_transition_count(),
+            build_time.as_secs_f64() * 1000.0,
+            token_count,
+            text.len() as f64 / seconds / 1_000_000.0
+        );
+    }
+}
+
+fn build_lexer(strategy: KeywordStrategy) -> Lexer<Token> {
+    let lexer = LexerSpec::new()
+        .identifiers(
+            "[a-z_][a-z0-9_]*",
+            &KEYWORDS,
+            strategy,
+            |_| Token::Keyword,
+            |_| Token::Id,
+        )
+        .rule("[0-9]+", |_| Token::Number)
+        .rule("[(){};=<>+*,-]", |_| Token::Punctuation)
+        .rule("[ \t\n]+", |_| Token::Whitespace)
+        .build();
+
+    return match lexer {
+        Ok(lexer) => lexer,
+        Err(error) => {
+            eprintln!("Could not build the lexer: {}", error);
+            process::exit(1);
+        }
+    };
+}
+
+fn count_tokens(lexer: &Lexer<Token>, text: &str) -> usize {
+    return lexer
+        .tokens(text)
+        .recover_from_errors(|_| Token::Unknown)
+        .flatten()
+        .filter(|token| token.value != Token::Whitespace && token.value != Token::Unknown)
+        .count();
+}
+
+/// Creates the same pseudo random mix of keywords, identifiers, numbers and punctuation on every
+/// run, so that results can be compared.
+fn generate_input(token_count: usize) -> String {
+    let mut text = String::new();
+    let mut seed: u32 = 42;
+
+    for index in 0..token_count {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        let random = (seed >> 16) as usize;
+
+        match random % 8 {
+            0..=2 => text.push_str(KEYWORDS[random % KEYWORDS.len()]),
+            3..=5 => text.push_str(IDENTIFIERS[random % IDENTIFIERS.len()]),
+            6 => text.push_str(&(random % 1000).to_string()),
+            _ => text.push_str(["(", ")", "{", "}", ";", "=", "+"][random % 7]),
+        }
+
+        match index % 12 {
+            11 => text.push('\n'),
+            _ => text.push(' '),
+        }
+    }
+
+    return text;
+}
@@ -0,0 +1,552 @@
+use crate::lexer_spec::get_entry_points;
+use crate::CompressedTable;
+use crate::LexFile;
+use crate::SpecError;
+use crate::INITIAL;
+use regex::RegexBuilder;
+use regex::RegexSet;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+/// Generates a self-contained Rust module with the lexer of a lex file, e.g. from a `build.rs`,
+/// so the state machine does not have to be compiled at runtime. The actions of the lex file are
+/// Rust expressions of the type `Option<Token>`, which can use `lexeme: &str` and
+/// `lexer: &mut Lexer`. Rules without action do not create a token.
+///
+/// ```text
+/// %x COMMENT
+/// DIGIT [0-9]
+/// %%
+/// {DIGIT}+ Some(Token::Number(lexeme.parse().unwrap()))
+/// "/*" { lexer.begin(COMMENT); None }
+/// <COMMENT>"*/" { lexer.begin(INITIAL); None }
+/// <COMMENT>[^*]+|"*"
+/// " "+
+/// ```
+///
+/// The module contains the `Token` enum with the variants given to `token`, a constant for every
+/// start condition, the `Lexer` which iterates over the tokens of a text and the state machine as
+/// `match` expressions. It is usually included with
+/// `include!(concat!(env!("OUT_DIR"), "/lexer.rs"))`.
+pub struct RustLexerGenerator<'a> {
+    lex_file: &'a LexFile,
+    token_variants: Vec<String>,
+    compress_tables: bool,
+}
+impl<'a> RustLexerGenerator<'a> {
+    pub fn new(lex_file: &'a LexFile) -> Self {
+        RustLexerGenerator {
+            lex_file,
+            token_variants: Vec::new(),
+            compress_tables: false,
+        }
+    }
+
+    /// Adds a variant to the generated `Token` enum, e.g. `If` or `Number(u64)`. Without any
+    /// variants, no enum is generated and the declarations of the lex file have to define
+    /// `Token`.
+    pub fn token(mut self, variant: &str) -> Self {
+        self.token_variants.push(variant.to_string());
+
+        return self;
+    }
+
+    /// Generates the state machine as compressed tables like the ones of flex instead of `match`
+    /// expressions, see `CompressedTable`. The generated code is smaller for lexers with many
+    /// states, but every transition needs a few table lookups.
+    pub fn compress_tables(mut self) -> Self {
+        self.compress_tables = true;
+
+        return self;
+    }
+
+    pub fn generate(&self) -> Result<String, SpecError> {
+        let mut start_conditions = vec![(String::from(INITIAL), false)];
+        for start_condition in &self.lex_file.start_conditions {
+            if !start_conditions
+                .iter()
+                .any(|(name, _)| name == &start_condition.name)
+            {
+                start_conditions.push((start_condition.name.clone(), start_condition.is_exclusive));
+            }
+        }
+
+        let rule_start_conditions: Vec<Option<Vec<String>>> = self
+            .lex_file
+            .rules
+            .iter()
+            .map(|rule| match rule.start_conditions.is_empty() {
+                true => None,
+                false => Some(rule.start_conditions.clone()),
+            })
+            .collect();
+        let entry_points = get_entry_points(&start_conditions, &rule_start_conditions)?;
+
+        let mut regex_builder = RegexBuilder::new();
+        for (name, regex) in &self.lex_file.definitions {
+            regex_builder = regex_builder.define(name, regex);
+        }
+        let mut asts = Vec::with_capacity(self.lex_file.rules.len());
+        for rule in &self.lex_file.rules {
+            asts.push(regex_builder.get_regex_syntax_tree(&rule.pattern)?);
+        }
+        let regex_set = RegexSet::with_entry_points(asts, entry_points);
+
+        let mut code = String::new();
+        self.write_header(&mut code, &start_conditions);
+        match self.compress_tables {
+            true => write_compressed_state_machine(&mut code, &regex_set, start_conditions.len()),
+            false => write_state_machine(&mut code, &regex_set, start_conditions.len()),
+        }
+        if !self.lex_file.user_code.trim().is_empty() {
+            code.push('\n');
+            code.push_str(&self.lex_file.user_code);
+            code.push('\n');
+        }
+
+        return Ok(code);
+    }
+
+    fn write_header(&self, code: &mut String, start_conditions: &[(String, bool)]) {
+        code.push_str("// Generated by lexer-with-dynamic-state-machine, do not edit.\n");
+
+        if !self.lex_file.top_code.trim().is_empty() {
+            code.push('\n');
+            code.push_str(&self.lex_file.top_code);
+            code.push('\n');
+        }
+
+        if !self.lex_file.declarations.trim().is_empty() {
+            code.push('\n');
+            code.push_str(&self.lex_file.declarations);
+            code.push('\n');
+        }
+
+        if !self.token_variants.is_empty() {
+            code.push_str("\n#[derive(Clone, Debug, PartialEq)]\npub enum Token {\n");
+            for variant in &self.token_variants {
+                code.push_str(&format!("    {},\n", variant));
+            }
+            code.push_str("}\n");
+        }
+
+        code.push('\n');
+        for (index, (name, _)) in start_conditions.iter().enumerate() {
+            code.push_str(&format!("pub const {}: usize = {};\n", name, index));
+        }
+
+        code.push_str(DRIVER);
+
+        code.push_str(
+            "\n    #[allow(unused_variables)]\n    fn run_action(&mut self, rule: usize, lexeme: &'t str) -> Option<Token> {\n        let lexer = self;\n\n        return match rule {\n",
+        );
+        for (index, rule) in self.lex_file.rules.iter().enumerate() {
+            let action = rule.action.trim();
+            code.push_str(&format!(
+                "            // Line {}: {}\n",
+                rule.line, rule.pattern
+            ));
+            let arm = match action {
+                "" => format!("            {} => None,\n", index),
+                _ if action.starts_with('{') => {
+                    format!("            {} => {}\n", index, indent(action))
+                }
+                _ => format!("            {} => {},\n", index, action),
+            };
+            code.push_str(&arm);
+        }
+        code.push_str("            _ => None,\n        };\n    }\n}\n");
+        code.push_str(ITERATOR);
+    }
+}
+
+/// Returns the states of the regex set in the order they are reached from the start states,
+/// together with the number of every state in that order. The numbers only depend on the
+/// regexes, so the same lex file always generates the same code.
+pub(crate) fn number_states(
+    regex_set: &RegexSet,
+    entry_point_count: usize,
+) -> (Vec<usize>, HashMap<usize, usize>) {
+    let mut state_ids: HashMap<usize, usize> = HashMap::new();
+    let mut states = Vec::new();
+    let mut queue = VecDeque::new();
+
+    for entry_point in 0..entry_point_count {
+        let start_state = regex_set.get_start_state(entry_point);
+        if let Entry::Vacant(entry) = state_ids.entry(start_state) {
+            entry.insert(states.len());
+            states.push(start_state);
+            queue.push_back(start_state);
+        }
+    }
+    while let Some(state) = queue.pop_front() {
+        for (_, _, next_state) in regex_set.get_transitions(state) {
+            if let Entry::Vacant(entry) = state_ids.entry(next_state) {
+                entry.insert(states.len());
+                states.push(next_state);
+                queue.push_back(next_state);
+            }
+        }
+    }
+
+    return (states, state_ids);
+}
+
+fn write_state_machine(code: &mut String, regex_set: &RegexSet, entry_point_count: usize) {
+    let (states, state_ids) = number_states(regex_set, entry_point_count);
+
+    let start_states: Vec<String> = (0..entry_point_count)
+        .map(|entry_point| state_ids[&regex_set.get_start_state(entry_point)].to_string())
+        .collect();
+    code.push_str(&format!(
+        "\nconst START_STATES: [usize; {}] = [{}];\n",
+        entry_point_count,
+        start_states.join(", ")
+    ));
+
+    // The ranges of a state can cover all characters, which makes its last arm unreachable.
+    code.push_str(
+        "\n#[allow(unreachable_patterns)]\nfn get_next_state(state: usize, character: char) -> Option<usize> {\n    return match state {\n",
+    );
+    for (state_id, state) in states.iter().enumerate() {
+        let transitions = regex_set.get_transitions(*state);
+        if transitions.is_empty() {
+            continue;
+        }
+
+        // Ranges leading to the same state share an arm, in the order of their first range.
+        let mut arms: Vec<(usize, Vec<String>)> = Vec::new();
+        for (start, end, next_state) in transitions {
+            let next_state_id = state_ids[&next_state];
+            let pattern = match start == end {
+                true => format!("{:?}", start),
+                false => format!("{:?}..={:?}", start, end),
+            };
+
+            match arms.iter_mut().find(|(id, _)| *id == next_state_id) {
+                Some((_, patterns)) => patterns.push(pattern),
+                None => arms.push((next_state_id, vec![pattern])),
+            }
+        }
+
+        code.push_str(&format!("        {} => match character {{\n", state_id));
+        for (next_state_id, patterns) in arms {
+            code.push_str(&format!(
+                "            {} => Some({}),\n",
+                patterns.join(" | "),
+                next_state_id
+            ));
+        }
+        code.push_str("            _ => None,\n        },\n");
+    }
+    code.push_str("        _ => None,\n    };\n}\n");
+
+    // States accepting the same rule share an arm.
+    let mut accepted_rules: Vec<(usize, Vec<String>)> = Vec::new();
+    for (state_id, state) in states.iter().enumerate() {
+        if let Some(rule) = regex_set.get_accepted_regex(*state) {
+            match accepted_rules.iter_mut().find(|(index, _)| *index == rule) {
+                Some((_, state_ids)) => state_ids.push(state_id.to_string()),
+                None => accepted_rules.push((rule, vec![state_id.to_string()])),
+            }
+        }
+    }
+    accepted_rules.sort();
+
+    code.push_str(
+        "\nfn get_accepted_rule(state: usize) -> Option<usize> {\n    return match state {\n",
+    );
+    for (rule, state_ids) in accepted_rules {
+        code.push_str(&format!(
+            "        {} => Some({}),\n",
+            state_ids.join(" | "),
+            rule
+        ));
+    }
+    code.push_str("        _ => None,\n    };\n}\n");
+}
+
+fn write_compressed_state_machine(
+    code: &mut String,
+    regex_set: &RegexSet,
+    entry_point_count: usize,
+) {
+    let table = CompressedTable::new(regex_set, entry_point_count);
+
+    let start_states: Vec<String> = (0..entry_point_count)
+        .map(|entry_point| table.get_start_state(entry_point).to_string())
+        .collect();
+    code.push_str(&format!(
+        "\nconst START_STATES: [usize; {}] = [{}];\n",
+        entry_point_count,
+        start_states.join(", ")
+    ));
+    table.write_rust(code);
+}
+
+/// Indents the following lines of a multi-line action to the level of the match arm.
+fn indent(action: &str) -> String {
+    return action.lines().collect::<Vec<&str>>().join("\n            ");
+}
+
+const DRIVER: &str = "
+/// The part of the lexed text a token was created from. `start` and `end` are byte offsets,
+/// `line` and `column` are the position of the start, both counted from 1.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Self {
+        Span {
+            start,
+            end,
+            line,
+            column,
+        }
+    }
+}
+
+impl std::fmt::Display for Span {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        return write!(formatter, \"{}:{}\", self.line, self.column);
+    }
+}
+
+/// A token together with the text it was created from and where that text is.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenMatch<'t> {
+    pub token: Token,
+    pub lexeme: &'t str,
+    pub span: Span,
+}
+
+/// No rule matches the text at the start of `span`, which covers the first character.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LexError {
+    pub span: Span,
+}
+
+/// Tabs advance the column to the next multiple of the tab width.
+const TAB_WIDTH: usize = 8;
+
+/// Counts lines and columns while the text is lexed from the start to the end. `\\n`, `\\r\\n`
+/// and a single `\\r` all end a line.
+struct LineCounter {
+    offset: usize,
+    line: usize,
+    column: usize,
+    is_after_carriage_return: bool,
+}
+
+impl LineCounter {
+    fn get_span(&mut self, text: &str, start: usize, end: usize) -> Span {
+        self.advance_to(text, start);
+        let span = Span::new(start, end, self.line, self.column);
+        self.advance_to(text, end);
+
+        return span;
+    }
+
+    fn advance_to(&mut self, text: &str, offset: usize) {
+        for character in text[self.offset..offset].chars() {
+            match character {
+                '\\n' if self.is_after_carriage_return => {}
+                '\\n' | '\\r' => {
+                    self.line += 1;
+                    self.column = 1;
+                }
+                '\\t' => self.column += TAB_WIDTH - (self.column - 1) % TAB_WIDTH,
+                _ => self.column += 1,
+            }
+
+            self.is_after_carriage_return = character == '\\r';
+        }
+        self.offset = offset;
+    }
+}
+
+/// Iterates over the tokens of a text. The iterator stops after the first error, unless it
+/// recovers from errors.
+pub struct Lexer<'t> {
+    text: &'t str,
+    position: Option<usize>,
+    start_condition: usize,
+    start_condition_stack: Vec<usize>,
+    error_token: Option<fn(&'t str) -> Token>,
+    line_counter: LineCounter,
+}
+
+#[allow(dead_code)]
+impl<'t> Lexer<'t> {
+    pub fn new(text: &'t str) -> Self {
+        Lexer {
+            text,
+            position: Some(0),
+            start_condition: INITIAL,
+            start_condition_stack: Vec::new(),
+            error_token: None,
+            line_counter: LineCounter {
+                offset: 0,
+                line: 1,
+                column: 1,
+                is_after_carriage_return: false,
+            },
+        }
+    }
+
+    /// Keeps lexing after errors. All text up to the next position where a rule matches becomes
+    /// a single token created by `error_token`.
+    pub fn recover_from_errors(mut self, error_token: fn(&'t str) -> Token) -> Self {
+        self.error_token = Some(error_token);
+
+        return self;
+    }
+
+    pub fn begin(&mut self, start_condition: usize) {
+        self.start_condition = start_condition;
+    }
+
+    pub fn push_state(&mut self, start_condition: usize) {
+        self.start_condition_stack.push(self.start_condition);
+        self.start_condition = start_condition;
+    }
+
+    pub fn pop_state(&mut self) {
+        match self.start_condition_stack.pop() {
+            Some(start_condition) => self.start_condition = start_condition,
+            None => panic!(\"Start condition stack underflow\"),
+        }
+    }
+
+    pub fn current_state(&self) -> usize {
+        return self.start_condition;
+    }
+
+    fn get_longest_match(&self, start: usize) -> Option<(usize, usize)> {
+        let mut state = START_STATES[self.start_condition];
+        let mut longest_match = get_accepted_rule(state).map(|rule| (rule, start));
+
+        for (offset, character) in self.text[start..].char_indices() {
+            state = match get_next_state(state, character) {
+                Some(next_state) => next_state,
+                None => break,
+            };
+
+            if let Some(rule) = get_accepted_rule(state) {
+                longest_match = Some((rule, start + offset + character.len_utf8()));
+            }
+        }
+
+        return longest_match;
+    }
+
+    /// Returns the end of the unmatched text starting at `start`. Every position after `start`
+    /// starts a thread in the state machine, threads reaching the same state are merged into the
+    /// one which started first, so the text is read only once.
+    fn find_next_token_start(&self, start: usize) -> usize {
+        let start_state = START_STATES[self.start_condition];
+        let first_length = self.text[start..].chars().next().map_or(0, char::len_utf8);
+        // Pairs of the current state and the start of the thread, ordered by their starts.
+        let mut threads: Vec<(usize, usize)> = Vec::new();
+        let mut token_start: Option<usize> = None;
+
+        for (offset, character) in self.text[start + first_length..].char_indices() {
+            if token_start.is_none() {
+                threads.push((start_state, start + first_length + offset));
+            }
+
+            let mut next_threads: Vec<(usize, usize)> = Vec::with_capacity(threads.len());
+            for (state, thread_start) in threads {
+                let next_state = match get_next_state(state, character) {
+                    Some(next_state) => next_state,
+                    None => continue,
+                };
+                if next_threads.iter().any(|(state, _)| *state == next_state) {
+                    continue;
+                }
+
+                next_threads.push((next_state, thread_start));
+                if get_accepted_rule(next_state).is_some()
+                    && token_start.is_none_or(|token_start| thread_start < token_start)
+                {
+                    token_start = Some(thread_start);
+                }
+            }
+            threads = next_threads;
+
+            if let Some(token_start) = token_start {
+                threads.retain(|(_, thread_start)| *thread_start < token_start);
+                if threads.is_empty() {
+                    return token_start;
+                }
+            }
+        }
+
+        return token_start.unwrap_or(self.text.len());
+    }
+";
+
+const ITERATOR: &str = "
+impl<'t> Iterator for Lexer<'t> {
+    type Item = Result<TokenMatch<'t>, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let start = self.position?;
+            if start == self.text.len() {
+                self.position = None;
+                return None;
+            }
+
+            // Empty matches would never advance the lexer, they are treated like no match at all.
+            match self.get_longest_match(start) {
+                Some((rule, end)) if end > start => {
+                    self.position = Some(end);
+                    let lexeme = &self.text[start..end];
+                    // Skipped text is counted as well, the line counter has to see all line
+                    // breaks.
+                    let span = self.line_counter.get_span(self.text, start, end);
+
+                    if let Some(token) = self.run_action(rule, lexeme) {
+                        return Some(Ok(TokenMatch {
+                            token,
+                            lexeme,
+                            span,
+                        }));
+                    }
+                }
+                _ => {
+                    let error_token = match self.error_token {
+                        Some(error_token) => error_token,
+                        None => {
+                            self.position = None;
+                            let end = start
+                                + self.text[start..].chars().next().map_or(0, char::len_utf8);
+
+                            return Some(Err(LexError {
+                                span: self.line_counter.get_span(self.text, start, end),
+                            }));
+                        }
+                    };
+
+                    let end = self.find_next_token_start(start);
+                    self.position = Some(end);
+                    let lexeme = &self.text[start..end];
+
+                    return Some(Ok(TokenMatch {
+                        token: error_token(lexeme),
+                        lexeme,
+                        span: self.line_counter.get_span(self.text, start, end),
+                    }));
+                }
+            }
+        }
+    }
+}
+";
@@ -0,0 +1,398 @@
+use crate::codegen::number_states;
+use regex::RegexSet;
+use std::collections::HashMap;
+use std::fmt;
+
+/// The first and last code point of the surrogates, which are no characters.
+const SURROGATES: (u32, u32) = (0xD800, 0xDFFF);
+
+/// The next states of a state, with a column per equivalence class.
+type Row = Vec<Option<usize>>;
+
+/// The transition table of a lexer, compressed like the `yy_base`, `yy_def`, `yy_nxt` and
+/// `yy_chk` tables of flex.
+///
+/// Characters which lead to the same states everywhere share an equivalence class, so that the
+/// table only needs a column per class instead of per character. The rows of the table are then
+/// packed into the single vector `next` with row displacement: every state has a `base` offset
+/// and its entry for a class is `next[base + class]`, if `check` at the same index names the
+/// state. Otherwise the row of its `default` state is used, which lets states store only the
+/// entries in which they differ from a similar state.
+#[derive(Clone, Debug)]
+pub struct CompressedTable {
+    /// Sorted ranges of characters together with their class. Characters without any transition
+    /// have no class.
+    class_ranges: Vec<(char, char, usize)>,
+    class_count: usize,
+    start_states: Vec<usize>,
+    accepted_rules: Vec<Option<usize>>,
+    base: Vec<usize>,
+    default: Vec<Option<usize>>,
+    /// `None` in `next` is an entry without transition, which overrides the default state.
+    next: Vec<Option<usize>>,
+    /// `None` in `check` is an unused entry.
+    check: Vec<Option<usize>>,
+}
+impl CompressedTable {
+    /// States are numbered in the order they are reached from the start states of the entry
+    /// points, like in the code of `RustLexerGenerator`.
+    pub fn new(regex_set: &RegexSet, entry_point_count: usize) -> Self {
+        let (states, state_ids) = number_states(regex_set, entry_point_count);
+        let transitions: Vec<Vec<(u32, u32, usize)>> = states
+            .iter()
+            .map(|state| {
+                regex_set
+                    .get_transitions(*state)
+                    .into_iter()
+                    .map(|(start, end, next_state)| {
+                        (start as u32, end as u32, state_ids[&next_state])
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let (class_ranges, rows) = get_equivalence_classes(&transitions);
+        let class_count = rows.first().map_or(0, |row| row.len());
+
+        let mut table = CompressedTable {
+            class_ranges,
+            class_count,
+            start_states: (0..entry_point_count)
+                .map(|entry_point| state_ids[&regex_set.get_start_state(entry_point)])
+                .collect(),
+            accepted_rules: states
+                .iter()
+                .map(|state| regex_set.get_accepted_regex(*state))
+                .collect(),
+            base: Vec::with_capacity(states.len()),
+            default: Vec::with_capacity(states.len()),
+            next: Vec::new(),
+            check: Vec::new(),
+        };
+        for state in 0..rows.len() {
+            table.add_row(state, &rows);
+        }
+
+        return table;
+    }
+
+    /// Stores the row of the state, either completely or as the differences to the most similar
+    /// row which is already stored.
+    fn add_row(&mut self, state: usize, rows: &[Row]) {
+        let row = &rows[state];
+        let mut entries: Vec<(usize, Option<usize>)> = row
+            .iter()
+            .enumerate()
+            .filter(|(_, next_state)| next_state.is_some())
+            .map(|(class, next_state)| (class, *next_state))
+            .collect();
+        let mut default = None;
+
+        for (other_state, other_row) in rows[..state].iter().enumerate() {
+            let differences: Vec<(usize, Option<usize>)> = row
+                .iter()
+                .zip(other_row)
+                .enumerate()
+                .filter(|(_, (next_state, other_next_state))| next_state != other_next_state)
+                .map(|(class, (next_state, _))| (class, *next_state))
+                .collect();
+
+            // A default state makes lookups slower, so it has to save entries.
+            if differences.len() < entries.len() {
+                entries = differences;
+                default = Some(other_state);
+            }
+        }
+
+        let base = self.find_free_base(&entries);
+        for (class, next_state) in entries {
+            let index = base + class;
+            if index >= self.next.len() {
+                self.next.resize(index + 1, None);
+                self.check.resize(index + 1, None);
+            }
+            self.next[index] = next_state;
+            self.check[index] = Some(state);
+        }
+        self.base.push(base);
+        self.default.push(default);
+    }
+
+    /// Returns the first offset at which all entries fit into unused places of `next`.
+    fn find_free_base(&self, entries: &[(usize, Option<usize>)]) -> usize {
+        let mut base = 0;
+        loop {
+            let fits = entries
+                .iter()
+                .all(|(class, _)| !matches!(self.check.get(base + class), Some(Some(_))));
+            if fits {
+                return base;
+            }
+
+            base += 1;
+        }
+    }
+
+    /// Returns the equivalence class of the character, or `None` if no state has a transition
+    /// for it.
+    pub fn get_class(&self, character: char) -> Option<usize> {
+        return get_class(&self.class_ranges, character);
+    }
+
+    pub fn get_start_state(&self, entry_point: usize) -> usize {
+        return self.start_states[entry_point];
+    }
+
+    pub fn get_next_state(&self, state: usize, character: char) -> Option<usize> {
+        let class = self.get_class(character)?;
+        let mut state = state;
+
+        loop {
+            let index = self.base[state] + class;
+            if self.check.get(index) == Some(&Some(state)) {
+                return self.next[index];
+            }
+
+            state = self.default[state]?;
+        }
+    }
+
+    pub fn get_accepted_rule(&self, state: usize) -> Option<usize> {
+        return self.accepted_rules[state];
+    }
+
+    pub fn get_size_report(&self) -> TableSizeReport {
+        return TableSizeReport {
+            state_count: self.base.len(),
+            class_count: self.class_count,
+            class_range_count: self.class_ranges.len(),
+            dense_entry_count: self.base.len() * self.class_count,
+            compressed_entry_count: self.base.len() + self.default.len() + self.next.len() * 2,
+        };
+    }
+
+    /// Writes the tables as Rust constants together with the functions `get_next_state` and
+    /// `get_accepted_rule`, which look up states in them.
+    pub(crate) fn write_rust(&self, code: &mut String) {
+        code.push_str(&format!(
+            "\n// {}\nconst NO_STATE: usize = usize::MAX;\n",
+            self.get_size_report().to_string().replace('\n', "\n// ")
+        ));
+
+        let class_ranges: Vec<String> = self
+            .class_ranges
+            .iter()
+            .map(|(start, end, class)| format!("({:?}, {:?}, {})", start, end, class))
+            .collect();
+        write_array(code, "CLASS_RANGES", "(char, char, usize)", &class_ranges);
+
+        let to_strings = |values: &[Option<usize>]| -> Vec<String> {
+            return values
+                .iter()
+                .map(|value| match value {
+                    Some(value) => value.to_string(),
+                    None => String::from("NO_STATE"),
+                })
+                .collect();
+        };
+        let base: Vec<String> = self.base.iter().map(|base| base.to_string()).collect();
+        write_array(code, "BASE", "usize", &base);
+        write_array(code, "DEFAULT", "usize", &to_strings(&self.default));
+        write_array(code, "NEXT", "usize", &to_strings(&self.next));
+        write_array(code, "CHECK", "usize", &to_strings(&self.check));
+
+        let accepted_rules: Vec<String> = self
+            .accepted_rules
+            .iter()
+            .map(|rule| format!("{:?}", rule))
+            .collect();
+        write_array(code, "ACCEPTED_RULES", "Option<usize>", &accepted_rules);
+
+        code.push_str(LOOKUP);
+    }
+}
+
+/// The sizes of a compressed transition table compared to a dense table with a column for every
+/// equivalence class. Entry counts include all vectors of the table.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TableSizeReport {
+    pub state_count: usize,
+    pub class_count: usize,
+    pub class_range_count: usize,
+    pub dense_entry_count: usize,
+    pub compressed_entry_count: usize,
+}
+
+impl fmt::Display for TableSizeReport {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        return write!(
+            formatter,
+            "{} states, {} character classes in {} ranges\ndense table: {} entries\ncompressed table: {} entries ({:.1}%)",
+            self.state_count,
+            self.class_count,
+            self.class_range_count,
+            self.dense_entry_count,
+            self.compressed_entry_count,
+            match self.dense_entry_count {
+                0 => 100.0,
+                _ => self.compressed_entry_count as f64 * 100.0 / self.dense_entry_count as f64,
+            }
+        );
+    }
+}
+
+fn get_class(class_ranges: &[(char, char, usize)], character: char) -> Option<usize> {
+    let index = class_ranges
+        .binary_search_by(|(start, end, _)| {
+            if *end < character {
+                std::cmp::Ordering::Less
+            } else if *start > character {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .ok()?;
+
+    return Some(class_ranges[index].2);
+}
+
+/// Splits the characters into the smallest intervals in which every state has the same
+/// transition for all characters, and gives intervals with the same transitions in all states
+/// the same class. Returns the class ranges and the rows of the table, with a column per class.
+fn get_equivalence_classes(
+    transitions: &[Vec<(u32, u32, usize)>],
+) -> (Vec<(char, char, usize)>, Vec<Row>) {
+    let mut boundaries: Vec<u32> = transitions
+        .iter()
+        .flatten()
+        .flat_map(|(start, end, _)| vec![*start, end + 1])
+        .collect();
+    boundaries.sort_unstable();
+    boundaries.dedup();
+
+    let mut classes: HashMap<Vec<Option<usize>>, usize> = HashMap::new();
+    let mut columns: Vec<Vec<Option<usize>>> = Vec::new();
+    let mut class_ranges: Vec<(char, char, usize)> = Vec::new();
+
+    for interval in boundaries.windows(2) {
+        let (start, end) = match to_char_range(interval[0], interval[1] - 1) {
+            Some(range) => range,
+            None => continue,
+        };
+
+        let column: Vec<Option<usize>> = transitions
+            .iter()
+            .map(|state_transitions| get_next_state(state_transitions, start as u32))
+            .collect();
+        if column.iter().all(|next_state| next_state.is_none()) {
+            continue;
+        }
+
+        let class = match classes.get(&column) {
+            Some(class) => *class,
+            None => {
+                let class = columns.len();
+                classes.insert(column.clone(), class);
+                columns.push(column);
+                class
+            }
+        };
+
+        match class_ranges.last_mut() {
+            Some((_, last_end, last_class))
+                if *last_class == class && (*last_end as u32) + 1 == start as u32 =>
+            {
+                *last_end = end
+            }
+            _ => class_ranges.push((start, end, class)),
+        }
+    }
+
+    let rows = (0..transitions.len())
+        .map(|state| columns.iter().map(|column| column[state]).collect())
+        .collect();
+
+    return (class_ranges, rows);
+}
+
+fn get_next_state(transitions: &[(u32, u32, usize)], character: u32) -> Option<usize> {
+    return transitions
+        .iter()
+        .find(|(start, end, _)| *start <= character && character <= *end)
+        .map(|(_, _, next_state)| *next_state);
+}
+
+/// Returns the characters of the code points from `start` to `end` without the surrogates at
+/// their ends, or `None` if there are none.
+fn to_char_range(start: u32, end: u32) -> Option<(char, char)> {
+    let start = match start >= SURROGATES.0 && start <= SURROGATES.1 {
+        true => SURROGATES.1 + 1,
+        false => start,
+    };
+    let end = match end >= SURROGATES.0 && end <= SURROGATES.1 {
+        true => SURROGATES.0 - 1,
+        false => end,
+    };
+    if start > end {
+        return None;
+    }
+
+    return Some((std::char::from_u32(start)?, std::char::from_u32(end)?));
+}
+
+fn write_array(code: &mut String, name: &str, item_type: &str, values: &[String]) {
+    code.push_str(&format!(
+        "\n#[rustfmt::skip]\nconst {}: [{}; {}] = [\n",
+        name,
+        item_type,
+        values.len()
+    ));
+    for line in values.chunks(8) {
+        code.push_str(&format!("    {},\n", line.join(", ")));
+    }
+    code.push_str("];\n");
+}
+
+const LOOKUP: &str = "
+fn get_class(character: char) -> Option<usize> {
+    let index = CLASS_RANGES
+        .binary_search_by(|(start, end, _)| {
+            if *end < character {
+                std::cmp::Ordering::Less
+            } else if *start > character {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .ok()?;
+
+    return Some(CLASS_RANGES[index].2);
+}
+
+/// Follows the default states until a row has an entry for the class of the character.
+fn get_next_state(state: usize, character: char) -> Option<usize> {
+    let class = get_class(character)?;
+    let mut state = state;
+
+    loop {
+        let index = BASE[state] + class;
+        if index < CHECK.len() && CHECK[index] == state {
+            return match NEXT[index] {
+                NO_STATE => None,
+                next_state => Some(next_state),
+            };
+        }
+
+        state = match DEFAULT[state] {
+            NO_STATE => return None,
+            default_state => default_state,
+        };
+    }
+}
+
+fn get_accepted_rule(state: usize) -> Option<usize> {
+    return ACCEPTED_RULES[state];
+}
+";
@@ -0,0 +1,73 @@
+use crate::UnescapeError;
+use std::char;
+
+/// Returns the value of a string literal in double or single quotes, e.g. for the action of a
+/// `rule_with_conversion`. The escape sequences `\n`, `\r`, `\t`, `\0`, `\\`, `\"`, `\'` and
+/// `\u{...}` with up to six hex digits are replaced by their characters.
+pub fn unescape_string(lexeme: &str) -> Result<String, UnescapeError> {
+    let quote = match lexeme.chars().next() {
+        Some(quote) if quote == '"' || quote == '\'' => quote,
+        _ => return Err(UnescapeError::MissingQuotes),
+    };
+    if lexeme.len() < 2 || !lexeme.ends_with(quote) {
+        return Err(UnescapeError::MissingQuotes);
+    }
+
+    // Offsets are counted in the literal, which starts with the quote.
+    let end = lexeme.len() - 1;
+    let mut value = String::with_capacity(end);
+    let mut position = 1;
+
+    while position < end {
+        let character = lexeme[position..].chars().next().unwrap();
+        if character != '\\' {
+            value.push(character);
+            position += character.len_utf8();
+            continue;
+        }
+
+        let escaped = match lexeme[position + 1..end].chars().next() {
+            Some(escaped) => escaped,
+            None => return Err(UnescapeError::UnterminatedEscape(position)),
+        };
+        let (unescaped, length) = match escaped {
+            'n' => ('\n', 2),
+            'r' => ('\r', 2),
+            't' => ('\t', 2),
+            '0' => ('\0', 2),
+            '\\' | '"' | '\'' => (escaped, 2),
+            'u' => match get_unicode_escape(&lexeme[position..end]) {
+                Some(unicode_escape) => unicode_escape,
+                None => return Err(UnescapeError::InvalidUnicodeEscape(position)),
+            },
+            _ => return Err(UnescapeError::UnknownEscape(escaped, position)),
+        };
+
+        value.push(unescaped);
+        position += length;
+    }
+
+    return Ok(value);
+}
+
+/// Returns the character of the `\u{...}` escape at the start of the text together with the
+/// length of the escape.
+fn get_unicode_escape(text: &str) -> Option<(char, usize)> {
+    if !text.starts_with("\\u{") {
+        return None;
+    }
+
+    let end = text.find('}')?;
+    let digits = &text[3..end];
+    // `from_str_radix` would also accept a leading sign, like in `\u{+41}`.
+    if digits.is_empty()
+        || digits.len() > 6
+        || !digits.chars().all(|digit| digit.is_ascii_hexdigit())
+    {
+        return None;
+    }
+
+    let code_point = u32::from_str_radix(digits, 16).ok()?;
+
+    return char::from_u32(code_point).map(|character| (character, end + 1));
+}
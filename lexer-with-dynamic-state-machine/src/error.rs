@@ -0,0 +1,270 @@
+use crate::Span;
+use regex::RegexError;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Errors in the rules of a lexer, which are found while building it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SpecError {
+    Regex(RegexError),
+    /// A rule is active in a start condition which was never declared.
+    UnknownStartCondition(String),
+}
+
+impl From<RegexError> for SpecError {
+    fn from(error: RegexError) -> Self {
+        return SpecError::Regex(error);
+    }
+}
+
+impl fmt::Display for SpecError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            SpecError::Regex(error) => write!(formatter, "{}", error),
+            SpecError::UnknownStartCondition(name) => {
+                write!(formatter, "The start condition {} was never declared", name)
+            }
+        };
+    }
+}
+
+impl Error for SpecError {}
+
+/// No rule matches the text of the span. Usually this is only the first character which could
+/// not be matched, when recovering from errors it is all text up to the next token.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LexError {
+    pub span: Span,
+    pub text: String,
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        return write!(
+            formatter,
+            "No rule matches the text {:?} at {}",
+            self.text, self.span
+        );
+    }
+}
+
+impl Error for LexError {}
+
+/// A rule matched, but its action could not convert the lexeme into the value of the token, e.g.
+/// a number which does not fit into its type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConversionError {
+    pub span: Span,
+    pub text: String,
+    pub message: String,
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        return write!(
+            formatter,
+            "The text {:?} can not be converted: {} (at {})",
+            self.text, self.message, self.span
+        );
+    }
+}
+
+impl Error for ConversionError {}
+
+/// Errors while lexing a text.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TokenError {
+    Lex(LexError),
+    Conversion(ConversionError),
+    /// The text ends in a comment, the span is the delimiter which opened it.
+    UnterminatedComment(Span),
+}
+
+impl From<LexError> for TokenError {
+    fn from(error: LexError) -> Self {
+        return TokenError::Lex(error);
+    }
+}
+
+impl From<ConversionError> for TokenError {
+    fn from(error: ConversionError) -> Self {
+        return TokenError::Conversion(error);
+    }
+}
+
+impl fmt::Display for TokenError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            TokenError::Lex(error) => write!(formatter, "{}", error),
+            TokenError::Conversion(error) => write!(formatter, "{}", error),
+            TokenError::UnterminatedComment(span) => {
+                write!(formatter, "The comment is never closed (at {})", span)
+            }
+        };
+    }
+}
+
+impl Error for TokenError {}
+
+/// Errors in the escape sequences of a string literal. Offsets are byte offsets in the literal.
+#[derive(Clone, Debug, PartialEq)]
+pub enum UnescapeError {
+    /// The literal does not start and end with the same quote.
+    MissingQuotes,
+    UnknownEscape(char, usize),
+    /// A `\u{...}` escape without hex digits or with a value which is no character.
+    InvalidUnicodeEscape(usize),
+    /// The literal ends with a backslash.
+    UnterminatedEscape(usize),
+}
+
+impl fmt::Display for UnescapeError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            UnescapeError::MissingQuotes => {
+                write!(formatter, "The string literal is not enclosed in quotes")
+            }
+            UnescapeError::UnknownEscape(character, offset) => write!(
+                formatter,
+                "The escape sequence \\{} is unknown (at offset {})",
+                character, offset
+            ),
+            UnescapeError::InvalidUnicodeEscape(offset) => write!(
+                formatter,
+                "The unicode escape is no valid character (at offset {})",
+                offset
+            ),
+            UnescapeError::UnterminatedEscape(offset) => write!(
+                formatter,
+                "The escape sequence is never finished (at offset {})",
+                offset
+            ),
+        };
+    }
+}
+
+impl Error for UnescapeError {}
+
+/// Errors while lexing text from a reader. Text which is no valid UTF-8 is an `io::Error` of the
+/// kind `InvalidData`, like for `read_to_string`.
+#[derive(Debug)]
+pub enum StreamError {
+    Io(io::Error),
+    Lex(LexError),
+    Conversion(ConversionError),
+    /// The input ends in a comment, the span is the delimiter which opened it.
+    UnterminatedComment(Span),
+}
+
+impl From<io::Error> for StreamError {
+    fn from(error: io::Error) -> Self {
+        return StreamError::Io(error);
+    }
+}
+
+impl From<LexError> for StreamError {
+    fn from(error: LexError) -> Self {
+        return StreamError::Lex(error);
+    }
+}
+
+impl From<ConversionError> for StreamError {
+    fn from(error: ConversionError) -> Self {
+        return StreamError::Conversion(error);
+    }
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            StreamError::Io(error) => write!(formatter, "{}", error),
+            StreamError::Lex(error) => write!(formatter, "{}", error),
+            StreamError::Conversion(error) => write!(formatter, "{}", error),
+            StreamError::UnterminatedComment(span) => {
+                write!(formatter, "The comment is never closed (at {})", span)
+            }
+        };
+    }
+}
+
+impl Error for StreamError {}
+
+/// Errors in the format of a lex file. Lines are counted from 1.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LexFileError {
+    /// The file has no `%%` line which starts the rules section.
+    MissingRulesSection,
+    UnterminatedCodeBlock(usize),
+    UnterminatedComment(usize),
+    UnterminatedAction(usize),
+    /// A `<NAME>{` block is never closed with `}`.
+    UnterminatedStartConditionBlock(usize),
+    InvalidDefinition(usize),
+    UnknownDirective(String, usize),
+    /// The last rule uses the `|` action, but there is no next rule.
+    MissingNextAction(usize),
+}
+
+impl fmt::Display for LexFileError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            LexFileError::MissingRulesSection => {
+                write!(formatter, "The file has no %% line starting the rules")
+            }
+            LexFileError::UnterminatedCodeBlock(line) => {
+                write!(
+                    formatter,
+                    "The %{{ block is never closed (at line {})",
+                    line
+                )
+            }
+            LexFileError::UnterminatedComment(line) => {
+                write!(formatter, "The comment is never closed (at line {})", line)
+            }
+            LexFileError::UnterminatedAction(line) => {
+                write!(formatter, "The action is never closed (at line {})", line)
+            }
+            LexFileError::UnterminatedStartConditionBlock(line) => write!(
+                formatter,
+                "The start condition block is never closed (at line {})",
+                line
+            ),
+            LexFileError::InvalidDefinition(line) => {
+                write!(formatter, "The definition has no regex (at line {})", line)
+            }
+            LexFileError::UnknownDirective(directive, line) => write!(
+                formatter,
+                "The directive {} is not supported (at line {})",
+                directive, line
+            ),
+            LexFileError::MissingNextAction(line) => write!(
+                formatter,
+                "The rule shares the action of the next rule, but there is none (at line {})",
+                line
+            ),
+        };
+    }
+}
+
+impl Error for LexFileError {}
+
+/// The name was declared twice in the same scope.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RedeclarationError {
+    pub name: String,
+    pub span: Span,
+    pub previous_span: Span,
+}
+
+impl fmt::Display for RedeclarationError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        return write!(
+            formatter,
+            "{} is already declared at {} (at {})",
+            self.name, self.previous_span, self.span
+        );
+    }
+}
+
+impl Error for RedeclarationError {}
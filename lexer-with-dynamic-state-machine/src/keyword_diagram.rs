@@ -0,0 +1,282 @@
+/// What a state of a `KeywordDiagram` accepts. Keywords are given by their index in the keyword
+/// list.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeywordMatch {
+    Keyword(usize),
+    Identifier,
+}
+
+#[derive(Clone, Debug)]
+struct DiagramState {
+    /// The characters of the keywords which continue the prefix of this state, sorted.
+    transitions: Vec<(char, usize)>,
+    /// Whether all other identifier characters lead to the identifier state.
+    falls_back_to_identifier: bool,
+    accepted: Option<KeywordMatch>,
+}
+
+/// A transition diagram which recognizes keywords and identifiers, like the hand written state
+/// machine of `id_reserved_words_diagram`. The keywords form a trie, so keywords with the same
+/// prefix share their states. Every character which leaves the trie, but is still part of an
+/// identifier, leads to the identifier state.
+///
+/// The diagram can be used as a table with `get_next_state` and `get_accepted`, or turned into
+/// Rust code with `generate_rust`.
+#[derive(Clone, Debug)]
+pub struct KeywordDiagram {
+    keywords: Vec<String>,
+    identifier_start: Vec<(char, char)>,
+    identifier_part: Vec<(char, char)>,
+    states: Vec<DiagramState>,
+}
+impl KeywordDiagram {
+    pub const START_STATE: usize = 0;
+    pub const IDENTIFIER_STATE: usize = 1;
+
+    /// Identifiers start with a character of `identifier_start` and continue with characters of
+    /// `identifier_part`, both are lists of inclusive character ranges. The trie states are
+    /// numbered from 2 on in the order of the keywords.
+    pub fn new(
+        keywords: &[&str],
+        identifier_start: &[(char, char)],
+        identifier_part: &[(char, char)],
+    ) -> Self {
+        let mut diagram = KeywordDiagram {
+            keywords: Vec::new(),
+            identifier_start: identifier_start.to_vec(),
+            identifier_part: identifier_part.to_vec(),
+            states: Vec::new(),
+        };
+        diagram.add_state(false, None);
+        diagram.add_state(true, Some(KeywordMatch::Identifier));
+
+        for keyword in keywords {
+            if keyword.is_empty() || diagram.keywords.iter().any(|known| known == keyword) {
+                continue;
+            }
+
+            diagram.add_keyword(keyword);
+        }
+
+        return diagram;
+    }
+
+    fn add_keyword(&mut self, keyword: &str) {
+        let keyword_index = self.keywords.len();
+        self.keywords.push(keyword.to_string());
+
+        let mut state = KeywordDiagram::START_STATE;
+        for (offset, character) in keyword.char_indices() {
+            let existing_state = self.states[state]
+                .transitions
+                .iter()
+                .find(|(transition_character, _)| *transition_character == character)
+                .map(|(_, next_state)| *next_state);
+
+            state = match existing_state {
+                Some(next_state) => next_state,
+                None => {
+                    // Prefixes which are identifiers can still become any other identifier.
+                    let prefix = &keyword[..offset + character.len_utf8()];
+                    let is_identifier = self.is_identifier(prefix);
+                    let next_state = self.add_state(
+                        is_identifier,
+                        match is_identifier {
+                            true => Some(KeywordMatch::Identifier),
+                            false => None,
+                        },
+                    );
+
+                    let transitions = &mut self.states[state].transitions;
+                    transitions.push((character, next_state));
+                    transitions.sort();
+
+                    next_state
+                }
+            };
+        }
+
+        self.states[state].accepted = Some(KeywordMatch::Keyword(keyword_index));
+    }
+
+    fn add_state(
+        &mut self,
+        falls_back_to_identifier: bool,
+        accepted: Option<KeywordMatch>,
+    ) -> usize {
+        self.states.push(DiagramState {
+            transitions: Vec::new(),
+            falls_back_to_identifier,
+            accepted,
+        });
+
+        return self.states.len() - 1;
+    }
+
+    fn is_identifier(&self, text: &str) -> bool {
+        let mut characters = text.chars();
+
+        return match characters.next() {
+            Some(first) => {
+                contains(&self.identifier_start, first)
+                    && characters.all(|character| contains(&self.identifier_part, character))
+            }
+            None => false,
+        };
+    }
+
+    pub fn get_keywords(&self) -> &[String] {
+        return &self.keywords;
+    }
+
+    pub fn get_state_count(&self) -> usize {
+        return self.states.len();
+    }
+
+    pub fn get_next_state(&self, state: usize, character: char) -> Option<usize> {
+        let diagram_state = &self.states[state];
+
+        if let Some((_, next_state)) = diagram_state
+            .transitions
+            .iter()
+            .find(|(transition_character, _)| *transition_character == character)
+        {
+            return Some(*next_state);
+        }
+
+        let identifier_characters = match state {
+            KeywordDiagram::START_STATE => &self.identifier_start,
+            _ if diagram_state.falls_back_to_identifier => &self.identifier_part,
+            _ => return None,
+        };
+
+        return match contains(identifier_characters, character) {
+            true => Some(KeywordDiagram::IDENTIFIER_STATE),
+            false => None,
+        };
+    }
+
+    pub fn get_accepted(&self, state: usize) -> Option<KeywordMatch> {
+        return self.states[state].accepted;
+    }
+
+    /// Returns the longest keyword or identifier starting at the byte offset `start` together
+    /// with the end of the match.
+    pub fn longest_match_at(&self, text: &str, start: usize) -> Option<(KeywordMatch, usize)> {
+        let mut state = KeywordDiagram::START_STATE;
+        let mut longest_match = None;
+
+        for (offset, character) in text[start..].char_indices() {
+            state = match self.get_next_state(state, character) {
+                Some(next_state) => next_state,
+                None => break,
+            };
+
+            if let Some(accepted) = self.get_accepted(state) {
+                longest_match = Some((accepted, start + offset + character.len_utf8()));
+            }
+        }
+
+        return longest_match;
+    }
+
+    /// Generates Rust code with the keywords as `KEYWORDS`, the states `START_STATE` and
+    /// `IDENTIFIER_STATE` and the diagram as the functions `get_next_state`, `get_keyword`, which
+    /// returns the index of the keyword accepted in a state, and `is_identifier`.
+    pub fn generate_rust(&self) -> String {
+        let mut code =
+            String::from("// Generated by lexer-with-dynamic-state-machine, do not edit.\n\n");
+
+        let keywords: Vec<String> = self
+            .keywords
+            .iter()
+            .map(|keyword| format!("{:?}", keyword))
+            .collect();
+        code.push_str(&format!(
+            "pub const KEYWORDS: [&str; {}] = [{}];\n",
+            keywords.len(),
+            keywords.join(", ")
+        ));
+        code.push_str(&format!(
+            "pub const START_STATE: usize = {};\npub const IDENTIFIER_STATE: usize = {};\n",
+            KeywordDiagram::START_STATE,
+            KeywordDiagram::IDENTIFIER_STATE
+        ));
+
+        let identifier_start = get_patterns(&self.identifier_start);
+        let identifier_part = get_patterns(&self.identifier_part);
+
+        // The identifier ranges can overlap the keyword characters, which makes them unreachable.
+        code.push_str(
+            "\n#[allow(unreachable_patterns)]\npub fn get_next_state(state: usize, character: char) -> Option<usize> {\n    return match state {\n",
+        );
+        for (state, diagram_state) in self.states.iter().enumerate() {
+            let identifier_characters = match state {
+                KeywordDiagram::START_STATE => identifier_start.as_str(),
+                _ if diagram_state.falls_back_to_identifier => identifier_part.as_str(),
+                _ => "",
+            };
+            if diagram_state.transitions.is_empty() && identifier_characters.is_empty() {
+                continue;
+            }
+
+            code.push_str(&format!("        {} => match character {{\n", state));
+            for (character, next_state) in &diagram_state.transitions {
+                code.push_str(&format!(
+                    "            {:?} => Some({}),\n",
+                    character, next_state
+                ));
+            }
+            if !identifier_characters.is_empty() {
+                code.push_str(&format!(
+                    "            {} => Some({}),\n",
+                    identifier_characters,
+                    KeywordDiagram::IDENTIFIER_STATE
+                ));
+            }
+            code.push_str("            _ => None,\n        },\n");
+        }
+        code.push_str("        _ => None,\n    };\n}\n");
+
+        code.push_str(
+            "\npub fn get_keyword(state: usize) -> Option<usize> {\n    return match state {\n",
+        );
+        let mut identifier_states = Vec::new();
+        for (state, diagram_state) in self.states.iter().enumerate() {
+            match diagram_state.accepted {
+                Some(KeywordMatch::Keyword(keyword)) => {
+                    code.push_str(&format!("        {} => Some({}),\n", state, keyword))
+                }
+                Some(KeywordMatch::Identifier) => identifier_states.push(state.to_string()),
+                None => {}
+            }
+        }
+        code.push_str("        _ => None,\n    };\n}\n");
+
+        code.push_str(&format!(
+            "\npub fn is_identifier(state: usize) -> bool {{\n    return matches!(state, {});\n}}\n",
+            identifier_states.join(" | ")
+        ));
+
+        return code;
+    }
+}
+
+fn contains(ranges: &[(char, char)], character: char) -> bool {
+    return ranges
+        .iter()
+        .any(|(start, end)| *start <= character && character <= *end);
+}
+
+/// Returns the ranges as a pattern of a match arm, or an empty string if there are no ranges.
+fn get_patterns(ranges: &[(char, char)]) -> String {
+    let patterns: Vec<String> = ranges
+        .iter()
+        .map(|(start, end)| match start == end {
+            true => format!("{:?}", start),
+            false => format!("{:?}..={:?}", start, end),
+        })
+        .collect();
+
+    return patterns.join(" | ");
+}
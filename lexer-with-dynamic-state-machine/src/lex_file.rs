@@ -0,0 +1,474 @@
+use crate::LexFileError;
+use crate::LexerActions;
+use crate::LexerSpec;
+
+/// A start condition declared with `%s` (inclusive) or `%x` (exclusive).
+#[derive(Clone, Debug, PartialEq)]
+pub struct StartCondition {
+    pub name: String,
+    pub is_exclusive: bool,
+}
+
+/// A rule of the rules section. The action is the C code of the lex file as written, e.g.
+/// `{return (GT);}`, or an empty string for rules without action.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LexFileRule {
+    /// An empty list means the rule has no start conditions.
+    pub start_conditions: Vec<String>,
+    pub pattern: String,
+    pub action: String,
+    pub line: usize,
+}
+
+/// A parsed lex file, like the ones flex reads:
+///
+/// ```text
+/// definitions
+/// %%
+/// rules
+/// %%
+/// user code
+/// ```
+///
+/// Patterns are kept as they are written and are handed to the regex engine unchanged, therefore
+/// they can only use the syntax the engine supports.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LexFile {
+    /// The code of `%top{ }` blocks, which belongs before all other code.
+    pub top_code: String,
+    /// The code of `%{ %}` blocks and indented lines, in the order of the file.
+    pub declarations: String,
+    pub definitions: Vec<(String, String)>,
+    pub start_conditions: Vec<StartCondition>,
+    /// The values of all `%option` lines.
+    pub options: Vec<String>,
+    pub rules: Vec<LexFileRule>,
+    pub user_code: String,
+    /// Directives which are accepted for compatibility with flex, but have no effect.
+    pub warnings: Vec<String>,
+}
+impl LexFile {
+    pub fn parse(text: &str) -> Result<LexFile, LexFileError> {
+        let lines: Vec<&str> = text.lines().collect();
+        let mut lex_file = LexFile::default();
+        let mut index = 0;
+
+        index = lex_file.parse_definitions(&lines, index)?;
+        index = lex_file.parse_rules(&lines, index)?;
+        if index < lines.len() {
+            lex_file.user_code = lines[index..].join("\n");
+        }
+
+        return Ok(lex_file);
+    }
+
+    /// Creates a lexer spec with all definitions, start conditions and rules of the file. The
+    /// actions are written in C, `get_action` maps every rule to the Rust action which replaces
+    /// it.
+    pub fn to_lexer_spec<T, F, A>(&self, mut get_action: F) -> LexerSpec<T>
+    where
+        F: FnMut(&LexFileRule) -> A,
+        A: Fn(&mut LexerActions, &str) -> Option<T> + 'static,
+    {
+        let mut spec = LexerSpec::new();
+
+        for (name, regex) in &self.definitions {
+            spec = spec.define(name, regex);
+        }
+
+        for start_condition in &self.start_conditions {
+            spec = match start_condition.is_exclusive {
+                true => spec.exclusive_condition(&start_condition.name),
+                false => spec.inclusive_condition(&start_condition.name),
+            };
+        }
+
+        for rule in &self.rules {
+            let action = get_action(rule);
+
+            if rule.start_conditions.is_empty() {
+                spec = spec.rule_with_actions(&rule.pattern, action);
+            } else {
+                let start_conditions: Vec<&str> = rule
+                    .start_conditions
+                    .iter()
+                    .map(|start_condition| start_condition.as_str())
+                    .collect();
+                spec = spec.in_conditions(&start_conditions, |spec| {
+                    spec.rule_with_actions(&rule.pattern, action)
+                });
+            }
+        }
+
+        return spec;
+    }
+
+    /// Parses the definitions section and returns the index of the first line of the rules.
+    fn parse_definitions(
+        &mut self,
+        lines: &[&str],
+        mut index: usize,
+    ) -> Result<usize, LexFileError> {
+        while index < lines.len() {
+            let line = lines[index];
+
+            if line.trim_end() == "%%" {
+                return Ok(index + 1);
+            } else if line.trim_end() == "%{" {
+                index = self.parse_code_block(lines, index)?;
+                continue;
+            } else if line.starts_with("%top{") {
+                index = self.parse_top_block(lines, index)?;
+                continue;
+            } else if line.starts_with("/*") {
+                index = skip_comment(lines, index)?;
+                continue;
+            }
+
+            if line.trim().is_empty() {
+                // Empty lines separate definitions, they have no meaning.
+            } else if line.starts_with(char::is_whitespace) {
+                // Indented lines are code, like in flex.
+                self.add_declaration(line);
+            } else if line.starts_with('%') {
+                self.parse_directive(line, index + 1)?;
+            } else {
+                self.parse_definition(line, index + 1)?;
+            }
+
+            index += 1;
+        }
+
+        return Err(LexFileError::MissingRulesSection);
+    }
+
+    /// Parses the rules section and returns the index of the first line of the user code.
+    fn parse_rules(&mut self, lines: &[&str], mut index: usize) -> Result<usize, LexFileError> {
+        // The start conditions and first lines of all open `<NAME>{` blocks.
+        let mut blocks: Vec<(Vec<String>, usize)> = Vec::new();
+        let mut rules_sharing_action: Vec<LexFileRule> = Vec::new();
+
+        while index < lines.len() {
+            let line = lines[index];
+
+            if line.trim_end() == "%%" {
+                index += 1;
+                break;
+            } else if line.trim_end() == "%{" {
+                index = self.parse_code_block(lines, index)?;
+                continue;
+            } else if line.trim().is_empty() {
+                index += 1;
+                continue;
+            } else if !blocks.is_empty() && line.trim() == "}" {
+                blocks.pop();
+                index += 1;
+                continue;
+            } else if blocks.is_empty() && line.starts_with(char::is_whitespace) {
+                self.add_declaration(line);
+                index += 1;
+                continue;
+            }
+
+            let rule_line = index + 1;
+            let (mut start_conditions, pattern_and_action) =
+                split_start_conditions(line.trim_start());
+            for (block_start_conditions, _) in &blocks {
+                for start_condition in block_start_conditions {
+                    if !start_conditions.contains(start_condition) {
+                        start_conditions.push(start_condition.clone());
+                    }
+                }
+            }
+
+            if pattern_and_action.trim() == "{" {
+                blocks.push((start_conditions, rule_line));
+                index += 1;
+                continue;
+            }
+
+            let (pattern, action) = split_pattern(pattern_and_action);
+            let (action, next_index) = read_action(lines, index, action)?;
+            index = next_index;
+
+            let rule = LexFileRule {
+                start_conditions,
+                pattern: pattern.to_string(),
+                action,
+                line: rule_line,
+            };
+
+            // Rules with the action `|` share the action of the next rule.
+            if rule.action == "|" {
+                rules_sharing_action.push(rule);
+                continue;
+            }
+
+            for mut previous_rule in rules_sharing_action.drain(..) {
+                previous_rule.action = rule.action.clone();
+                self.rules.push(previous_rule);
+            }
+            self.rules.push(rule);
+        }
+
+        if let Some((_, line)) = blocks.pop() {
+            return Err(LexFileError::UnterminatedStartConditionBlock(line));
+        }
+        if let Some(rule) = rules_sharing_action.first() {
+            return Err(LexFileError::MissingNextAction(rule.line));
+        }
+
+        return Ok(index);
+    }
+
+    /// Adds the lines of the `%{` block starting at `index` to the declarations and returns the
+    /// index after the closing `%}`.
+    fn parse_code_block(&mut self, lines: &[&str], index: usize) -> Result<usize, LexFileError> {
+        let mut end = index + 1;
+
+        while end < lines.len() {
+            if lines[end].trim_end() == "%}" {
+                for line in &lines[index + 1..end] {
+                    self.add_declaration(line);
+                }
+
+                return Ok(end + 1);
+            }
+
+            end += 1;
+        }
+
+        return Err(LexFileError::UnterminatedCodeBlock(index + 1));
+    }
+
+    /// Adds the code of the `%top{` block starting at `index` to the top code and returns the
+    /// index after the closing `}`.
+    fn parse_top_block(&mut self, lines: &[&str], index: usize) -> Result<usize, LexFileError> {
+        let (block, next_index) = match read_action(lines, index, &lines[index]["%top".len()..]) {
+            Ok(block) => block,
+            Err(_) => return Err(LexFileError::UnterminatedCodeBlock(index + 1)),
+        };
+
+        // Only the text between the braces is code.
+        let code = block[1..block.rfind('}').unwrap()].trim_matches('\n');
+        if !code.trim().is_empty() {
+            if !self.top_code.is_empty() {
+                self.top_code.push('\n');
+            }
+            self.top_code.push_str(code);
+        }
+
+        return Ok(next_index);
+    }
+
+    fn add_declaration(&mut self, line: &str) {
+        if !self.declarations.is_empty() {
+            self.declarations.push('\n');
+        }
+        self.declarations.push_str(line);
+    }
+
+    fn parse_directive(&mut self, line: &str, line_number: usize) -> Result<(), LexFileError> {
+        let mut words = line.split_whitespace();
+        let directive = words.next().unwrap_or_default();
+
+        let is_exclusive = match directive {
+            "%s" | "%start" => false,
+            "%x" | "%exclusive" => true,
+            "%option" => {
+                self.options.extend(words.map(|option| option.to_string()));
+                return Ok(());
+            }
+            // Flex chooses the type of `yytext` with these, lexemes are always string slices.
+            "%array" | "%pointer" => {
+                self.warnings.push(format!(
+                    "The directive {} has no effect (at line {})",
+                    directive, line_number
+                ));
+                return Ok(());
+            }
+            _ => {
+                return Err(LexFileError::UnknownDirective(
+                    directive.to_string(),
+                    line_number,
+                ))
+            }
+        };
+
+        for name in words {
+            self.start_conditions.push(StartCondition {
+                name: name.to_string(),
+                is_exclusive,
+            });
+        }
+
+        return Ok(());
+    }
+
+    fn parse_definition(&mut self, line: &str, line_number: usize) -> Result<(), LexFileError> {
+        let name_end = line
+            .find(char::is_whitespace)
+            .ok_or(LexFileError::InvalidDefinition(line_number))?;
+        let regex = line[name_end..].trim();
+        if regex.is_empty() {
+            return Err(LexFileError::InvalidDefinition(line_number));
+        }
+
+        self.definitions
+            .push((line[..name_end].to_string(), regex.to_string()));
+
+        return Ok(());
+    }
+}
+
+/// Returns the index after the comment starting at `index`.
+fn skip_comment(lines: &[&str], index: usize) -> Result<usize, LexFileError> {
+    let mut end = index;
+    let mut text = &lines[index][2..];
+
+    loop {
+        if text.contains("*/") {
+            return Ok(end + 1);
+        }
+
+        end += 1;
+        if end == lines.len() {
+            return Err(LexFileError::UnterminatedComment(index + 1));
+        }
+        text = lines[end];
+    }
+}
+
+/// Splits a `<A,B>` prefix off the rule. `<*>` is kept as the start condition `*`.
+fn split_start_conditions(line: &str) -> (Vec<String>, &str) {
+    if !line.starts_with('<') {
+        return (Vec::new(), line);
+    }
+
+    let end = match line.find('>') {
+        Some(end) => end,
+        None => return (Vec::new(), line),
+    };
+    let start_conditions: Vec<String> = line[1..end]
+        .split(',')
+        .map(|start_condition| start_condition.trim().to_string())
+        .collect();
+
+    // Patterns like `<=` also start with `<`, only names are start conditions.
+    let is_start_condition_list = start_conditions.iter().all(|start_condition| {
+        start_condition == "*"
+            || (!start_condition.is_empty()
+                && start_condition
+                    .chars()
+                    .all(|character| character.is_alphanumeric() || character == '_'))
+    });
+    if !is_start_condition_list {
+        return (Vec::new(), line);
+    }
+
+    return (start_conditions, &line[end + 1..]);
+}
+
+/// Splits the rule at the first whitespace which is not quoted, escaped or part of a `[...]`
+/// group.
+fn split_pattern(rule: &str) -> (&str, &str) {
+    let mut is_quoted = false;
+    let mut is_in_group = false;
+    let mut is_escaped = false;
+
+    for (offset, character) in rule.char_indices() {
+        if is_escaped {
+            is_escaped = false;
+            continue;
+        }
+
+        match character {
+            '\\' => is_escaped = true,
+            '"' if !is_in_group => is_quoted = !is_quoted,
+            '[' if !is_quoted => is_in_group = true,
+            ']' if !is_quoted => is_in_group = false,
+            _ if character.is_whitespace() && !is_quoted && !is_in_group => {
+                return (&rule[..offset], rule[offset..].trim());
+            }
+            _ => {}
+        }
+    }
+
+    return (rule, "");
+}
+
+/// Reads the action which starts with `action` on the line at `index`. Actions in braces can span
+/// several lines. Returns the action and the index of the next line.
+fn read_action(
+    lines: &[&str],
+    index: usize,
+    action: &str,
+) -> Result<(String, usize), LexFileError> {
+    if !action.starts_with('{') {
+        return Ok((action.to_string(), index + 1));
+    }
+
+    let mut text = action.to_string();
+    let mut end = index;
+    loop {
+        if is_braced_block_closed(&text) {
+            return Ok((text.trim_end().to_string(), end + 1));
+        }
+
+        end += 1;
+        if end == lines.len() {
+            return Err(LexFileError::UnterminatedAction(index + 1));
+        }
+        text.push('\n');
+        text.push_str(lines[end]);
+    }
+}
+
+/// Returns whether the braced block at the start of the text is closed. Braces in string and
+/// character literals and in comments are ignored.
+fn is_braced_block_closed(text: &str) -> bool {
+    let mut depth = 0;
+    let mut quote = None;
+    let mut is_escaped = false;
+    let mut characters = text.chars().peekable();
+
+    while let Some(character) = characters.next() {
+        if is_escaped {
+            is_escaped = false;
+            continue;
+        }
+
+        match (quote, character) {
+            (Some(_), '\\') => is_escaped = true,
+            (Some(quote_character), _) if quote_character == character => quote = None,
+            (Some(_), _) => {}
+            (None, '/') if characters.peek() == Some(&'/') => {
+                // The line comment ends at the end of the line, which is not part of it.
+                while characters.peek().is_some_and(|next| *next != '\n') {
+                    characters.next();
+                }
+            }
+            (None, '/') if characters.peek() == Some(&'*') => {
+                characters.next();
+                let mut previous = ' ';
+                for next in characters.by_ref() {
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            (None, '"') | (None, '\'') => quote = Some(character),
+            (None, '{') => depth += 1,
+            (None, '}') => {
+                depth -= 1;
+                if depth == 0 {
+                    return true;
+                }
+            }
+            (None, _) => {}
+        }
+    }
+
+    return false;
+}
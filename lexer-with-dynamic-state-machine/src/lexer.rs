@@ -0,0 +1,357 @@
+use crate::lexer_spec::Action;
+use crate::CompressedTable;
+use crate::ConversionError;
+use crate::LexError;
+use crate::LineCounter;
+use crate::Span;
+use crate::StreamTokens;
+use crate::SymbolId;
+use crate::SymbolTable;
+use crate::TokenError;
+use regex::RegexAstElements;
+use regex::RegexSet;
+use std::io::Read;
+
+/// Creates the token for text which no rule matches.
+type ErrorToken<'l, T> = Box<dyn Fn(&str) -> T + 'l>;
+
+/// A token together with the text it was created from and where that text is.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token<'t, T> {
+    pub value: T,
+    pub lexeme: &'t str,
+    pub span: Span,
+}
+
+pub struct Lexer<T> {
+    pub(crate) regex_set: RegexSet,
+    pub(crate) actions: Vec<Action<T>>,
+    start_conditions: Vec<String>,
+}
+impl<T> Lexer<T> {
+    pub(crate) fn new(
+        asts: Vec<RegexAstElements>,
+        entry_points: Vec<Vec<usize>>,
+        actions: Vec<Action<T>>,
+        start_conditions: Vec<String>,
+    ) -> Self {
+        Lexer {
+            regex_set: RegexSet::with_entry_points(asts, entry_points),
+            actions,
+            start_conditions,
+        }
+    }
+
+    /// The number of states of the state machine of all rules.
+    pub fn get_state_count(&self) -> usize {
+        return self.regex_set.get_state_count();
+    }
+
+    /// The number of transitions of the state machine, see `RegexSet::get_transition_count`.
+    pub fn get_transition_count(&self) -> usize {
+        return self.regex_set.get_transition_count();
+    }
+
+    /// Returns the transition table of the state machine compressed with equivalence classes and
+    /// row displacement, e.g. to compare its size with the dense table.
+    pub fn compress_table(&self) -> CompressedTable {
+        return CompressedTable::new(&self.regex_set, self.start_conditions.len());
+    }
+
+    /// Returns an iterator over the tokens of the text. The iterator stops at the first text no
+    /// rule matches, unless `recover_from_errors` is used. Conversion errors never stop it.
+    pub fn tokens<'l, 't>(&'l self, text: &'t str) -> Tokens<'l, 't, T> {
+        Tokens {
+            lexer: self,
+            text,
+            position: Some(0),
+            error_token: None,
+            line_counter: LineCounter::new(),
+            actions: LexerActions::new(&self.start_conditions),
+        }
+    }
+
+    /// Like `tokens`, but reads the text from the reader while lexing, e.g. for files which are
+    /// too large to be read at once. The iterator stops after the first error which is no
+    /// conversion error.
+    pub fn stream_tokens<R: Read>(&self, reader: R) -> StreamTokens<'_, R, T> {
+        return StreamTokens::new(self, reader, LexerActions::new(&self.start_conditions));
+    }
+}
+
+/// Lets the actions of rules switch between start conditions, like `BEGIN`, `yy_push_state` and
+/// `yy_pop_state` in flex. The new start condition is used from the next match on.
+///
+/// Actions can also intern identifiers, so that tokens can hold a `SymbolId` instead of the name.
+pub struct LexerActions<'l> {
+    start_conditions: &'l [String],
+    pub(crate) current_start_condition: usize,
+    start_condition_stack: Vec<usize>,
+    symbol_table: SymbolTable,
+    /// The span of the lexeme the action is called for.
+    pub(crate) current_span: Span,
+    comment_depth: usize,
+    /// The span of the delimiter which opened the outermost comment.
+    comment_start: Span,
+}
+impl<'l> LexerActions<'l> {
+    fn new(start_conditions: &'l [String]) -> Self {
+        LexerActions {
+            start_conditions,
+            current_start_condition: 0,
+            start_condition_stack: Vec::new(),
+            symbol_table: SymbolTable::new(),
+            current_span: Span::new(0, 0, 1, 1),
+            comment_depth: 0,
+            comment_start: Span::new(0, 0, 1, 1),
+        }
+    }
+
+    /// Interns the name in the symbol table of the lexed text.
+    pub fn intern(&mut self, name: &str) -> SymbolId {
+        return self.symbol_table.intern(name);
+    }
+
+    pub fn symbol_table(&self) -> &SymbolTable {
+        return &self.symbol_table;
+    }
+
+    /// Switches to the start condition. Panics if the start condition was never declared.
+    pub fn begin(&mut self, start_condition: &str) {
+        self.current_start_condition = self.get_start_condition_index(start_condition);
+    }
+
+    /// Switches to the start condition and remembers the current one for `pop_state`.
+    pub fn push_state(&mut self, start_condition: &str) {
+        self.start_condition_stack
+            .push(self.current_start_condition);
+        self.begin(start_condition);
+    }
+
+    /// Switches back to the start condition before the last `push_state`. Panics if the stack is
+    /// empty, like flex does.
+    pub fn pop_state(&mut self) {
+        match self.start_condition_stack.pop() {
+            Some(start_condition) => self.current_start_condition = start_condition,
+            None => panic!("Start condition stack underflow"),
+        }
+    }
+
+    pub fn current_state(&self) -> &str {
+        return &self.start_conditions[self.current_start_condition];
+    }
+
+    /// Opens a comment, which can be nested in other comments. The outermost comment switches to
+    /// the start condition with `push_state`, so that the text of comments can be lexed with
+    /// different rules. Comments which are still open at the end of the text are reported as
+    /// `UnterminatedComment`.
+    pub fn open_comment(&mut self, start_condition: &str) {
+        if self.comment_depth == 0 {
+            self.comment_start = self.current_span;
+            self.push_state(start_condition);
+        }
+        self.comment_depth += 1;
+    }
+
+    /// Closes the innermost comment and switches back with `pop_state` after the outermost one.
+    /// Panics if no comment is open.
+    pub fn close_comment(&mut self) {
+        match self.comment_depth {
+            0 => panic!("No comment is open"),
+            1 => self.pop_state(),
+            _ => {}
+        }
+        self.comment_depth -= 1;
+    }
+
+    /// The number of comments which are open, 0 outside of comments.
+    pub fn get_comment_depth(&self) -> usize {
+        return self.comment_depth;
+    }
+
+    /// Returns the start of the open comment at the end of the text. It is only reported once.
+    pub(crate) fn take_unterminated_comment(&mut self) -> Option<Span> {
+        if self.comment_depth == 0 {
+            return None;
+        }
+        self.comment_depth = 0;
+
+        return Some(self.comment_start);
+    }
+
+    fn get_start_condition_index(&self, start_condition: &str) -> usize {
+        return match self
+            .start_conditions
+            .iter()
+            .position(|name| name == start_condition)
+        {
+            Some(index) => index,
+            None => panic!("The start condition {} was never declared", start_condition),
+        };
+    }
+}
+
+pub struct Tokens<'l, 't, T> {
+    lexer: &'l Lexer<T>,
+    text: &'t str,
+    position: Option<usize>,
+    /// Creates the error tokens for unmatched text, if the lexer recovers from errors.
+    error_token: Option<ErrorToken<'l, T>>,
+    line_counter: LineCounter,
+    actions: LexerActions<'l>,
+}
+impl<'l, 't, T> Tokens<'l, 't, T> {
+    /// Keeps lexing after errors. All text up to the next position where a rule matches becomes
+    /// a single token created by `error_token`, e.g. for an editor which has to show all tokens
+    /// of a broken file.
+    pub fn recover_from_errors<F: Fn(&str) -> T + 'l>(mut self, error_token: F) -> Self {
+        self.error_token = Some(Box::new(error_token));
+
+        return self;
+    }
+
+    /// Returns the start condition the next token is matched in.
+    pub fn current_state(&self) -> &str {
+        return self.actions.current_state();
+    }
+
+    /// The names interned by the actions so far.
+    pub fn symbol_table(&self) -> &SymbolTable {
+        return self.actions.symbol_table();
+    }
+
+    /// Returns the names interned by the actions, e.g. to share them with a `ScopedSymbolTable`
+    /// after the text was lexed.
+    pub fn into_symbol_table(self) -> SymbolTable {
+        return self.actions.symbol_table;
+    }
+
+    /// Returns the end of the unmatched text starting at `start`, which is the next position
+    /// where a token can start.
+    ///
+    /// The text is read only once: every position after `start` starts a thread in the state
+    /// machine, and threads which reach the same state are merged into the one which started
+    /// first. There are therefore never more threads than states. The first thread which accepts
+    /// a non-empty match is the next token start, once all threads which started before it died.
+    fn find_next_token_start(&self, start: usize) -> usize {
+        let regex_set = &self.lexer.regex_set;
+        let start_state = regex_set.get_start_state(self.actions.current_start_condition);
+        let first_length = self.text[start..].chars().next().map_or(0, char::len_utf8);
+        // Pairs of the current state and the start of the thread, ordered by their starts.
+        let mut threads: Vec<(usize, usize)> = Vec::new();
+        let mut token_start: Option<usize> = None;
+
+        for (offset, character) in self.text[start + first_length..].char_indices() {
+            if token_start.is_none() {
+                threads.push((start_state, start + first_length + offset));
+            }
+
+            let mut next_threads: Vec<(usize, usize)> = Vec::with_capacity(threads.len());
+            for (state, thread_start) in threads {
+                let next_state = match regex_set.get_next_state(state, character) {
+                    Some(next_state) => next_state,
+                    None => continue,
+                };
+                if next_threads.iter().any(|(state, _)| *state == next_state) {
+                    continue;
+                }
+
+                next_threads.push((next_state, thread_start));
+                if regex_set.get_accepted_regex(next_state).is_some()
+                    && token_start.is_none_or(|token_start| thread_start < token_start)
+                {
+                    token_start = Some(thread_start);
+                }
+            }
+            threads = next_threads;
+
+            if let Some(token_start) = token_start {
+                threads.retain(|(_, thread_start)| *thread_start < token_start);
+                if threads.is_empty() {
+                    return token_start;
+                }
+            }
+        }
+
+        return token_start.unwrap_or(self.text.len());
+    }
+}
+
+impl<'l, 't, T> Iterator for Tokens<'l, 't, T> {
+    type Item = Result<Token<'t, T>, TokenError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let start = self.position?;
+            if start == self.text.len() {
+                self.position = None;
+                return self
+                    .actions
+                    .take_unterminated_comment()
+                    .map(|span| Err(TokenError::UnterminatedComment(span)));
+            }
+
+            // Empty matches would never advance the lexer, they are treated like no match at all.
+            let found_match = self.lexer.regex_set.longest_match_from_entry_point(
+                self.actions.current_start_condition,
+                self.text,
+                start,
+            );
+            match found_match {
+                Some((rule_index, end)) if end > start => {
+                    self.position = Some(end);
+                    let lexeme = &self.text[start..end];
+                    // Spans are also counted for skipped text, the line counter has to see all
+                    // line breaks.
+                    let span = self.line_counter.get_span(self.text, start, end);
+                    self.actions.current_span = span;
+
+                    // Actions without a token, e.g. ones which only switch the start condition,
+                    // continue with the next match.
+                    match (self.lexer.actions[rule_index])(&mut self.actions, lexeme) {
+                        Ok(Some(value)) => {
+                            return Some(Ok(Token {
+                                value,
+                                lexeme,
+                                span,
+                            }))
+                        }
+                        Ok(None) => {}
+                        Err(message) => {
+                            return Some(Err(TokenError::Conversion(ConversionError {
+                                span,
+                                text: lexeme.to_string(),
+                                message,
+                            })))
+                        }
+                    }
+                }
+                _ => {
+                    let error_token = match self.error_token {
+                        Some(ref error_token) => error_token,
+                        None => {
+                            self.position = None;
+                            let end =
+                                start + self.text[start..].chars().next().map_or(0, char::len_utf8);
+
+                            return Some(Err(TokenError::Lex(LexError {
+                                span: self.line_counter.get_span(self.text, start, end),
+                                text: self.text[start..end].to_string(),
+                            })));
+                        }
+                    };
+
+                    let end = self.find_next_token_start(start);
+                    self.position = Some(end);
+                    let lexeme = &self.text[start..end];
+
+                    return Some(Ok(Token {
+                        value: error_token(lexeme),
+                        lexeme,
+                        span: self.line_counter.get_span(self.text, start, end),
+                    }));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,426 @@
+use crate::get_identifier_syntax_tree;
+use crate::Lexer;
+use crate::LexerActions;
+use crate::Normalization;
+use crate::SpecError;
+use crate::SymbolId;
+use crate::SymbolTable;
+use regex::MatchingGroup;
+use regex::RegexAstElements;
+use regex::RegexBuilder;
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::rc::Rc;
+
+/// Actions return `Err` with a message if the lexeme can not be converted into a token.
+pub(crate) type Action<T> = Box<dyn Fn(&mut LexerActions, &str) -> Result<Option<T>, String>>;
+
+/// The name of the start condition every lexer starts in.
+pub const INITIAL: &str = "INITIAL";
+
+/// Rules of conditions which are active in all start conditions, like `<*>` in lex.
+pub const ALL_CONDITIONS: &str = "*";
+
+/// The exclusive start condition the text of comments added by `nested_comments` is lexed in.
+pub const NESTED_COMMENT: &str = "NESTED_COMMENT";
+
+/// Keyword lists whose trie needs at most this many states are recognized by the state machine if
+/// the strategy is `Automatic`.
+pub const MAX_KEYWORD_STATES: usize = 64;
+
+/// How `identifiers` tells keywords and identifiers apart. These are the two approaches of
+/// `id_reserved_words_diagram` and `id_reserved_words_symbol_table`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeywordStrategy {
+    /// Every keyword is a rule of its own, so the state machine gets a trie of states for the
+    /// keywords. Lexing needs no lookup, but the transition table grows with every keyword.
+    StateMachine,
+    /// Keywords are matched by the identifier rule and looked up in a symbol table afterwards.
+    /// The transition table stays small, but every identifier is hashed.
+    Lookup,
+    /// Uses the state machine if the keyword trie has at most `MAX_KEYWORD_STATES` states and the
+    /// lookup otherwise.
+    Automatic,
+}
+impl KeywordStrategy {
+    /// Returns the strategy which is used for the keywords, which is never `Automatic`.
+    pub fn resolve(self, keywords: &[&str]) -> KeywordStrategy {
+        if self != KeywordStrategy::Automatic {
+            return self;
+        }
+
+        return match get_trie_state_count(keywords) <= MAX_KEYWORD_STATES {
+            true => KeywordStrategy::StateMachine,
+            false => KeywordStrategy::Lookup,
+        };
+    }
+}
+
+/// Every prefix of a keyword is a state of the trie, the root is the start state.
+fn get_trie_state_count(keywords: &[&str]) -> usize {
+    let mut prefixes = HashSet::new();
+    for keyword in keywords {
+        for (offset, _) in keyword.char_indices().skip(1) {
+            prefixes.insert(&keyword[..offset]);
+        }
+        prefixes.insert(*keyword);
+    }
+
+    return prefixes.len();
+}
+
+/// Most rules are regexes, but rules whose characters can not be written down in a regex, like
+/// all Unicode identifiers, are added as syntax trees.
+enum Pattern {
+    Regex(String),
+    SyntaxTree(RegexAstElements),
+}
+
+struct Rule<T> {
+    pattern: Pattern,
+    /// Rules without start conditions are active in all inclusive start conditions.
+    start_conditions: Option<Vec<String>>,
+    action: Action<T>,
+}
+
+/// The rules of a lexer. Definitions can be referenced in the regexes of all rules as `{NAME}`,
+/// like the definitions section of a lex file.
+///
+/// Like in lex, rules can be limited to start conditions. Inclusive start conditions also
+/// activate all rules without start conditions, exclusive start conditions only activate their
+/// own rules. Every lexer has the inclusive start condition `INITIAL`.
+pub struct LexerSpec<T> {
+    regex_builder: RegexBuilder,
+    rules: Vec<Rule<T>>,
+    /// Pairs of name and whether the start condition is exclusive.
+    start_conditions: Vec<(String, bool)>,
+    current_start_conditions: Option<Vec<String>>,
+}
+impl<T> LexerSpec<T> {
+    pub fn new() -> Self {
+        LexerSpec {
+            regex_builder: RegexBuilder::new(),
+            rules: Vec::new(),
+            start_conditions: vec![(String::from(INITIAL), false)],
+            current_start_conditions: None,
+        }
+    }
+
+    pub fn define(mut self, name: &str, regex: &str) -> Self {
+        self.regex_builder = self.regex_builder.define(name, regex);
+
+        return self;
+    }
+
+    /// Declares a start condition which also activates the rules without start conditions, like
+    /// `%s` in lex.
+    pub fn inclusive_condition(self, name: &str) -> Self {
+        return self.add_start_condition(name, false);
+    }
+
+    /// Declares a start condition which only activates its own rules, like `%x` in lex.
+    pub fn exclusive_condition(self, name: &str) -> Self {
+        return self.add_start_condition(name, true);
+    }
+
+    fn add_start_condition(mut self, name: &str, is_exclusive: bool) -> Self {
+        match self
+            .start_conditions
+            .iter_mut()
+            .find(|(condition_name, _)| condition_name == name)
+        {
+            Some(start_condition) => start_condition.1 = is_exclusive,
+            None => self.start_conditions.push((name.to_string(), is_exclusive)),
+        }
+
+        return self;
+    }
+
+    /// All rules added by `add_rules` are only active in the given start conditions, like a
+    /// `<NAME>{ ... }` block in lex.
+    pub fn in_conditions<F: FnOnce(Self) -> Self>(
+        mut self,
+        start_conditions: &[&str],
+        add_rules: F,
+    ) -> Self {
+        let previous_start_conditions = self.current_start_conditions.take();
+        self.current_start_conditions = Some(
+            start_conditions
+                .iter()
+                .map(|start_condition| start_condition.to_string())
+                .collect(),
+        );
+
+        let mut spec = add_rules(self);
+        spec.current_start_conditions = previous_start_conditions;
+
+        return spec;
+    }
+
+    /// Adds a rule. The action is called with the matched text to create the token. Rules added
+    /// earlier win over later rules which match the same text.
+    pub fn rule<F: Fn(&str) -> T + 'static>(self, regex: &str, action: F) -> Self {
+        return self.rule_with_actions(regex, move |_, lexeme| Some(action(lexeme)));
+    }
+
+    /// Adds a rule whose action can change the start condition of the lexer. If the action
+    /// returns no token, the lexer continues with the next match.
+    pub fn rule_with_actions<F: Fn(&mut LexerActions, &str) -> Option<T> + 'static>(
+        self,
+        regex: &str,
+        action: F,
+    ) -> Self {
+        return self.add_rule(
+            regex,
+            Box::new(move |actions, lexeme| Ok(action(actions, lexeme))),
+        );
+    }
+
+    /// Adds a rule whose action converts the lexeme into the token, e.g. with `str::parse`. If
+    /// the conversion fails, the lexer returns a `ConversionError` with the span of the lexeme
+    /// and continues with the next match.
+    pub fn rule_with_conversion<F, E>(self, regex: &str, convert: F) -> Self
+    where
+        F: Fn(&str) -> Result<T, E> + 'static,
+        E: Display,
+    {
+        return self.add_rule(
+            regex,
+            Box::new(move |_, lexeme| match convert(lexeme) {
+                Ok(value) => Ok(Some(value)),
+                Err(error) => Err(error.to_string()),
+            }),
+        );
+    }
+
+    /// Adds a rule whose text does not create a token, e.g. for whitespace or line comments.
+    pub fn skip(self, regex: &str) -> Self {
+        return self.rule_with_actions(regex, |_, _| None);
+    }
+
+    /// Adds block comments from `open` to `close` which can be nested, like `/* a /* b */ */`.
+    /// Comments do not create tokens. Their text is lexed in the exclusive start condition
+    /// `NESTED_COMMENT`, where the actions count the depth of the comments. Comments which are
+    /// still open at the end of the text are reported as `UnterminatedComment`.
+    ///
+    /// The delimiters are matched literally, their rules are built as syntax trees, so that no
+    /// character has to be escaped. Panics if a delimiter is empty.
+    pub fn nested_comments(self, open: &str, close: &str) -> Self {
+        for delimiter in &[open, close] {
+            if delimiter.is_empty() {
+                panic!("The comment delimiter {:?} is not supported", delimiter);
+            }
+        }
+
+        let open_ast = RegexAstElements::sequence(open.chars().collect());
+        let close_ast = RegexAstElements::sequence(close.chars().collect());
+        // All other text is skipped in runs, only the first characters of the delimiters have to
+        // be matched one at a time.
+        let mut delimiter_starts: Vec<char> = open.chars().take(1).collect();
+        delimiter_starts.extend(close.chars().take(1));
+        delimiter_starts.dedup();
+        let other_text_ast = RegexAstElements::none_of(delimiter_starts.clone()).one_or_more();
+
+        return self
+            .exclusive_condition(NESTED_COMMENT)
+            .add_pattern(
+                Pattern::SyntaxTree(open_ast.clone()),
+                Box::new(|actions, _| {
+                    actions.open_comment(NESTED_COMMENT);
+                    Ok(None)
+                }),
+            )
+            .in_conditions(&[NESTED_COMMENT], |spec| {
+                let mut spec = spec
+                    .add_pattern(
+                        Pattern::SyntaxTree(open_ast),
+                        Box::new(|actions, _| {
+                            actions.open_comment(NESTED_COMMENT);
+                            Ok(None)
+                        }),
+                    )
+                    .add_pattern(
+                        Pattern::SyntaxTree(close_ast),
+                        Box::new(|actions, _| {
+                            actions.close_comment();
+                            Ok(None)
+                        }),
+                    )
+                    .add_pattern(
+                        Pattern::SyntaxTree(other_text_ast),
+                        Box::new(|_, _| Ok(None)),
+                    );
+                for delimiter_start in delimiter_starts {
+                    spec = spec.add_pattern(
+                        Pattern::SyntaxTree(RegexAstElements::symbol(delimiter_start)),
+                        Box::new(|_, _| Ok(None)),
+                    );
+                }
+
+                spec
+            });
+    }
+
+    fn add_rule(self, regex: &str, action: Action<T>) -> Self {
+        return self.add_pattern(Pattern::Regex(regex.to_string()), action);
+    }
+
+    fn add_pattern(mut self, pattern: Pattern, action: Action<T>) -> Self {
+        self.rules.push(Rule {
+            pattern,
+            start_conditions: self.current_start_conditions.clone(),
+            action,
+        });
+
+        return self;
+    }
+
+    /// Adds a rule for identifiers which are turned into keywords if they are part of the keyword
+    /// list. The keywords have to match the regex of the identifiers, which wins over the
+    /// keywords for longer matches, like `iffy` for the keyword `if`. The strategy only changes
+    /// the size and speed of the lexer, not the tokens.
+    pub fn identifiers<K, I>(
+        mut self,
+        regex: &str,
+        keywords: &[&str],
+        strategy: KeywordStrategy,
+        keyword_action: K,
+        identifier_action: I,
+    ) -> Self
+    where
+        K: Fn(&str) -> T + 'static,
+        I: Fn(&str) -> T + 'static,
+    {
+        match strategy.resolve(keywords) {
+            KeywordStrategy::Lookup => {
+                let keyword_table = SymbolTable::with_keywords(keywords);
+
+                return self.rule(regex, move |lexeme| match keyword_table.get(lexeme) {
+                    Some(_) => keyword_action(lexeme),
+                    None => identifier_action(lexeme),
+                });
+            }
+            _ => {
+                // The keyword rules come first, so that they win over the identifier rule. They are
+                // built as syntax trees, so that keywords need no escaping.
+                let keyword_action = Rc::new(keyword_action);
+                for keyword in keywords {
+                    let keyword_action = Rc::clone(&keyword_action);
+                    self = self.add_pattern(
+                        Pattern::SyntaxTree(RegexAstElements::sequence(keyword.chars().collect())),
+                        Box::new(move |_, lexeme| Ok(Some(keyword_action(lexeme)))),
+                    );
+                }
+
+                return self.rule(regex, identifier_action);
+            }
+        }
+    }
+
+    /// Adds a rule for identifiers following the default identifier syntax of Unicode (UAX #31),
+    /// like `größe` or `名前`: a character with `XID_Start` followed by any number of characters
+    /// with `XID_Continue`. The name is normalized and interned before the action gets its id.
+    /// Keyword rules have to be added before, so that they win over identifiers of the same
+    /// length.
+    pub fn unicode_identifiers<F: Fn(SymbolId) -> T + 'static>(
+        self,
+        normalization: Normalization,
+        action: F,
+    ) -> Self {
+        return self.add_pattern(
+            Pattern::SyntaxTree(get_identifier_syntax_tree()),
+            Box::new(move |actions, lexeme| {
+                let id = actions.intern(&normalization.normalize(lexeme));
+
+                Ok(Some(action(id)))
+            }),
+        );
+    }
+
+    /// Compiles all rules into a single state machine with a start state for every start
+    /// condition.
+    pub fn build(self) -> Result<Lexer<T>, SpecError> {
+        let rule_start_conditions: Vec<Option<Vec<String>>> = self
+            .rules
+            .iter()
+            .map(|rule| rule.start_conditions.clone())
+            .collect();
+        let entry_points = get_entry_points(&self.start_conditions, &rule_start_conditions)?;
+
+        let mut asts = Vec::with_capacity(self.rules.len());
+        let mut actions = Vec::with_capacity(self.rules.len());
+        for rule in self.rules {
+            asts.push(match rule.pattern {
+                Pattern::Regex(regex) => self.regex_builder.get_regex_syntax_tree(&regex)?,
+                // Parsed regexes already end with the accepted state.
+                Pattern::SyntaxTree(ast) => RegexAstElements::Concatenation(
+                    Box::new(ast),
+                    Box::new(RegexAstElements::Leaf(MatchingGroup::AcceptedState)),
+                ),
+            });
+            actions.push(rule.action);
+        }
+
+        let start_condition_names = self
+            .start_conditions
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+
+        return Ok(Lexer::new(
+            asts,
+            entry_points,
+            actions,
+            start_condition_names,
+        ));
+    }
+}
+
+/// Returns for every start condition the indices of the rules which are active in it. The start
+/// conditions are pairs of name and whether the start condition is exclusive.
+pub(crate) fn get_entry_points(
+    start_conditions: &[(String, bool)],
+    rule_start_conditions: &[Option<Vec<String>>],
+) -> Result<Vec<Vec<usize>>, SpecError> {
+    let mut entry_points = vec![Vec::new(); start_conditions.len()];
+
+    for (rule_index, rule_start_conditions) in rule_start_conditions.iter().enumerate() {
+        if let Some(rule_start_conditions) = rule_start_conditions {
+            for rule_start_condition in rule_start_conditions {
+                let is_declared = rule_start_condition == ALL_CONDITIONS
+                    || start_conditions
+                        .iter()
+                        .any(|(name, _)| name == rule_start_condition);
+
+                if !is_declared {
+                    return Err(SpecError::UnknownStartCondition(
+                        rule_start_condition.clone(),
+                    ));
+                }
+            }
+        }
+
+        for (condition_index, (name, is_exclusive)) in start_conditions.iter().enumerate() {
+            let is_active = match rule_start_conditions {
+                Some(rule_start_conditions) => {
+                    rule_start_conditions.iter().any(|rule_start_condition| {
+                        rule_start_condition == name || rule_start_condition == ALL_CONDITIONS
+                    })
+                }
+                None => !is_exclusive,
+            };
+
+            if is_active {
+                entry_points[condition_index].push(rule_index);
+            }
+        }
+    }
+
+    return Ok(entry_points);
+}
+
+impl<T> Default for LexerSpec<T> {
+    fn default() -> Self {
+        return LexerSpec::new();
+    }
+}
@@ -1,44 +1,97 @@
-fn create_lexer() {
-    let code = "if hello then hi else bye 'if else' how";
+//! A lexer generator. Every rule of a `LexerSpec` is a regex together with an action which
+//! turns the matched text into a token. All rules are compiled into a single state machine, which
+//! always takes the longest match. If several rules match the same text, the first rule wins.
+//!
+//! ```
+//! use lexer_with_dynamic_state_machine::LexerSpec;
+//!
+//! #[derive(Debug, PartialEq)]
+//! enum Token {
+//!     If,
+//!     Id(String),
+//!     Number(String),
+//!     Whitespace,
+//! }
+//!
+//! let lexer = LexerSpec::new()
+//!     .define("LNUM", "[0-9]+")
+//!     .rule("if", |_| Token::If)
+//!     .rule("[a-z]+", |lexeme| Token::Id(lexeme.to_string()))
+//!     .rule("{LNUM}", |lexeme| Token::Number(lexeme.to_string()))
+//!     .rule(" +", |_| Token::Whitespace)
+//!     .build()
+//!     .unwrap();
+//!
+//! let tokens: Vec<Token> = lexer
+//!     .tokens("if iffy 42")
+//!     .map(|token| token.unwrap().value)
+//!     .collect();
+//! assert_eq!(
+//!     vec![
+//!         Token::If,
+//!         Token::Whitespace,
+//!         Token::Id(String::from("iffy")),
+//!         Token::Whitespace,
+//!         Token::Number(String::from("42")),
+//!     ],
+//!     tokens
+//! );
+//! ```
 
-    // Define tokens
-    // %TOKENS%{
-    //     IF,
-    //     THEN,
-    //     ELSE,
-    //     ID(&str),
-    // }
+mod codegen;
+mod compressed_table;
+mod conversions;
+mod error;
+mod keyword_diagram;
+mod lex_file;
+mod lexer;
+mod lexer_spec;
+mod scoped_symbol_table;
+mod span;
+mod stream;
+mod symbol_table;
+mod unicode;
 
-    // Define states
-    // %STATES%{
-    //     INITIAL,
-    //     QUOTATION,
-    // }
-
-    // Allow additional logic & functions
-    // - current character
-    // - current line
-    // - functions to use
-
-    // Macros in regex
-    // LNUM	[0-9]+
-    // DNUM	([0-9]*"."[0-9]+)|([0-9]+"."[0-9]*)
-    // EXPONENT_DNUM (({LNUM}|{DNUM})[eE][+-]?{LNUM})
-
-    // Regex with code if the lexer detects a match
-    // <INITIAL>"if" {
-    //     self.return_token(TOKEN::IF);
-    // }
-
-    // Change states
-    // <INITIAL>"'" {
-    //     self.push_state(STATE::QUOTATION);
-    // }
-
-    // Symbol table - add manually?
-    // %SYMBOL%{
-    //     pub name: String,
-    // }
-
-    // Run 🚀
-}
+pub use codegen::RustLexerGenerator;
+pub use compressed_table::CompressedTable;
+pub use compressed_table::TableSizeReport;
+pub use conversions::unescape_string;
+pub use error::ConversionError;
+pub use error::LexError;
+pub use error::LexFileError;
+pub use error::RedeclarationError;
+pub use error::SpecError;
+pub use error::StreamError;
+pub use error::TokenError;
+pub use error::UnescapeError;
+pub use keyword_diagram::KeywordDiagram;
+pub use keyword_diagram::KeywordMatch;
+pub use lex_file::LexFile;
+pub use lex_file::LexFileRule;
+pub use lex_file::StartCondition;
+pub use lexer::Lexer;
+pub use lexer::LexerActions;
+pub use lexer::Token;
+pub use lexer::Tokens;
+pub use lexer_spec::KeywordStrategy;
+pub use lexer_spec::LexerSpec;
+pub use lexer_spec::ALL_CONDITIONS;
+pub use lexer_spec::INITIAL;
+pub use lexer_spec::MAX_KEYWORD_STATES;
+pub use lexer_spec::NESTED_COMMENT;
+pub use scoped_symbol_table::Declaration;
+pub use scoped_symbol_table::Lookup;
+pub use scoped_symbol_table::ScopedSymbolTable;
+pub use span::LineCounter;
+pub use span::Span;
+pub use span::TAB_WIDTH;
+pub use stream::StreamToken;
+pub use stream::StreamTokens;
+pub use stream::BLOCK_SIZE;
+pub use symbol_table::SymbolId;
+pub use symbol_table::SymbolTable;
+pub use unicode::get_identifier_syntax_tree;
+pub use unicode::is_identifier_continue;
+pub use unicode::is_identifier_start;
+pub use unicode::normalize_identifier;
+pub use unicode::Normalization;
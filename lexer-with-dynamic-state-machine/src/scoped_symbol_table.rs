@@ -0,0 +1,133 @@
+use crate::RedeclarationError;
+use crate::Span;
+use crate::SymbolId;
+use crate::SymbolTable;
+use std::collections::HashMap;
+
+/// A declared name together with its attributes. The kind, e.g. variable or function, and the
+/// type are defined by the front end using the table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Declaration<K, T> {
+    pub name: SymbolId,
+    pub kind: K,
+    pub symbol_type: T,
+    pub span: Span,
+}
+
+/// The declaration a name refers to. `scope_depth` is the scope the declaration was found in,
+/// 0 is the global scope.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lookup<'a, K, T> {
+    pub declaration: &'a Declaration<K, T>,
+    pub scope_depth: usize,
+}
+
+/// A symbol table for nested scopes, e.g. blocks and functions. Names are looked up from the
+/// innermost to the global scope, therefore declarations in inner scopes shadow outer ones until
+/// their scope is exited.
+///
+/// The names are interned in a shared `SymbolTable`, e.g. the one of the lexer, and the scopes
+/// are keyed by its `SymbolId`s. Identifier tokens can therefore be declared and looked up
+/// without hashing their names again.
+#[derive(Debug)]
+pub struct ScopedSymbolTable<'s, K, T> {
+    symbol_table: &'s mut SymbolTable,
+    scopes: Vec<HashMap<SymbolId, Declaration<K, T>>>,
+}
+impl<'s, K, T> ScopedSymbolTable<'s, K, T> {
+    /// Creates a table with the global scope, which interns its names in `symbol_table`.
+    pub fn new(symbol_table: &'s mut SymbolTable) -> Self {
+        ScopedSymbolTable {
+            symbol_table,
+            scopes: vec![HashMap::new()],
+        }
+    }
+
+    /// The shared table with the interned names, e.g. to get the name of a `SymbolId`.
+    pub fn symbol_table(&self) -> &SymbolTable {
+        return self.symbol_table;
+    }
+
+    /// Interns the name in the shared table, e.g. for names which are declared without a token
+    /// like built-in functions.
+    pub fn intern(&mut self, name: &str) -> SymbolId {
+        return self.symbol_table.intern(name);
+    }
+
+    pub fn enter_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    /// Leaves the current scope and returns its declarations, e.g. to report unused ones.
+    /// Panics for the global scope, which can not be left.
+    pub fn exit_scope(&mut self) -> Vec<Declaration<K, T>> {
+        if self.scopes.len() == 1 {
+            panic!("The global scope can not be exited");
+        }
+
+        let mut declarations: Vec<Declaration<K, T>> = match self.scopes.pop() {
+            Some(scope) => scope.into_values().collect(),
+            None => Vec::new(),
+        };
+        // The scope is a hash map, sorting keeps the order of the source.
+        declarations.sort_by_key(|declaration| declaration.span.start);
+
+        return declarations;
+    }
+
+    /// The depth of the current scope, 0 is the global scope.
+    pub fn get_scope_depth(&self) -> usize {
+        return self.scopes.len() - 1;
+    }
+
+    /// Declares the name in the current scope. Names of outer scopes can be shadowed, but every
+    /// name can only be declared once per scope. The name has to be interned in the shared table.
+    pub fn declare(
+        &mut self,
+        name: SymbolId,
+        kind: K,
+        symbol_type: T,
+        span: Span,
+    ) -> Result<(), RedeclarationError> {
+        let scope = self.scopes.last_mut().unwrap();
+
+        if let Some(previous_declaration) = scope.get(&name) {
+            return Err(RedeclarationError {
+                name: self.symbol_table.get_name(name).to_string(),
+                span,
+                previous_span: previous_declaration.span,
+            });
+        }
+
+        scope.insert(
+            name,
+            Declaration {
+                name,
+                kind,
+                symbol_type,
+                span,
+            },
+        );
+
+        return Ok(());
+    }
+
+    /// Returns the innermost declaration of the name.
+    pub fn lookup(&self, name: SymbolId) -> Option<Lookup<'_, K, T>> {
+        for (scope_depth, scope) in self.scopes.iter().enumerate().rev() {
+            if let Some(declaration) = scope.get(&name) {
+                return Some(Lookup {
+                    declaration,
+                    scope_depth,
+                });
+            }
+        }
+
+        return None;
+    }
+
+    /// Returns the declaration of the name in the current scope only.
+    pub fn lookup_in_current_scope(&self, name: SymbolId) -> Option<&Declaration<K, T>> {
+        return self.scopes.last()?.get(&name);
+    }
+}
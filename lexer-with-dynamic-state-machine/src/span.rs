@@ -0,0 +1,109 @@
+use std::fmt;
+
+/// Tabs advance the column to the next multiple of the tab width, like most editors show them.
+pub const TAB_WIDTH: usize = 8;
+
+/// The part of a text a token was created from. `start` and `end` are byte offsets, the token is
+/// `&text[start..end]`. `line` and `column` are the position of the start, both counted from 1.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Self {
+        Span {
+            start,
+            end,
+            line,
+            column,
+        }
+    }
+
+    pub fn as_str<'a>(&self, text: &'a str) -> &'a str {
+        return &text[self.start..self.end];
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        return write!(formatter, "{}:{}", self.line, self.column);
+    }
+}
+
+/// Counts lines and columns while a text is lexed from the start to the end, so that every
+/// character is only looked at once. `\n`, `\r\n` and a single `\r` all end a line.
+#[derive(Clone, Debug)]
+pub struct LineCounter {
+    offset: usize,
+    line: usize,
+    column: usize,
+    is_after_carriage_return: bool,
+}
+impl LineCounter {
+    pub fn new() -> Self {
+        LineCounter {
+            offset: 0,
+            line: 1,
+            column: 1,
+            is_after_carriage_return: false,
+        }
+    }
+
+    /// Returns the span of `&text[start..end]`. The spans have to be requested in the order of
+    /// the text, `start` can not be before the end of the previous span.
+    pub fn get_span(&mut self, text: &str, start: usize, end: usize) -> Span {
+        self.advance_to(text, start);
+        let span = Span::new(start, end, self.line, self.column);
+        self.advance_to(text, end);
+
+        return span;
+    }
+
+    /// Returns the span of the lexeme which starts at the end of the previous span, for texts
+    /// which are not available as a whole, e.g. while they are read.
+    pub fn get_span_of_next(&mut self, lexeme: &str) -> Span {
+        let start = self.offset;
+        let span = Span::new(start, start + lexeme.len(), self.line, self.column);
+        self.count(lexeme);
+        self.offset = span.end;
+
+        return span;
+    }
+
+    fn advance_to(&mut self, text: &str, offset: usize) {
+        if offset < self.offset {
+            panic!(
+                "The line counter is already at {}, it can not go back to {}",
+                self.offset, offset
+            );
+        }
+
+        self.count(&text[self.offset..offset]);
+        self.offset = offset;
+    }
+
+    fn count(&mut self, text: &str) {
+        for character in text.chars() {
+            match character {
+                '\n' if self.is_after_carriage_return => {}
+                '\n' | '\r' => {
+                    self.line += 1;
+                    self.column = 1;
+                }
+                '\t' => self.column += TAB_WIDTH - (self.column - 1) % TAB_WIDTH,
+                _ => self.column += 1,
+            }
+
+            self.is_after_carriage_return = character == '\r';
+        }
+    }
+}
+
+impl Default for LineCounter {
+    fn default() -> Self {
+        return LineCounter::new();
+    }
+}
@@ -0,0 +1,256 @@
+use crate::ConversionError;
+use crate::LexError;
+use crate::Lexer;
+use crate::LexerActions;
+use crate::LineCounter;
+use crate::Span;
+use crate::StreamError;
+use crate::SymbolTable;
+use std::io;
+use std::io::ErrorKind;
+use std::io::Read;
+use std::str;
+
+/// The number of bytes which are read at once if no other block size is set.
+pub const BLOCK_SIZE: usize = 4096;
+
+/// A token read from a stream. The lexeme is owned, because the buffer it was read from is reused
+/// for the following text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamToken<T> {
+    pub value: T,
+    pub lexeme: String,
+    pub span: Span,
+}
+
+/// Iterates over the tokens of a reader, which is read in blocks while lexing.
+///
+/// The buffer works like the classic pair of buffers with the pointers `lexeme_begin` and
+/// `forward`: the state machine moves `forward` until no transition is left, the token is
+/// `lexeme_begin..` the end of the longest match. When `forward` reaches the end of the buffer in
+/// the middle of a token, the text before `lexeme_begin` is dropped and the next block is read
+/// behind the current lexeme. Lexemes longer than a block therefore stay in one piece, the
+/// buffer grows with them.
+pub struct StreamTokens<'l, R, T> {
+    lexer: &'l Lexer<T>,
+    reader: R,
+    block_size: usize,
+    buffer: Vec<u8>,
+    lexeme_begin: usize,
+    forward: usize,
+    is_at_end_of_input: bool,
+    has_failed: bool,
+    line_counter: LineCounter,
+    actions: LexerActions<'l>,
+}
+impl<'l, R: Read, T> StreamTokens<'l, R, T> {
+    pub(crate) fn new(lexer: &'l Lexer<T>, reader: R, actions: LexerActions<'l>) -> Self {
+        StreamTokens {
+            lexer,
+            reader,
+            block_size: BLOCK_SIZE,
+            buffer: Vec::new(),
+            lexeme_begin: 0,
+            forward: 0,
+            is_at_end_of_input: false,
+            has_failed: false,
+            line_counter: LineCounter::new(),
+            actions,
+        }
+    }
+
+    /// Sets the number of bytes which are read at once. Panics if the size is 0.
+    pub fn with_block_size(mut self, block_size: usize) -> Self {
+        if block_size == 0 {
+            panic!("The block size has to be at least 1");
+        }
+        self.block_size = block_size;
+
+        return self;
+    }
+
+    /// Returns the start condition the next token is matched in.
+    pub fn current_state(&self) -> &str {
+        return self.actions.current_state();
+    }
+
+    /// The names interned by the actions so far.
+    pub fn symbol_table(&self) -> &SymbolTable {
+        return self.actions.symbol_table();
+    }
+
+    /// Reads the next block behind the current lexeme. Returns false at the end of the input.
+    fn refill(&mut self) -> Result<bool, StreamError> {
+        if self.is_at_end_of_input {
+            return Ok(false);
+        }
+
+        self.buffer.drain(..self.lexeme_begin);
+        self.forward -= self.lexeme_begin;
+        self.lexeme_begin = 0;
+
+        let length = self.buffer.len();
+        self.buffer.resize(length + self.block_size, 0);
+        loop {
+            match self.reader.read(&mut self.buffer[length..]) {
+                Ok(read) => {
+                    self.buffer.truncate(length + read);
+                    self.is_at_end_of_input = read == 0;
+
+                    return Ok(read > 0);
+                }
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error) => {
+                    self.buffer.truncate(length);
+
+                    return Err(StreamError::Io(error));
+                }
+            }
+        }
+    }
+
+    /// Returns the character at `forward` without moving `forward`, or `None` at the end of the
+    /// input. Characters whose bytes are split between two blocks are read completely.
+    fn peek_character(&mut self) -> Result<Option<char>, StreamError> {
+        loop {
+            let bytes = &self.buffer[self.forward..];
+            if let Some(first_byte) = bytes.first() {
+                let length = get_utf8_length(*first_byte);
+
+                if bytes.len() >= length {
+                    return match str::from_utf8(&bytes[..length]) {
+                        Ok(text) => Ok(text.chars().next()),
+                        Err(error) => Err(io::Error::new(ErrorKind::InvalidData, error).into()),
+                    };
+                }
+            }
+
+            if !self.refill()? {
+                return match self.forward == self.buffer.len() {
+                    true => Ok(None),
+                    false => Err(io::Error::new(
+                        ErrorKind::UnexpectedEof,
+                        "The input ends in the middle of a UTF-8 character",
+                    )
+                    .into()),
+                };
+            }
+        }
+    }
+
+    /// Runs the state machine from `lexeme_begin` and returns the rule and end of the longest
+    /// non-empty match.
+    fn find_longest_match(&mut self) -> Result<Option<(usize, usize)>, StreamError> {
+        let lexer = self.lexer;
+        let regex_set = &lexer.regex_set;
+        let mut state = regex_set.get_start_state(self.actions.current_start_condition);
+        let mut longest_match = None;
+        self.forward = self.lexeme_begin;
+
+        while let Some(character) = self.peek_character()? {
+            state = match regex_set.get_next_state(state, character) {
+                Some(next_state) => next_state,
+                None => break,
+            };
+            self.forward += character.len_utf8();
+
+            if let Some(rule_index) = regex_set.get_accepted_regex(state) {
+                // The end is relative to `lexeme_begin`, which is moved by refills.
+                longest_match = Some((rule_index, self.forward - self.lexeme_begin));
+            }
+        }
+
+        return Ok(
+            longest_match.map(|(rule_index, length)| (rule_index, self.lexeme_begin + length))
+        );
+    }
+
+    fn next_token(&mut self) -> Result<Option<StreamToken<T>>, StreamError> {
+        loop {
+            self.forward = self.lexeme_begin;
+            let character = match self.peek_character()? {
+                Some(character) => character,
+                None => {
+                    return match self.actions.take_unterminated_comment() {
+                        Some(span) => Err(StreamError::UnterminatedComment(span)),
+                        None => Ok(None),
+                    }
+                }
+            };
+
+            let (rule_index, end) = match self.find_longest_match()? {
+                Some(found_match) => found_match,
+                None => {
+                    let end = self.lexeme_begin + character.len_utf8();
+                    let text = self.get_text(end);
+
+                    return Err(StreamError::Lex(LexError {
+                        span: self.line_counter.get_span_of_next(&text),
+                        text,
+                    }));
+                }
+            };
+
+            let lexeme = self.get_text(end);
+            self.lexeme_begin = end;
+            self.forward = end;
+            let span = self.line_counter.get_span_of_next(&lexeme);
+            self.actions.current_span = span;
+
+            match (self.lexer.actions[rule_index])(&mut self.actions, &lexeme) {
+                Ok(Some(value)) => {
+                    return Ok(Some(StreamToken {
+                        value,
+                        lexeme,
+                        span,
+                    }))
+                }
+                Ok(None) => {}
+                Err(message) => {
+                    return Err(StreamError::Conversion(ConversionError {
+                        span,
+                        text: lexeme,
+                        message,
+                    }))
+                }
+            }
+        }
+    }
+
+    fn get_text(&self, end: usize) -> String {
+        // Only whole characters are matched, the bytes are valid UTF-8.
+        return String::from_utf8_lossy(&self.buffer[self.lexeme_begin..end]).into_owned();
+    }
+}
+
+impl<'l, R: Read, T> Iterator for StreamTokens<'l, R, T> {
+    type Item = Result<StreamToken<T>, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.has_failed {
+            return None;
+        }
+
+        return match self.next_token() {
+            Ok(token) => token.map(Ok),
+            // The text after a conversion error can still be lexed.
+            Err(StreamError::Conversion(error)) => Some(Err(StreamError::Conversion(error))),
+            Err(error) => {
+                self.has_failed = true;
+
+                Some(Err(error))
+            }
+        };
+    }
+}
+
+/// Returns the number of bytes of the UTF-8 character starting with the byte. Invalid bytes
+/// count as a single byte, decoding them reports the error.
+fn get_utf8_length(first_byte: u8) -> usize {
+    return match first_byte {
+        0xF0..=0xF7 => 4,
+        0xE0..=0xEF => 3,
+        0xC0..=0xDF => 2,
+        _ => 1,
+    };
+}
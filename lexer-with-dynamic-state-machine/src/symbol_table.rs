@@ -0,0 +1,128 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::hash::BuildHasher;
+
+/// The id of an interned name. Ids are numbered from 0 in the order the names were interned,
+/// therefore they can index a `Vec` with more information about each symbol.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct SymbolId(u32);
+impl SymbolId {
+    pub fn index(self) -> usize {
+        return self.0 as usize;
+    }
+}
+
+/// The name of a symbol is a range of the name arena.
+#[derive(Clone, Debug)]
+struct Symbol {
+    name_start: usize,
+    name_end: usize,
+    is_keyword: bool,
+}
+
+/// Interns names, so that every name is only stored once and can be compared by its id. All
+/// names are stored one after another in a single string, the hash map only stores ids.
+///
+/// Keywords are interned like any other name, but are flagged as keywords. A lexer can therefore
+/// match keywords and identifiers with the same rule and look up the keyword flag afterwards.
+#[derive(Clone, Debug, Default)]
+pub struct SymbolTable {
+    names: String,
+    symbols: Vec<Symbol>,
+    /// Names with the same hash share an entry, which lists all their ids.
+    ids_by_hash: HashMap<u64, Vec<SymbolId>>,
+    hash_builder: RandomState,
+}
+impl SymbolTable {
+    pub fn new() -> Self {
+        return SymbolTable::default();
+    }
+
+    /// Creates a table which already contains the keywords, with the ids `0..keywords.len()`.
+    pub fn with_keywords(keywords: &[&str]) -> Self {
+        let mut symbol_table = SymbolTable::new();
+        for keyword in keywords {
+            symbol_table.add_keyword(keyword);
+        }
+
+        return symbol_table;
+    }
+
+    /// Interns the name and flags it as keyword.
+    pub fn add_keyword(&mut self, name: &str) -> SymbolId {
+        let id = self.intern(name);
+        self.symbols[id.index()].is_keyword = true;
+
+        return id;
+    }
+
+    /// Returns the id of the name, which is added if it is not part of the table yet. Panics if
+    /// the table already holds 2^32 names, which is the most `SymbolId` can number.
+    pub fn intern(&mut self, name: &str) -> SymbolId {
+        let hash = self.get_hash(name);
+        if let Some(id) = self.find(hash, name) {
+            return id;
+        }
+
+        let id = match u32::try_from(self.symbols.len()) {
+            Ok(index) => SymbolId(index),
+            Err(_) => panic!("The symbol table is full, it can hold at most 2^32 names"),
+        };
+        let name_start = self.names.len();
+        self.names.push_str(name);
+        self.symbols.push(Symbol {
+            name_start,
+            name_end: self.names.len(),
+            is_keyword: false,
+        });
+        self.ids_by_hash.entry(hash).or_default().push(id);
+
+        return id;
+    }
+
+    /// Returns the id of the name without adding it.
+    pub fn get(&self, name: &str) -> Option<SymbolId> {
+        return self.find(self.get_hash(name), name);
+    }
+
+    pub fn get_name(&self, id: SymbolId) -> &str {
+        let symbol = &self.symbols[id.index()];
+
+        return &self.names[symbol.name_start..symbol.name_end];
+    }
+
+    pub fn is_keyword(&self, id: SymbolId) -> bool {
+        return self.symbols[id.index()].is_keyword;
+    }
+
+    pub fn len(&self) -> usize {
+        return self.symbols.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.symbols.is_empty();
+    }
+
+    /// Returns all ids together with their names, in the order they were interned.
+    pub fn iter(&self) -> impl Iterator<Item = (SymbolId, &str)> {
+        return (0..self.symbols.len()).map(move |index| {
+            let id = SymbolId(index as u32);
+
+            (id, self.get_name(id))
+        });
+    }
+
+    fn find(&self, hash: u64, name: &str) -> Option<SymbolId> {
+        return self
+            .ids_by_hash
+            .get(&hash)?
+            .iter()
+            .find(|id| self.get_name(**id) == name)
+            .cloned();
+    }
+
+    fn get_hash(&self, name: &str) -> u64 {
+        return self.hash_builder.hash_one(name);
+    }
+}
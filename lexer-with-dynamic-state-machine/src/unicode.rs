@@ -0,0 +1,82 @@
+use regex::MatchingGroup;
+use regex::MatchingGroupElements;
+use regex::RegexAstElements;
+use std::char;
+use unicode_normalization::is_nfc_quick;
+use unicode_normalization::IsNormalized;
+use unicode_normalization::UnicodeNormalization;
+use unicode_xid::UnicodeXID;
+
+/// How the names of identifiers are normalized before they are interned. Without normalization,
+/// names like `é` and `e\u{301}` are different symbols, although they look the same.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Normalization {
+    /// Interns the lexeme as it is.
+    None,
+    /// Interns the canonical composition (NFC) of the lexeme, as recommended by UAX #31 for
+    /// identifiers.
+    Nfc,
+}
+impl Normalization {
+    pub fn normalize(self, name: &str) -> String {
+        return match self {
+            Normalization::Nfc => normalize_identifier(name),
+            Normalization::None => name.to_string(),
+        };
+    }
+}
+
+/// Whether the character can start an identifier, which are the characters with the Unicode
+/// property `XID_Start`.
+pub fn is_identifier_start(character: char) -> bool {
+    return character.is_xid_start();
+}
+
+/// Whether the character can follow the first character of an identifier, which are the
+/// characters with the Unicode property `XID_Continue`, e.g. digits and `_`.
+pub fn is_identifier_continue(character: char) -> bool {
+    return character.is_xid_continue();
+}
+
+/// Returns the NFC of the name. Names which are already in NFC, like all ASCII names, are only
+/// checked.
+pub fn normalize_identifier(name: &str) -> String {
+    return match is_nfc_quick(name.chars()) {
+        IsNormalized::Yes => name.to_string(),
+        _ => name.nfc().collect(),
+    };
+}
+
+/// Returns the syntax tree of an identifier following the default identifier syntax of UAX #31,
+/// a character with `XID_Start` followed by any number of characters with `XID_Continue`.
+/// Like the trees of the ast builder of the regex crate, it does not end with the accepted state.
+pub fn get_identifier_syntax_tree() -> RegexAstElements {
+    let start = get_character_group(is_identifier_start);
+    let continuation = get_character_group(is_identifier_continue);
+
+    return start.then(continuation.zero_or_more());
+}
+
+/// Returns a group with ranges of all characters which have the property.
+fn get_character_group(has_property: fn(char) -> bool) -> RegexAstElements {
+    let mut ranges = Vec::new();
+    let mut range_start = None;
+
+    for code_point in 0..=(char::MAX as u32 + 1) {
+        let character = char::from_u32(code_point).filter(|character| has_property(*character));
+
+        match (range_start, character) {
+            (None, Some(character)) => range_start = Some(character),
+            // Surrogates are no characters, so they end ranges like characters without the
+            // property.
+            (Some(start), None) => {
+                let end = char::from_u32(code_point - 1).unwrap();
+                ranges.push(MatchingGroupElements::Range(start, end));
+                range_start = None;
+            }
+            _ => {}
+        }
+    }
+
+    return RegexAstElements::Leaf(MatchingGroup::Group(ranges));
+}
@@ -0,0 +1,80 @@
+mod common;
+
+mod calculator {
+    include!("lexers/calculator.rs");
+}
+
+#[test]
+fn generated_lexer_is_up_to_date() {
+    common::assert_generated_file_is_up_to_date(
+        "calculator.rs",
+        &common::generate_calculator_lexer(false),
+    );
+}
+
+#[test]
+fn lex_with_the_generated_lexer() {
+    use calculator::Token;
+
+    let tokens: Vec<Token> = calculator::Lexer::new("if x <= 42 /* a * b */ iffy")
+        .map(|token| token.unwrap().token)
+        .collect();
+
+    assert_eq!(
+        vec![
+            Token::If,
+            Token::Id(String::from("x")),
+            Token::Comparison(String::from("<=")),
+            Token::Number(42),
+            Token::Id(String::from("iffy")),
+        ],
+        tokens
+    );
+}
+
+#[test]
+fn report_positions_of_the_generated_lexer() {
+    use calculator::Span;
+
+    let mut lexer = calculator::Lexer::new("ab /*\n*/ 1!");
+
+    let token = lexer.next().unwrap().unwrap();
+    assert_eq!(("ab", Span::new(0, 2, 1, 1)), (token.lexeme, token.span));
+    assert_eq!(calculator::INITIAL, lexer.current_state());
+    let token = lexer.next().unwrap().unwrap();
+    assert_eq!(("1", Span::new(9, 10, 2, 4)), (token.lexeme, token.span));
+    assert_eq!("2:4", token.span.to_string());
+    assert_eq!(
+        Some(Err(calculator::LexError {
+            span: Span::new(10, 11, 2, 5)
+        })),
+        lexer.next()
+    );
+    assert_eq!(None, lexer.next());
+}
+
+#[test]
+fn recover_from_errors_in_the_generated_lexer() {
+    use calculator::Token;
+
+    let tokens: Vec<(Token, usize, usize)> = calculator::Lexer::new("a !?! 1 !")
+        .recover_from_errors(|lexeme| Token::Unknown(lexeme.to_string()))
+        .map(|token| token.unwrap())
+        .map(|token| (token.token, token.span.start, token.span.end))
+        .collect();
+
+    assert_eq!(
+        vec![
+            (Token::Id(String::from("a")), 0, 1),
+            (Token::Unknown(String::from("!?!")), 2, 5),
+            (Token::Number(1), 6, 7),
+            (Token::Unknown(String::from("!")), 8, 9),
+        ],
+        tokens
+    );
+}
+
+#[test]
+fn include_the_user_code_of_the_lex_file() {
+    assert_eq!(4, calculator::count_tokens("/* x */ a >= b 7"));
+}
@@ -0,0 +1,153 @@
+use lexer_with_dynamic_state_machine::Lexer;
+use lexer_with_dynamic_state_machine::LexerSpec;
+use lexer_with_dynamic_state_machine::Span;
+use lexer_with_dynamic_state_machine::StreamError;
+use lexer_with_dynamic_state_machine::TokenError;
+use lexer_with_dynamic_state_machine::INITIAL;
+use lexer_with_dynamic_state_machine::NESTED_COMMENT;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Id(String),
+    Times,
+    Divide,
+}
+
+fn get_lexer() -> Lexer<Token> {
+    return LexerSpec::new()
+        .skip("[ \t\n]+")
+        .skip("\"//\"[^\n]*")
+        .nested_comments("/*", "*/")
+        .rule("[a-z]+", |lexeme| Token::Id(lexeme.to_string()))
+        .rule("\"*\"", |_| Token::Times)
+        .rule("\"/\"", |_| Token::Divide)
+        .build()
+        .unwrap();
+}
+
+fn get_tokens(lexer: &Lexer<Token>, text: &str) -> Vec<Result<Token, TokenError>> {
+    return lexer
+        .tokens(text)
+        .map(|token| token.map(|token| token.value))
+        .collect();
+}
+
+#[test]
+fn skip_whitespace_and_line_comments() {
+    let lexer = get_lexer();
+
+    assert_eq!(
+        vec![
+            Ok(Token::Id(String::from("a"))),
+            Ok(Token::Divide),
+            Ok(Token::Id(String::from("b"))),
+        ],
+        get_tokens(&lexer, "a // a line comment\n  / b // the end")
+    );
+}
+
+#[test]
+fn skip_nested_comments() {
+    let lexer = get_lexer();
+
+    assert_eq!(
+        vec![
+            Ok(Token::Id(String::from("a"))),
+            Ok(Token::Times),
+            Ok(Token::Id(String::from("b"))),
+        ],
+        get_tokens(&lexer, "a /* x /* y ** / */ z\n */ * /**/ b")
+    );
+}
+
+#[test]
+fn return_to_the_previous_start_condition_after_comments() {
+    let lexer = get_lexer();
+    let mut tokens = lexer.tokens("/* /* */ x */ a");
+
+    assert_eq!(
+        Token::Id(String::from("a")),
+        tokens.next().unwrap().unwrap().value
+    );
+    assert_eq!(INITIAL, tokens.current_state());
+    assert!(tokens.next().is_none());
+}
+
+#[test]
+fn report_unterminated_comments_at_the_end() {
+    let lexer = get_lexer();
+
+    assert_eq!(
+        vec![
+            Ok(Token::Id(String::from("a"))),
+            Err(TokenError::UnterminatedComment(Span::new(2, 4, 1, 3))),
+        ],
+        get_tokens(&lexer, "a /* b /* c */\n")
+    );
+
+    let mut tokens = lexer.tokens("/* /*");
+    assert_eq!(
+        Some(Err(TokenError::UnterminatedComment(Span::new(0, 2, 1, 1)))),
+        tokens.next().map(|token| token.map(|token| token.value))
+    );
+    assert_eq!(NESTED_COMMENT, tokens.current_state());
+    assert!(tokens.next().is_none());
+}
+
+#[test]
+fn report_unterminated_comments_of_streams() {
+    let lexer = get_lexer();
+
+    let errors: Vec<String> = lexer
+        .stream_tokens(&b"a\n  * /* b"[..])
+        .with_block_size(3)
+        .filter_map(|token| match token {
+            Err(StreamError::UnterminatedComment(span)) => Some(span.to_string()),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(vec![String::from("2:5")], errors);
+}
+
+#[test]
+fn support_other_delimiters() {
+    let lexer = LexerSpec::new()
+        .skip(" +")
+        .nested_comments("(*", "*)")
+        .rule("[a-z]+", |lexeme| Token::Id(lexeme.to_string()))
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        vec![
+            Ok(Token::Id(String::from("a"))),
+            Ok(Token::Id(String::from("b"))),
+        ],
+        get_tokens(&lexer, "a (* (* ( * *) ) *) b")
+    );
+}
+
+#[test]
+fn support_delimiters_with_characters_of_groups() {
+    // `\`, `^`, `-` and `]` have a meaning in groups like `[^...]`, but are matched literally.
+    for (open, close, text) in &[
+        ("\\[", "]\\", "a \\[ \\ \\[ ] ]\\ x ]\\ b"),
+        ("^-", "-^", "a ^- - ^ ^- x -^ -^ b"),
+    ] {
+        let lexer = LexerSpec::new()
+            .skip(" +")
+            .nested_comments(open, close)
+            .rule("[a-z]+", |lexeme| Token::Id(lexeme.to_string()))
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                Ok(Token::Id(String::from("a"))),
+                Ok(Token::Id(String::from("b"))),
+            ],
+            get_tokens(&lexer, text)
+        );
+    }
+}
@@ -0,0 +1,35 @@
+use lexer_with_dynamic_state_machine::LexFile;
+use lexer_with_dynamic_state_machine::RustLexerGenerator;
+use std::env;
+use std::fs;
+
+/// Generated code is checked in under `tests/lexers`, so that the tests can compile it. Asserts
+/// that the file is the same as `code`. Run the tests with `UPDATE_GENERATED_LEXERS=1` to
+/// regenerate the files.
+pub fn assert_generated_file_is_up_to_date(file_name: &str, code: &str) {
+    let path = format!("{}/tests/lexers/{}", env!("CARGO_MANIFEST_DIR"), file_name);
+
+    if env::var("UPDATE_GENERATED_LEXERS").is_ok() {
+        fs::write(&path, code).unwrap();
+    }
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), code);
+}
+
+/// Generates the lexer of `lexers/calculator.l`, with match expressions or with compressed tables.
+/// Not every test uses it.
+#[allow(dead_code)]
+pub fn generate_calculator_lexer(compress: bool) -> String {
+    let lex_file = LexFile::parse(include_str!("../lexers/calculator.l")).unwrap();
+    let generator = RustLexerGenerator::new(&lex_file)
+        .token("If")
+        .token("Id(String)")
+        .token("Number(u64)")
+        .token("Comparison(String)")
+        .token("Unknown(String)");
+
+    return match compress {
+        true => generator.compress_tables().generate().unwrap(),
+        false => generator.generate().unwrap(),
+    };
+}
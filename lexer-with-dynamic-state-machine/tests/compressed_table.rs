@@ -0,0 +1,189 @@
+use lexer_with_dynamic_state_machine::CompressedTable;
+use lexer_with_dynamic_state_machine::LexerSpec;
+use regex::get_regex_syntax_tree;
+use regex::RegexSet;
+use std::collections::HashSet;
+
+mod common;
+
+mod compressed_calculator {
+    include!("lexers/compressed_calculator.rs");
+}
+
+/// Walks both state machines in lockstep and compares the transitions and accepted regexes of
+/// every pair of states for all the characters.
+fn assert_same_state_machine(regex_set: &RegexSet, table: &CompressedTable, characters: &str) {
+    let mut visited = HashSet::new();
+    let mut pairs = vec![(regex_set.get_start_state(0), table.get_start_state(0))];
+
+    while let Some((state, table_state)) = pairs.pop() {
+        if !visited.insert((state, table_state)) {
+            continue;
+        }
+
+        assert_eq!(
+            regex_set.get_accepted_regex(state),
+            table.get_accepted_rule(table_state)
+        );
+        for character in characters.chars() {
+            match (
+                regex_set.get_next_state(state, character),
+                table.get_next_state(table_state, character),
+            ) {
+                (Some(next_state), Some(next_table_state)) => {
+                    pairs.push((next_state, next_table_state))
+                }
+                (None, None) => {}
+                other => panic!("Different transitions for {:?}: {:?}", character, other),
+            }
+        }
+    }
+}
+
+#[test]
+fn look_up_the_same_transitions_as_the_regex_set() {
+    let regexes = [
+        "if",
+        "else",
+        "[a-z_][a-z0-9_]*",
+        "[0-9]+(\".\"[0-9]+)?",
+        "\"<=\"|\">=\"|\"<\"|\">\"",
+        "[^a-z0-9]",
+    ];
+    let regex_set = RegexSet::new(
+        regexes
+            .iter()
+            .map(|regex| get_regex_syntax_tree(regex))
+            .collect(),
+    );
+    let table = CompressedTable::new(&regex_set, 1);
+
+    assert_same_state_machine(&regex_set, &table, "iflsezx_09.<>= \n\u{e9}\u{1f600}");
+}
+
+#[test]
+fn group_characters_into_equivalence_classes() {
+    let lexer = LexerSpec::new()
+        .rule("[a-z]+", |_| ())
+        .rule("[0-9]+", |_| ())
+        .rule(" ", |_| ())
+        .build()
+        .unwrap();
+    let table = lexer.compress_table();
+
+    assert_eq!(table.get_class('a'), table.get_class('z'));
+    assert_eq!(table.get_class('0'), table.get_class('9'));
+    assert_ne!(table.get_class('a'), table.get_class('0'));
+    assert_eq!(None, table.get_class('A'));
+
+    let report = table.get_size_report();
+    assert_eq!(3, report.class_count);
+    assert_eq!(3, report.class_range_count);
+    assert_eq!(report.state_count * 3, report.dense_entry_count);
+}
+
+#[test]
+fn compress_tables_of_lexers_with_many_states() {
+    let keywords = [
+        "auto", "break", "case", "char", "const", "continue", "default", "do", "double", "else",
+        "enum", "extern", "float", "for", "goto", "if", "int", "long", "register", "return",
+    ];
+    let mut spec = LexerSpec::new();
+    for keyword in &keywords {
+        spec = spec.rule(keyword, |_| ());
+    }
+    let lexer = spec
+        .rule("[a-z_][a-z0-9_]*", |_| ())
+        .rule("[0-9]+", |_| ())
+        .rule("[ \t\n]+", |_| ())
+        .build()
+        .unwrap();
+
+    let report = lexer.compress_table().get_size_report();
+
+    assert_eq!(lexer.get_state_count(), report.state_count);
+    assert!(report.compressed_entry_count * 2 < report.dense_entry_count);
+    assert!(report.to_string().starts_with(&format!(
+        "{} states, {} character classes",
+        report.state_count, report.class_count
+    )));
+}
+
+#[test]
+fn generated_compressed_lexer_is_up_to_date() {
+    common::assert_generated_file_is_up_to_date(
+        "compressed_calculator.rs",
+        &common::generate_calculator_lexer(true),
+    );
+}
+
+#[test]
+fn lex_with_the_generated_compressed_lexer() {
+    use compressed_calculator::Token;
+
+    let tokens: Vec<Token> = compressed_calculator::Lexer::new("if x <= 42 /* a * b */ iffy")
+        .map(|token| token.unwrap().token)
+        .collect();
+
+    assert_eq!(
+        vec![
+            Token::If,
+            Token::Id(String::from("x")),
+            Token::Comparison(String::from("<=")),
+            Token::Number(42),
+            Token::Id(String::from("iffy")),
+        ],
+        tokens
+    );
+    assert_eq!(
+        Some(Err(compressed_calculator::LexError {
+            span: compressed_calculator::Span::new(2, 3, 1, 3)
+        })),
+        compressed_calculator::Lexer::new("a !").nth(1)
+    );
+    assert_eq!(4, compressed_calculator::count_tokens("/* x */ a >= b 7"));
+}
+
+#[test]
+fn lex_long_input_with_the_generated_compressed_lexer() {
+    use compressed_calculator::Span;
+    use compressed_calculator::Token;
+
+    // Every line pair uses all rules and start conditions, characters outside of ASCII both in
+    // comments and as unmatched text, which belong to the default character class.
+    let lines = "if abc <= 42 /* ä *\n */ ü\n";
+    let repetitions = 1000;
+    let text = lines.repeat(repetitions);
+
+    let tokens: Vec<(Token, Span)> = compressed_calculator::Lexer::new(&text)
+        .recover_from_errors(|lexeme| Token::Unknown(lexeme.to_string()))
+        .map(|token| token.unwrap())
+        .map(|token| (token.token, token.span))
+        .collect();
+
+    let mut expected = Vec::new();
+    for repetition in 0..repetitions {
+        let start = repetition * lines.len();
+        let line = repetition * 2 + 1;
+        let unknown_start = start + lines.find('ü').unwrap();
+
+        expected.push((Token::If, Span::new(start, start + 2, line, 1)));
+        expected.push((
+            Token::Id(String::from("abc")),
+            Span::new(start + 3, start + 6, line, 4),
+        ));
+        expected.push((
+            Token::Comparison(String::from("<=")),
+            Span::new(start + 7, start + 9, line, 8),
+        ));
+        expected.push((
+            Token::Number(42),
+            Span::new(start + 10, start + 12, line, 11),
+        ));
+        expected.push((
+            Token::Unknown(String::from("ü\n")),
+            Span::new(unknown_start, start + lines.len(), line + 1, 5),
+        ));
+    }
+    assert_eq!(expected, tokens);
+}
@@ -0,0 +1,151 @@
+use lexer_with_dynamic_state_machine::KeywordDiagram;
+use lexer_with_dynamic_state_machine::KeywordMatch;
+
+mod common;
+
+mod keywords {
+    include!("lexers/keywords.rs");
+}
+
+const LETTERS: [(char, char); 2] = [('a', 'z'), ('A', 'Z')];
+const LETTERS_AND_DIGITS: [(char, char); 3] = [('a', 'z'), ('A', 'Z'), ('0', '9')];
+
+fn build_diagram() -> KeywordDiagram {
+    return KeywordDiagram::new(&["if", "then", "else"], &LETTERS, &LETTERS_AND_DIGITS);
+}
+
+#[test]
+fn build_the_states_of_the_hand_written_diagram() {
+    let diagram = build_diagram();
+
+    // Like states 0, 10 and 12-21 of `id_reserved_words_diagram`.
+    assert_eq!(12, diagram.get_state_count());
+    assert_eq!(
+        Some(2),
+        diagram.get_next_state(KeywordDiagram::START_STATE, 'i')
+    );
+    assert_eq!(Some(3), diagram.get_next_state(2, 'f'));
+    assert_eq!(Some(KeywordMatch::Keyword(0)), diagram.get_accepted(3));
+    assert_eq!(Some(KeywordMatch::Keyword(1)), diagram.get_accepted(7));
+    assert_eq!(Some(KeywordMatch::Keyword(2)), diagram.get_accepted(11));
+
+    assert_eq!(
+        Some(KeywordDiagram::IDENTIFIER_STATE),
+        diagram.get_next_state(2, 'x')
+    );
+    assert_eq!(
+        Some(KeywordDiagram::IDENTIFIER_STATE),
+        diagram.get_next_state(3, '1')
+    );
+    assert_eq!(None, diagram.get_next_state(3, ' '));
+    assert_eq!(
+        None,
+        diagram.get_next_state(KeywordDiagram::START_STATE, '1')
+    );
+}
+
+#[test]
+fn share_states_of_common_prefixes() {
+    let diagram = KeywordDiagram::new(
+        &["else", "elsif", "end", "else"],
+        &LETTERS,
+        &LETTERS_AND_DIGITS,
+    );
+
+    // e, el, els, else, elsi, elsif, en, end
+    assert_eq!(10, diagram.get_state_count());
+    assert_eq!(3, diagram.get_keywords().len());
+}
+
+#[test]
+fn match_keywords_and_identifiers() {
+    let diagram = build_diagram();
+    let text = "if ifHello the then2 else";
+
+    assert_eq!(
+        Some((KeywordMatch::Keyword(0), 2)),
+        diagram.longest_match_at(text, 0)
+    );
+    assert_eq!(
+        Some((KeywordMatch::Identifier, 10)),
+        diagram.longest_match_at(text, 3)
+    );
+    assert_eq!(
+        Some((KeywordMatch::Identifier, 14)),
+        diagram.longest_match_at(text, 11)
+    );
+    assert_eq!(
+        Some((KeywordMatch::Identifier, 20)),
+        diagram.longest_match_at(text, 15)
+    );
+    assert_eq!(
+        Some((KeywordMatch::Keyword(2), 25)),
+        diagram.longest_match_at(text, 21)
+    );
+    assert_eq!(None, diagram.longest_match_at(text, 2));
+}
+
+#[test]
+fn prefixes_which_are_no_identifiers_are_not_accepted() {
+    let diagram = KeywordDiagram::new(&["<=", "x-y"], &LETTERS, &LETTERS);
+
+    assert_eq!(
+        Some((KeywordMatch::Keyword(0), 2)),
+        diagram.longest_match_at("<=", 0)
+    );
+    assert_eq!(None, diagram.longest_match_at("<", 0));
+    assert_eq!(
+        Some((KeywordMatch::Identifier, 1)),
+        diagram.longest_match_at("x-", 0)
+    );
+    assert_eq!(
+        Some((KeywordMatch::Keyword(1), 3)),
+        diagram.longest_match_at("x-y", 0)
+    );
+}
+
+#[test]
+fn generated_diagram_is_up_to_date() {
+    common::assert_generated_file_is_up_to_date("keywords.rs", &build_diagram().generate_rust());
+}
+
+#[test]
+fn generated_diagram_behaves_like_the_table() {
+    let diagram = build_diagram();
+
+    for text in &[
+        "if",
+        "iff",
+        "then",
+        "th",
+        "else",
+        "elsewhere",
+        "x1",
+        "1",
+        "i f",
+    ] {
+        let mut state = keywords::START_STATE;
+        let mut generated_match = None;
+        let mut table_state = KeywordDiagram::START_STATE;
+
+        for (offset, character) in text.char_indices() {
+            state = match keywords::get_next_state(state, character) {
+                Some(next_state) => next_state,
+                None => break,
+            };
+            table_state = diagram.get_next_state(table_state, character).unwrap();
+            assert_eq!(state, table_state);
+
+            if let Some(keyword) = keywords::get_keyword(state) {
+                generated_match = Some((KeywordMatch::Keyword(keyword), offset + 1));
+            } else if keywords::is_identifier(state) {
+                generated_match = Some((KeywordMatch::Identifier, offset + 1));
+            }
+        }
+
+        assert_eq!(diagram.longest_match_at(text, 0), generated_match);
+    }
+
+    assert_eq!(["if", "then", "else"], keywords::KEYWORDS);
+    assert_eq!(true, keywords::is_identifier(keywords::IDENTIFIER_STATE));
+}
@@ -0,0 +1,114 @@
+use lexer_with_dynamic_state_machine::KeywordStrategy;
+use lexer_with_dynamic_state_machine::Lexer;
+use lexer_with_dynamic_state_machine::LexerSpec;
+
+const KEYWORDS: [&str; 6] = ["if", "then", "else", "elsif", "while", "end"];
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Keyword(String),
+    Id(String),
+    Whitespace,
+}
+
+fn build_lexer(strategy: KeywordStrategy) -> Lexer<Token> {
+    return LexerSpec::new()
+        .identifiers(
+            "[a-z][a-z0-9]*",
+            &KEYWORDS,
+            strategy,
+            |lexeme| Token::Keyword(lexeme.to_string()),
+            |lexeme| Token::Id(lexeme.to_string()),
+        )
+        .rule(" +", |_| Token::Whitespace)
+        .build()
+        .unwrap();
+}
+
+fn get_tokens(lexer: &Lexer<Token>, text: &str) -> Vec<Token> {
+    return lexer
+        .tokens(text)
+        .map(|token| token.unwrap().value)
+        .filter(|token| *token != Token::Whitespace)
+        .collect();
+}
+
+#[test]
+fn both_strategies_create_the_same_tokens() {
+    let text = "if iffy then else elsif elsewhere end endif while x1";
+    let expected = vec![
+        Token::Keyword(String::from("if")),
+        Token::Id(String::from("iffy")),
+        Token::Keyword(String::from("then")),
+        Token::Keyword(String::from("else")),
+        Token::Keyword(String::from("elsif")),
+        Token::Id(String::from("elsewhere")),
+        Token::Keyword(String::from("end")),
+        Token::Id(String::from("endif")),
+        Token::Keyword(String::from("while")),
+        Token::Id(String::from("x1")),
+    ];
+
+    let state_machine = build_lexer(KeywordStrategy::StateMachine);
+    let lookup = build_lexer(KeywordStrategy::Lookup);
+
+    assert_eq!(expected, get_tokens(&state_machine, text));
+    assert_eq!(expected, get_tokens(&lookup, text));
+}
+
+#[test]
+fn match_keywords_with_quotes_and_backslashes() {
+    // Keywords are matched literally, although `"` and `\` are operators of the regex syntax.
+    let keywords = ["a\"b", "c\\d", "\"", "e*"];
+    let lexer = LexerSpec::new()
+        .identifiers(
+            "[a-z]+",
+            &keywords,
+            KeywordStrategy::StateMachine,
+            |lexeme| Token::Keyword(lexeme.to_string()),
+            |lexeme| Token::Id(lexeme.to_string()),
+        )
+        .rule(" +", |_| Token::Whitespace)
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        vec![
+            Token::Keyword(String::from("a\"b")),
+            Token::Keyword(String::from("c\\d")),
+            Token::Keyword(String::from("\"")),
+            Token::Keyword(String::from("e*")),
+            Token::Id(String::from("ee")),
+        ],
+        get_tokens(&lexer, "a\"b c\\d \" e* ee")
+    );
+}
+
+#[test]
+fn keywords_in_the_state_machine_need_more_states() {
+    let state_machine = build_lexer(KeywordStrategy::StateMachine);
+    let lookup = build_lexer(KeywordStrategy::Lookup);
+
+    assert!(state_machine.get_state_count() > lookup.get_state_count());
+    assert!(state_machine.get_transition_count() > lookup.get_transition_count());
+}
+
+#[test]
+fn automatic_strategy_depends_on_the_keyword_trie() {
+    assert_eq!(
+        KeywordStrategy::StateMachine,
+        KeywordStrategy::Automatic.resolve(&KEYWORDS)
+    );
+
+    let many_keywords: Vec<String> = (0..100).map(|index| format!("kw{}", index)).collect();
+    let many_keywords: Vec<&str> = many_keywords.iter().map(|name| name.as_str()).collect();
+    assert_eq!(
+        KeywordStrategy::Lookup,
+        KeywordStrategy::Automatic.resolve(&many_keywords)
+    );
+
+    assert_eq!(
+        KeywordStrategy::Lookup,
+        KeywordStrategy::Lookup.resolve(&KEYWORDS)
+    );
+}
@@ -0,0 +1,203 @@
+use lexer_with_dynamic_state_machine::LexFile;
+use lexer_with_dynamic_state_machine::LexFileError;
+use lexer_with_dynamic_state_machine::LexFileRule;
+use lexer_with_dynamic_state_machine::StartCondition;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Tag {
+    GreaterThan,
+    GreaterEquals,
+    LessThan,
+    LessEquals,
+    Equals,
+    NotEquals,
+}
+
+#[test]
+fn parse_the_comparison_lex_file() {
+    let lex_file = LexFile::parse(include_str!("../../lex-test/comparison.l")).unwrap();
+
+    assert_eq!(
+        "\tenum {\n\t\tGT=1, GE, LT, LE, EQ, NE\n\t};",
+        lex_file.declarations
+    );
+    assert_eq!(6, lex_file.rules.len());
+    assert_eq!(
+        LexFileRule {
+            start_conditions: Vec::new(),
+            pattern: String::from("\">=\""),
+            action: String::from("{return (GE);}"),
+            line: 10,
+        },
+        lex_file.rules[1]
+    );
+    assert_eq!("\nint main() {\n\tyylex();\n}", lex_file.user_code);
+}
+
+#[test]
+fn lex_with_the_rules_of_the_comparison_lex_file() {
+    let lex_file = LexFile::parse(include_str!("../../lex-test/comparison.l")).unwrap();
+    let lexer = lex_file
+        .to_lexer_spec(|rule| {
+            let tag = match rule.action.as_str() {
+                "{return (GT);}" => Tag::GreaterThan,
+                "{return (GE);}" => Tag::GreaterEquals,
+                "{return (LT);}" => Tag::LessThan,
+                "{return (LE);}" => Tag::LessEquals,
+                "{return (EQ);}" => Tag::Equals,
+                _ => Tag::NotEquals,
+            };
+
+            move |_: &mut _, _: &str| Some(tag.clone())
+        })
+        .build()
+        .unwrap();
+
+    let tags: Vec<Tag> = lexer
+        .tokens("<=<>>=>")
+        .map(|token| token.unwrap().value)
+        .collect();
+    assert_eq!(
+        vec![
+            Tag::LessEquals,
+            Tag::NotEquals,
+            Tag::GreaterEquals,
+            Tag::GreaterThan
+        ],
+        tags
+    );
+}
+
+#[test]
+fn parse_definitions_and_start_conditions() {
+    let lex_file = LexFile::parse(
+        "/* Numbers\n   and strings */\nDIGIT [0-9]\n%x STRING\n%s KEYWORDS\n%option noyywrap\n%%\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        vec![(String::from("DIGIT"), String::from("[0-9]"))],
+        lex_file.definitions
+    );
+    assert_eq!(
+        vec![
+            StartCondition {
+                name: String::from("STRING"),
+                is_exclusive: true,
+            },
+            StartCondition {
+                name: String::from("KEYWORDS"),
+                is_exclusive: false,
+            },
+        ],
+        lex_file.start_conditions
+    );
+    assert_eq!(vec![String::from("noyywrap")], lex_file.options);
+}
+
+#[test]
+fn parse_rules_of_start_conditions() {
+    let lex_file = LexFile::parse(
+        "%x STRING\n%%\n<INITIAL,STRING>\"a b\" {\n  a();\n}\n<STRING>{\n    [^ ]+ b();\n    <*>\" \" c();\n}\n<= d();\n",
+    )
+    .unwrap();
+
+    let rules: Vec<(Vec<&str>, &str, &str)> = lex_file
+        .rules
+        .iter()
+        .map(|rule| {
+            (
+                rule.start_conditions
+                    .iter()
+                    .map(|start_condition| start_condition.as_str())
+                    .collect(),
+                rule.pattern.as_str(),
+                rule.action.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        vec![
+            (vec!["INITIAL", "STRING"], "\"a b\"", "{\n  a();\n}"),
+            (vec!["STRING"], "[^ ]+", "b();"),
+            (vec!["*", "STRING"], "\" \"", "c();"),
+            (Vec::new(), "<=", "d();"),
+        ],
+        rules
+    );
+}
+
+#[test]
+fn share_the_action_of_the_next_rule() {
+    let lex_file = LexFile::parse("%%\nif |\nthen |\nelse return KEYWORD;\n").unwrap();
+
+    let actions: Vec<&str> = lex_file
+        .rules
+        .iter()
+        .map(|rule| rule.action.as_str())
+        .collect();
+    assert_eq!(
+        vec!["return KEYWORD;", "return KEYWORD;", "return KEYWORD;"],
+        actions
+    );
+}
+
+#[test]
+fn accept_directives_of_flex_without_effect() {
+    let lex_file = LexFile::parse(
+        "%top{\n    // Braces in comments and strings, like }, do not close the block.\n    const CLOSE: &str = \"}\";\n}\n%array\n%pointer\n%%\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        "    // Braces in comments and strings, like }, do not close the block.\n    const CLOSE: &str = \"}\";",
+        lex_file.top_code
+    );
+    assert_eq!(
+        vec![
+            String::from("The directive %array has no effect (at line 5)"),
+            String::from("The directive %pointer has no effect (at line 6)"),
+        ],
+        lex_file.warnings
+    );
+    assert_eq!(true, lex_file.declarations.is_empty());
+}
+
+#[test]
+fn ignore_braces_in_comments_of_actions() {
+    let lex_file = LexFile::parse("%%\na {\n  /* { */ x(); // {\n}\nb { y(); /* } */ }\n").unwrap();
+
+    let actions: Vec<&str> = lex_file
+        .rules
+        .iter()
+        .map(|rule| rule.action.as_str())
+        .collect();
+    assert_eq!(
+        vec!["{\n  /* { */ x(); // {\n}", "{ y(); /* } */ }"],
+        actions
+    );
+}
+
+#[test]
+fn fail_on_invalid_lex_files() {
+    assert_eq!(
+        Err(LexFileError::MissingRulesSection),
+        LexFile::parse("DIGIT [0-9]\n")
+    );
+    assert_eq!(
+        Err(LexFileError::UnterminatedCodeBlock(1)),
+        LexFile::parse("%{\nint x;\n%%\n")
+    );
+    assert_eq!(
+        Err(LexFileError::UnterminatedAction(2)),
+        LexFile::parse("%%\nif { return IF;\n")
+    );
+    assert_eq!(
+        Err(LexFileError::UnknownDirective(String::from("%yylineno"), 1)),
+        LexFile::parse("%yylineno\n%%\n")
+    );
+    assert_eq!(
+        Err(LexFileError::MissingNextAction(2)),
+        LexFile::parse("%%\nif |\n")
+    );
+}
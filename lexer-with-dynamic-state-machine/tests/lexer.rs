@@ -0,0 +1,242 @@
+use lexer_with_dynamic_state_machine::LexError;
+use lexer_with_dynamic_state_machine::Lexer;
+use lexer_with_dynamic_state_machine::LexerSpec;
+use lexer_with_dynamic_state_machine::Span;
+use lexer_with_dynamic_state_machine::SpecError;
+use lexer_with_dynamic_state_machine::Token;
+use lexer_with_dynamic_state_machine::TokenError;
+use regex::RegexError;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Tag {
+    If,
+    Then,
+    Else,
+    Id,
+    Number,
+    Float,
+    LessEquals,
+    NotEquals,
+    LessThan,
+    Whitespace,
+    Unknown,
+}
+
+fn get_lexer() -> Lexer<Tag> {
+    return LexerSpec::new()
+        .define("LNUM", "[0-9]+")
+        .define("DNUM", "([0-9]*\".\"[0-9]+)|([0-9]+\".\"[0-9]*)")
+        .rule("if", |_| Tag::If)
+        .rule("then", |_| Tag::Then)
+        .rule("else", |_| Tag::Else)
+        .rule("[a-z][a-z0-9]*", |_| Tag::Id)
+        .rule("{LNUM}", |_| Tag::Number)
+        .rule("{DNUM}", |_| Tag::Float)
+        .rule("<=", |_| Tag::LessEquals)
+        .rule("<>", |_| Tag::NotEquals)
+        .rule("<", |_| Tag::LessThan)
+        .rule("[ \n]+", |_| Tag::Whitespace)
+        .build()
+        .unwrap();
+}
+
+fn get_tags(lexer: &Lexer<Tag>, text: &str) -> Vec<Tag> {
+    return lexer
+        .tokens(text)
+        .map(|token| token.unwrap().value)
+        .filter(|tag| tag != &Tag::Whitespace)
+        .collect();
+}
+
+#[test]
+fn lex_keywords_and_identifiers() {
+    let lexer = get_lexer();
+
+    assert_eq!(
+        vec![Tag::If, Tag::Id, Tag::Then, Tag::Id, Tag::Else, Tag::Id],
+        get_tags(&lexer, "if hello then hi else bye")
+    );
+}
+
+#[test]
+fn take_the_longest_match() {
+    let lexer = get_lexer();
+
+    assert_eq!(
+        vec![Tag::Id, Tag::Id, Tag::Id, Tag::Id],
+        get_tags(&lexer, "iffy thens elsewhere i")
+    );
+    assert_eq!(
+        vec![
+            Tag::LessEquals,
+            Tag::NotEquals,
+            Tag::LessThan,
+            Tag::LessThan
+        ],
+        get_tags(&lexer, "<= <> < <")
+    );
+    assert_eq!(
+        vec![Tag::Number, Tag::Float, Tag::Float],
+        get_tags(&lexer, "12 1.5 3.")
+    );
+}
+
+#[test]
+fn first_rule_wins_for_equal_matches() {
+    let lexer = LexerSpec::new()
+        .rule("[a-z]+", |_| Tag::Id)
+        .rule("if", |_| Tag::If)
+        .build()
+        .unwrap();
+
+    assert_eq!(vec![Tag::Id], get_tags(&lexer, "if"));
+}
+
+#[test]
+fn report_token_positions() {
+    let lexer = get_lexer();
+    let tokens: Vec<Token<Tag>> = lexer.tokens("if x1").map(|token| token.unwrap()).collect();
+
+    assert_eq!(
+        Token {
+            value: Tag::Id,
+            lexeme: "x1",
+            span: Span::new(3, 5, 1, 4),
+        },
+        tokens[2]
+    );
+}
+
+#[test]
+fn create_token_values_from_lexemes() {
+    let lexer = LexerSpec::new()
+        .rule("[0-9]+", |lexeme| lexeme.parse::<u32>().unwrap())
+        .rule(" ", |_| 0)
+        .build()
+        .unwrap();
+    let values: Vec<u32> = lexer
+        .tokens("12 7")
+        .map(|token| token.unwrap().value)
+        .collect();
+
+    assert_eq!(vec![12, 0, 7], values);
+}
+
+#[test]
+fn stop_at_unmatched_text() {
+    let lexer = get_lexer();
+    let tokens: Vec<Result<Tag, TokenError>> = lexer
+        .tokens("if ? then")
+        .map(|token| token.map(|token| token.value))
+        .collect();
+
+    assert_eq!(
+        vec![
+            Ok(Tag::If),
+            Ok(Tag::Whitespace),
+            Err(TokenError::Lex(LexError {
+                span: Span::new(3, 4, 1, 4),
+                text: String::from("?"),
+            }))
+        ],
+        tokens
+    );
+}
+
+#[test]
+fn continue_after_unmatched_text() {
+    let lexer = get_lexer();
+    let tokens: Vec<Token<Tag>> = lexer
+        .tokens("if ?? then\n?")
+        .recover_from_errors(|_| Tag::Unknown)
+        .map(|token| token.unwrap())
+        .filter(|token| token.value != Tag::Whitespace)
+        .collect();
+
+    assert_eq!(
+        vec![
+            Token {
+                value: Tag::If,
+                lexeme: "if",
+                span: Span::new(0, 2, 1, 1),
+            },
+            Token {
+                value: Tag::Unknown,
+                lexeme: "??",
+                span: Span::new(3, 5, 1, 4),
+            },
+            Token {
+                value: Tag::Then,
+                lexeme: "then",
+                span: Span::new(6, 10, 1, 7),
+            },
+            Token {
+                value: Tag::Unknown,
+                lexeme: "?",
+                span: Span::new(11, 12, 2, 1),
+            },
+        ],
+        tokens
+    );
+}
+
+#[test]
+fn recover_from_long_unmatched_text_in_linear_time() {
+    let lexer = LexerSpec::new()
+        .rule("[a-z]*X", |_| Tag::Id)
+        .rule(";", |_| Tag::Else)
+        .build()
+        .unwrap();
+    // Every position starts a match of the first rule which only fails at the `;`, so trying
+    // each position on its own reads the text quadratically often.
+    let text = format!("{};aX", "a".repeat(100_000));
+    let tokens: Vec<(Tag, usize, usize)> = lexer
+        .tokens(&text)
+        .recover_from_errors(|_| Tag::Unknown)
+        .map(|token| token.unwrap())
+        .map(|token| (token.value, token.span.start, token.span.end))
+        .collect();
+
+    assert_eq!(
+        vec![
+            (Tag::Unknown, 0, 100_000),
+            (Tag::Else, 100_000, 100_001),
+            (Tag::Id, 100_001, 100_003),
+        ],
+        tokens
+    );
+}
+
+#[test]
+fn fail_on_undefined_definition() {
+    let error = LexerSpec::new()
+        .rule("{DIGIT}+", |_| Tag::Number)
+        .build()
+        .err();
+
+    assert_eq!(
+        Some(SpecError::Regex(RegexError::UndefinedDefinition(
+            String::from("DIGIT"),
+            regex::Span::new(0, 7)
+        ))),
+        error
+    );
+}
+
+#[test]
+fn count_lines_and_columns() {
+    let lexer = LexerSpec::new()
+        .rule("[a-z0-9]+", |_| Tag::Id)
+        .rule("<", |_| Tag::LessThan)
+        .rule("[ \t\r\n]+", |_| Tag::Whitespace)
+        .build()
+        .unwrap();
+    let spans: Vec<(usize, usize)> = lexer
+        .tokens("if\r\n\tx <\n\n 1\r2")
+        .map(|token| token.unwrap())
+        .filter(|token| token.value != Tag::Whitespace)
+        .map(|token| (token.span.line, token.span.column))
+        .collect();
+
+    assert_eq!(vec![(1, 1), (2, 9), (2, 11), (4, 2), (5, 1)], spans);
+}
@@ -0,0 +1,387 @@
+// Generated by lexer-with-dynamic-state-machine, do not edit.
+
+// The tokens of a small calculator language.
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Token {
+    If,
+    Id(String),
+    Number(u64),
+    Comparison(String),
+    Unknown(String),
+}
+
+pub const INITIAL: usize = 0;
+pub const COMMENT: usize = 1;
+
+/// The part of the lexed text a token was created from. `start` and `end` are byte offsets,
+/// `line` and `column` are the position of the start, both counted from 1.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Self {
+        Span {
+            start,
+            end,
+            line,
+            column,
+        }
+    }
+}
+
+impl std::fmt::Display for Span {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        return write!(formatter, "{}:{}", self.line, self.column);
+    }
+}
+
+/// A token together with the text it was created from and where that text is.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenMatch<'t> {
+    pub token: Token,
+    pub lexeme: &'t str,
+    pub span: Span,
+}
+
+/// No rule matches the text at the start of `span`, which covers the first character.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LexError {
+    pub span: Span,
+}
+
+/// Tabs advance the column to the next multiple of the tab width.
+const TAB_WIDTH: usize = 8;
+
+/// Counts lines and columns while the text is lexed from the start to the end. `\n`, `\r\n`
+/// and a single `\r` all end a line.
+struct LineCounter {
+    offset: usize,
+    line: usize,
+    column: usize,
+    is_after_carriage_return: bool,
+}
+
+impl LineCounter {
+    fn get_span(&mut self, text: &str, start: usize, end: usize) -> Span {
+        self.advance_to(text, start);
+        let span = Span::new(start, end, self.line, self.column);
+        self.advance_to(text, end);
+
+        return span;
+    }
+
+    fn advance_to(&mut self, text: &str, offset: usize) {
+        for character in text[self.offset..offset].chars() {
+            match character {
+                '\n' if self.is_after_carriage_return => {}
+                '\n' | '\r' => {
+                    self.line += 1;
+                    self.column = 1;
+                }
+                '\t' => self.column += TAB_WIDTH - (self.column - 1) % TAB_WIDTH,
+                _ => self.column += 1,
+            }
+
+            self.is_after_carriage_return = character == '\r';
+        }
+        self.offset = offset;
+    }
+}
+
+/// Iterates over the tokens of a text. The iterator stops after the first error, unless it
+/// recovers from errors.
+pub struct Lexer<'t> {
+    text: &'t str,
+    position: Option<usize>,
+    start_condition: usize,
+    start_condition_stack: Vec<usize>,
+    error_token: Option<fn(&'t str) -> Token>,
+    line_counter: LineCounter,
+}
+
+#[allow(dead_code)]
+impl<'t> Lexer<'t> {
+    pub fn new(text: &'t str) -> Self {
+        Lexer {
+            text,
+            position: Some(0),
+            start_condition: INITIAL,
+            start_condition_stack: Vec::new(),
+            error_token: None,
+            line_counter: LineCounter {
+                offset: 0,
+                line: 1,
+                column: 1,
+                is_after_carriage_return: false,
+            },
+        }
+    }
+
+    /// Keeps lexing after errors. All text up to the next position where a rule matches becomes
+    /// a single token created by `error_token`.
+    pub fn recover_from_errors(mut self, error_token: fn(&'t str) -> Token) -> Self {
+        self.error_token = Some(error_token);
+
+        return self;
+    }
+
+    pub fn begin(&mut self, start_condition: usize) {
+        self.start_condition = start_condition;
+    }
+
+    pub fn push_state(&mut self, start_condition: usize) {
+        self.start_condition_stack.push(self.start_condition);
+        self.start_condition = start_condition;
+    }
+
+    pub fn pop_state(&mut self) {
+        match self.start_condition_stack.pop() {
+            Some(start_condition) => self.start_condition = start_condition,
+            None => panic!("Start condition stack underflow"),
+        }
+    }
+
+    pub fn current_state(&self) -> usize {
+        return self.start_condition;
+    }
+
+    fn get_longest_match(&self, start: usize) -> Option<(usize, usize)> {
+        let mut state = START_STATES[self.start_condition];
+        let mut longest_match = get_accepted_rule(state).map(|rule| (rule, start));
+
+        for (offset, character) in self.text[start..].char_indices() {
+            state = match get_next_state(state, character) {
+                Some(next_state) => next_state,
+                None => break,
+            };
+
+            if let Some(rule) = get_accepted_rule(state) {
+                longest_match = Some((rule, start + offset + character.len_utf8()));
+            }
+        }
+
+        return longest_match;
+    }
+
+    /// Returns the end of the unmatched text starting at `start`. Every position after `start`
+    /// starts a thread in the state machine, threads reaching the same state are merged into the
+    /// one which started first, so the text is read only once.
+    fn find_next_token_start(&self, start: usize) -> usize {
+        let start_state = START_STATES[self.start_condition];
+        let first_length = self.text[start..].chars().next().map_or(0, char::len_utf8);
+        // Pairs of the current state and the start of the thread, ordered by their starts.
+        let mut threads: Vec<(usize, usize)> = Vec::new();
+        let mut token_start: Option<usize> = None;
+
+        for (offset, character) in self.text[start + first_length..].char_indices() {
+            if token_start.is_none() {
+                threads.push((start_state, start + first_length + offset));
+            }
+
+            let mut next_threads: Vec<(usize, usize)> = Vec::with_capacity(threads.len());
+            for (state, thread_start) in threads {
+                let next_state = match get_next_state(state, character) {
+                    Some(next_state) => next_state,
+                    None => continue,
+                };
+                if next_threads.iter().any(|(state, _)| *state == next_state) {
+                    continue;
+                }
+
+                next_threads.push((next_state, thread_start));
+                if get_accepted_rule(next_state).is_some()
+                    && token_start.is_none_or(|token_start| thread_start < token_start)
+                {
+                    token_start = Some(thread_start);
+                }
+            }
+            threads = next_threads;
+
+            if let Some(token_start) = token_start {
+                threads.retain(|(_, thread_start)| *thread_start < token_start);
+                if threads.is_empty() {
+                    return token_start;
+                }
+            }
+        }
+
+        return token_start.unwrap_or(self.text.len());
+    }
+
+    #[allow(unused_variables)]
+    fn run_action(&mut self, rule: usize, lexeme: &'t str) -> Option<Token> {
+        let lexer = self;
+
+        return match rule {
+            // Line 7: if
+            0 => Some(Token::If),
+            // Line 8: [a-z]+
+            1 => Some(Token::Id(lexeme.to_string())),
+            // Line 9: {DIGIT}+
+            2 => Some(Token::Number(lexeme.parse().unwrap())),
+            // Line 10: "<="
+            3 => Some(Token::Comparison(lexeme.to_string())),
+            // Line 11: ">="
+            4 => Some(Token::Comparison(lexeme.to_string())),
+            // Line 12: "/*"
+            5 => {
+                lexer.begin(COMMENT);
+                None
+            }
+            // Line 16: "*/"
+            6 => { lexer.begin(INITIAL); None }
+            // Line 17: [^*]+|"*"
+            7 => None,
+            // Line 18: " "+
+            8 => None,
+            _ => None,
+        };
+    }
+}
+
+impl<'t> Iterator for Lexer<'t> {
+    type Item = Result<TokenMatch<'t>, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let start = self.position?;
+            if start == self.text.len() {
+                self.position = None;
+                return None;
+            }
+
+            // Empty matches would never advance the lexer, they are treated like no match at all.
+            match self.get_longest_match(start) {
+                Some((rule, end)) if end > start => {
+                    self.position = Some(end);
+                    let lexeme = &self.text[start..end];
+                    // Skipped text is counted as well, the line counter has to see all line
+                    // breaks.
+                    let span = self.line_counter.get_span(self.text, start, end);
+
+                    if let Some(token) = self.run_action(rule, lexeme) {
+                        return Some(Ok(TokenMatch {
+                            token,
+                            lexeme,
+                            span,
+                        }));
+                    }
+                }
+                _ => {
+                    let error_token = match self.error_token {
+                        Some(error_token) => error_token,
+                        None => {
+                            self.position = None;
+                            let end = start
+                                + self.text[start..].chars().next().map_or(0, char::len_utf8);
+
+                            return Some(Err(LexError {
+                                span: self.line_counter.get_span(self.text, start, end),
+                            }));
+                        }
+                    };
+
+                    let end = self.find_next_token_start(start);
+                    self.position = Some(end);
+                    let lexeme = &self.text[start..end];
+
+                    return Some(Ok(TokenMatch {
+                        token: error_token(lexeme),
+                        lexeme,
+                        span: self.line_counter.get_span(self.text, start, end),
+                    }));
+                }
+            }
+        }
+    }
+}
+
+const START_STATES: [usize; 2] = [0, 1];
+
+#[allow(unreachable_patterns)]
+fn get_next_state(state: usize, character: char) -> Option<usize> {
+    return match state {
+        0 => match character {
+            ' ' => Some(2),
+            '/' => Some(3),
+            '0'..='9' => Some(4),
+            '<' => Some(5),
+            '>' => Some(6),
+            'a'..='h' | 'j'..='z' => Some(7),
+            'i' => Some(8),
+            _ => None,
+        },
+        1 => match character {
+            '\0'..=')' | '+'..='\u{10ffff}' => Some(9),
+            '*' => Some(10),
+            _ => None,
+        },
+        2 => match character {
+            ' ' => Some(2),
+            _ => None,
+        },
+        3 => match character {
+            '*' => Some(11),
+            _ => None,
+        },
+        4 => match character {
+            '0'..='9' => Some(4),
+            _ => None,
+        },
+        5 => match character {
+            '=' => Some(12),
+            _ => None,
+        },
+        6 => match character {
+            '=' => Some(13),
+            _ => None,
+        },
+        7 => match character {
+            'a'..='z' => Some(7),
+            _ => None,
+        },
+        8 => match character {
+            'a'..='e' | 'g'..='z' => Some(7),
+            'f' => Some(14),
+            _ => None,
+        },
+        9 => match character {
+            '\0'..=')' | '+'..='\u{10ffff}' => Some(9),
+            _ => None,
+        },
+        10 => match character {
+            '/' => Some(15),
+            _ => None,
+        },
+        14 => match character {
+            'a'..='z' => Some(7),
+            _ => None,
+        },
+        _ => None,
+    };
+}
+
+fn get_accepted_rule(state: usize) -> Option<usize> {
+    return match state {
+        14 => Some(0),
+        7 | 8 => Some(1),
+        4 => Some(2),
+        12 => Some(3),
+        13 => Some(4),
+        11 => Some(5),
+        15 => Some(6),
+        9 | 10 => Some(7),
+        2 => Some(8),
+        _ => None,
+    };
+}
+
+pub fn count_tokens(text: &str) -> usize {
+    return Lexer::new(text).count();
+}
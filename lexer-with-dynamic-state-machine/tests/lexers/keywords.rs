@@ -0,0 +1,83 @@
+// Generated by lexer-with-dynamic-state-machine, do not edit.
+
+pub const KEYWORDS: [&str; 3] = ["if", "then", "else"];
+pub const START_STATE: usize = 0;
+pub const IDENTIFIER_STATE: usize = 1;
+
+#[allow(unreachable_patterns)]
+pub fn get_next_state(state: usize, character: char) -> Option<usize> {
+    return match state {
+        0 => match character {
+            'e' => Some(8),
+            'i' => Some(2),
+            't' => Some(4),
+            'a'..='z' | 'A'..='Z' => Some(1),
+            _ => None,
+        },
+        1 => match character {
+            'a'..='z' | 'A'..='Z' | '0'..='9' => Some(1),
+            _ => None,
+        },
+        2 => match character {
+            'f' => Some(3),
+            'a'..='z' | 'A'..='Z' | '0'..='9' => Some(1),
+            _ => None,
+        },
+        3 => match character {
+            'a'..='z' | 'A'..='Z' | '0'..='9' => Some(1),
+            _ => None,
+        },
+        4 => match character {
+            'h' => Some(5),
+            'a'..='z' | 'A'..='Z' | '0'..='9' => Some(1),
+            _ => None,
+        },
+        5 => match character {
+            'e' => Some(6),
+            'a'..='z' | 'A'..='Z' | '0'..='9' => Some(1),
+            _ => None,
+        },
+        6 => match character {
+            'n' => Some(7),
+            'a'..='z' | 'A'..='Z' | '0'..='9' => Some(1),
+            _ => None,
+        },
+        7 => match character {
+            'a'..='z' | 'A'..='Z' | '0'..='9' => Some(1),
+            _ => None,
+        },
+        8 => match character {
+            'l' => Some(9),
+            'a'..='z' | 'A'..='Z' | '0'..='9' => Some(1),
+            _ => None,
+        },
+        9 => match character {
+            's' => Some(10),
+            'a'..='z' | 'A'..='Z' | '0'..='9' => Some(1),
+            _ => None,
+        },
+        10 => match character {
+            'e' => Some(11),
+            'a'..='z' | 'A'..='Z' | '0'..='9' => Some(1),
+            _ => None,
+        },
+        11 => match character {
+            'a'..='z' | 'A'..='Z' | '0'..='9' => Some(1),
+            _ => None,
+        },
+        _ => None,
+    };
+}
+
+pub fn get_keyword(state: usize) -> Option<usize> {
+    return match state {
+        3 => Some(0),
+        7 => Some(1),
+        11 => Some(2),
+        _ => None,
+    };
+}
+
+pub fn is_identifier(state: usize) -> bool {
+    return matches!(state, 1 | 2 | 4 | 5 | 6 | 8 | 9 | 10);
+}
@@ -0,0 +1,170 @@
+use lexer_with_dynamic_state_machine::LexerSpec;
+use lexer_with_dynamic_state_machine::RedeclarationError;
+use lexer_with_dynamic_state_machine::ScopedSymbolTable;
+use lexer_with_dynamic_state_machine::Span;
+use lexer_with_dynamic_state_machine::SymbolId;
+use lexer_with_dynamic_state_machine::SymbolTable;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Kind {
+    Variable,
+    Function,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Type {
+    Int,
+    Bool,
+}
+
+fn span(start: usize) -> Span {
+    return Span::new(start, start + 1, 1, start + 1);
+}
+
+#[test]
+fn look_up_declarations() {
+    let mut names = SymbolTable::new();
+    let x = names.intern("x");
+    let y = names.intern("y");
+    let mut symbol_table = ScopedSymbolTable::new(&mut names);
+    symbol_table
+        .declare(x, Kind::Variable, Type::Int, span(0))
+        .unwrap();
+
+    let lookup = symbol_table.lookup(x).unwrap();
+    assert_eq!(x, lookup.declaration.name);
+    assert_eq!(Kind::Variable, lookup.declaration.kind);
+    assert_eq!(Type::Int, lookup.declaration.symbol_type);
+    assert_eq!(span(0), lookup.declaration.span);
+    assert_eq!(0, lookup.scope_depth);
+    assert_eq!("x", symbol_table.symbol_table().get_name(x));
+    assert_eq!(true, symbol_table.lookup(y).is_none());
+}
+
+#[test]
+fn shadow_declarations_of_outer_scopes() {
+    let mut names = SymbolTable::new();
+    let mut symbol_table = ScopedSymbolTable::new(&mut names);
+    let x = symbol_table.intern("x");
+    let f = symbol_table.intern("f");
+    symbol_table
+        .declare(x, Kind::Variable, Type::Int, span(0))
+        .unwrap();
+    symbol_table
+        .declare(f, Kind::Function, Type::Int, span(2))
+        .unwrap();
+
+    symbol_table.enter_scope();
+    symbol_table
+        .declare(x, Kind::Variable, Type::Bool, span(4))
+        .unwrap();
+    let lookup = symbol_table.lookup(x).unwrap();
+    assert_eq!(
+        (Type::Bool, 1),
+        (lookup.declaration.symbol_type.clone(), lookup.scope_depth)
+    );
+    let lookup = symbol_table.lookup(f).unwrap();
+    assert_eq!(
+        (Kind::Function, 0),
+        (lookup.declaration.kind.clone(), lookup.scope_depth)
+    );
+    assert_eq!(true, symbol_table.lookup_in_current_scope(f).is_none());
+
+    let declarations = symbol_table.exit_scope();
+    assert_eq!(1, declarations.len());
+    assert_eq!(span(4), declarations[0].span);
+    let lookup = symbol_table.lookup(x).unwrap();
+    assert_eq!(
+        (Type::Int, 0),
+        (lookup.declaration.symbol_type.clone(), lookup.scope_depth)
+    );
+}
+
+#[test]
+fn fail_on_redeclaration_in_the_same_scope() {
+    let mut names = SymbolTable::new();
+    let x = names.intern("x");
+    let mut symbol_table = ScopedSymbolTable::new(&mut names);
+    symbol_table.enter_scope();
+    symbol_table
+        .declare(x, Kind::Variable, Type::Int, span(0))
+        .unwrap();
+
+    assert_eq!(
+        Err(RedeclarationError {
+            name: String::from("x"),
+            span: span(5),
+            previous_span: span(0),
+        }),
+        symbol_table.declare(x, Kind::Variable, Type::Bool, span(5))
+    );
+    assert_eq!(1, symbol_table.get_scope_depth());
+}
+
+#[test]
+fn return_declarations_of_exited_scopes_in_source_order() {
+    let mut names = SymbolTable::new();
+    let mut symbol_table = ScopedSymbolTable::new(&mut names);
+    symbol_table.enter_scope();
+    for (index, name) in ["c", "a", "b"].iter().enumerate() {
+        let id = symbol_table.intern(name);
+        symbol_table
+            .declare(id, Kind::Variable, Type::Int, span(index * 2))
+            .unwrap();
+    }
+
+    let declarations = symbol_table.exit_scope();
+
+    let declared_names: Vec<&str> = declarations
+        .iter()
+        .map(|declaration| symbol_table.symbol_table().get_name(declaration.name))
+        .collect();
+    assert_eq!(vec!["c", "a", "b"], declared_names);
+    let a = symbol_table.intern("a");
+    assert_eq!(true, symbol_table.lookup(a).is_none());
+}
+
+#[test]
+fn declare_the_identifiers_of_the_lexer() {
+    let lexer = LexerSpec::new()
+        .rule_with_actions("[a-z]+", |actions, lexeme| Some(actions.intern(lexeme)))
+        .skip(" +")
+        .build()
+        .unwrap();
+    let mut tokens = lexer.tokens("x y x");
+    let identifiers: Vec<(SymbolId, Span)> = tokens
+        .by_ref()
+        .map(|token| token.unwrap())
+        .map(|token| (token.value, token.span))
+        .collect();
+    let mut names = tokens.into_symbol_table();
+    let mut symbol_table = ScopedSymbolTable::new(&mut names);
+
+    let (x, x_span) = identifiers[0];
+    symbol_table
+        .declare(x, Kind::Variable, Type::Int, x_span)
+        .unwrap();
+    let (y, y_span) = identifiers[1];
+    symbol_table
+        .declare(y, Kind::Function, Type::Bool, y_span)
+        .unwrap();
+
+    let (same_x, _) = identifiers[2];
+    assert_eq!(x, same_x);
+    assert_eq!(
+        Some(x_span),
+        symbol_table
+            .lookup(same_x)
+            .map(|lookup| lookup.declaration.span)
+    );
+    assert_eq!(2, symbol_table.symbol_table().len());
+}
+
+#[test]
+#[should_panic(expected = "The global scope can not be exited")]
+fn panic_when_exiting_the_global_scope() {
+    let mut names = SymbolTable::new();
+    let mut symbol_table: ScopedSymbolTable<Kind, Type> = ScopedSymbolTable::new(&mut names);
+
+    symbol_table.exit_scope();
+}
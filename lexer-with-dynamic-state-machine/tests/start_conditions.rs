@@ -0,0 +1,151 @@
+use lexer_with_dynamic_state_machine::Lexer;
+use lexer_with_dynamic_state_machine::LexerSpec;
+use lexer_with_dynamic_state_machine::SpecError;
+use lexer_with_dynamic_state_machine::ALL_CONDITIONS;
+use lexer_with_dynamic_state_machine::INITIAL;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Tag {
+    If,
+    Id(String),
+    Quotation(String),
+    Comment,
+    Whitespace,
+}
+
+fn get_tags(lexer: &Lexer<Tag>, text: &str) -> Vec<Tag> {
+    return lexer
+        .tokens(text)
+        .map(|token| token.unwrap().value)
+        .filter(|tag| tag != &Tag::Whitespace)
+        .collect();
+}
+
+fn get_quotation_lexer() -> Lexer<Tag> {
+    return LexerSpec::new()
+        .exclusive_condition("QUOTATION")
+        .rule("if", |_| Tag::If)
+        .rule("[a-z]+", |lexeme| Tag::Id(lexeme.to_string()))
+        .rule(" +", |_| Tag::Whitespace)
+        .rule_with_actions("'", |actions, _| {
+            actions.begin("QUOTATION");
+            None
+        })
+        .in_conditions(&["QUOTATION"], |spec| {
+            spec.rule("[^']+", |lexeme| Tag::Quotation(lexeme.to_string()))
+                .rule_with_actions("'", |actions, _| {
+                    actions.begin(INITIAL);
+                    None
+                })
+        })
+        .build()
+        .unwrap();
+}
+
+#[test]
+fn only_match_rules_of_the_exclusive_condition() {
+    let lexer = get_quotation_lexer();
+
+    assert_eq!(
+        vec![
+            Tag::If,
+            Tag::Id(String::from("hello")),
+            Tag::Quotation(String::from("if else")),
+            Tag::Id(String::from("bye")),
+        ],
+        get_tags(&lexer, "if hello 'if else' bye")
+    );
+}
+
+#[test]
+fn keep_the_start_condition_between_tokens() {
+    let lexer = get_quotation_lexer();
+    let mut tokens = lexer.tokens("a 'b' c");
+
+    assert_eq!(
+        Tag::Id(String::from("a")),
+        tokens.next().unwrap().unwrap().value
+    );
+    assert_eq!(INITIAL, tokens.current_state());
+    assert_eq!(Tag::Whitespace, tokens.next().unwrap().unwrap().value);
+    assert_eq!(
+        Tag::Quotation(String::from("b")),
+        tokens.next().unwrap().unwrap().value
+    );
+    assert_eq!("QUOTATION", tokens.current_state());
+    assert_eq!(Tag::Whitespace, tokens.next().unwrap().unwrap().value);
+    assert_eq!(INITIAL, tokens.current_state());
+}
+
+#[test]
+fn match_unconditioned_rules_in_inclusive_conditions() {
+    let lexer = LexerSpec::new()
+        .inclusive_condition("KEYWORDS")
+        .in_conditions(&["KEYWORDS"], |spec| spec.rule("if", |_| Tag::If))
+        .rule("[a-z]+", |lexeme| Tag::Id(lexeme.to_string()))
+        .rule(" +", |_| Tag::Whitespace)
+        .rule_with_actions("!", |actions, _| {
+            actions.begin("KEYWORDS");
+            None
+        })
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        vec![
+            Tag::Id(String::from("if")),
+            Tag::If,
+            Tag::Id(String::from("iffy")),
+        ],
+        get_tags(&lexer, "if !if iffy")
+    );
+}
+
+#[test]
+fn return_to_pushed_conditions() {
+    let lexer = LexerSpec::new()
+        .exclusive_condition("COMMENT")
+        .rule("[a-z]+", |lexeme| Tag::Id(lexeme.to_string()))
+        .rule(" +", |_| Tag::Whitespace)
+        .in_conditions(&[ALL_CONDITIONS], |spec| {
+            spec.rule_with_actions("\"/*\"", |actions, _| {
+                actions.push_state("COMMENT");
+                None
+            })
+        })
+        .in_conditions(&["COMMENT"], |spec| {
+            spec.rule_with_actions("\"*/\"", |actions, _| {
+                actions.pop_state();
+                if actions.current_state() == INITIAL {
+                    Some(Tag::Comment)
+                } else {
+                    None
+                }
+            })
+            .rule("[^*/]+|\"*\"|/", |_| Tag::Whitespace)
+        })
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        vec![
+            Tag::Id(String::from("a")),
+            Tag::Comment,
+            Tag::Id(String::from("b")),
+        ],
+        get_tags(&lexer, "a /* x /* y */ z */ b")
+    );
+}
+
+#[test]
+fn fail_on_unknown_start_condition() {
+    let error = LexerSpec::new()
+        .in_conditions(&["STRING"], |spec| spec.rule("[a-z]+", |_| Tag::Whitespace))
+        .build()
+        .err();
+
+    assert_eq!(
+        Some(SpecError::UnknownStartCondition(String::from("STRING"))),
+        error
+    );
+}
@@ -0,0 +1,150 @@
+use lexer_with_dynamic_state_machine::LexError;
+use lexer_with_dynamic_state_machine::Lexer;
+use lexer_with_dynamic_state_machine::LexerSpec;
+use lexer_with_dynamic_state_machine::Span;
+use lexer_with_dynamic_state_machine::StreamError;
+use lexer_with_dynamic_state_machine::StreamToken;
+use std::io::ErrorKind;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Tag {
+    If,
+    Id,
+    Number,
+    Comment,
+    Other,
+}
+
+fn get_lexer() -> Lexer<Tag> {
+    return LexerSpec::new()
+        .exclusive_condition("COMMENT")
+        .rule("if", |_| Tag::If)
+        .rule("[a-z][a-z0-9]*", |_| Tag::Id)
+        .rule("[0-9]+", |_| Tag::Number)
+        .rule_with_actions("\"/*\"", |actions, _| {
+            actions.begin("COMMENT");
+            None
+        })
+        .in_conditions(&["COMMENT"], |spec| {
+            spec.rule_with_actions("\"*/\"", |actions, _| {
+                actions.begin("INITIAL");
+                None
+            })
+            .rule("[^*]+|\"*\"", |_| Tag::Comment)
+        })
+        .rule_with_actions("[ \t\n]+", |_, _| None)
+        .rule("[^a-z0-9 \t\n]", |_| Tag::Other)
+        .build()
+        .unwrap();
+}
+
+fn stream(lexer: &Lexer<Tag>, text: &[u8], block_size: usize) -> Vec<StreamToken<Tag>> {
+    return lexer
+        .stream_tokens(text)
+        .with_block_size(block_size)
+        .map(|token| token.unwrap())
+        .collect();
+}
+
+#[test]
+fn stream_the_same_tokens_for_every_block_size() {
+    let lexer = get_lexer();
+    let text = "if iffy 42\n  gr\u{fc}\u{df}e /* \u{1f600} */ x1\tlast";
+
+    let expected: Vec<StreamToken<Tag>> = lexer
+        .tokens(text)
+        .map(|token| {
+            let token = token.unwrap();
+
+            StreamToken {
+                value: token.value,
+                lexeme: token.lexeme.to_string(),
+                span: token.span,
+            }
+        })
+        .collect();
+
+    for block_size in &[1, 2, 3, 5, 4096] {
+        assert_eq!(expected, stream(&lexer, text.as_bytes(), *block_size));
+    }
+}
+
+#[test]
+fn keep_lexemes_longer_than_a_block() {
+    let lexer = get_lexer();
+    let identifier = "a".repeat(100);
+    let text = format!("{} 1", identifier);
+
+    let tokens = stream(&lexer, text.as_bytes(), 8);
+
+    assert_eq!(2, tokens.len());
+    assert_eq!(identifier, tokens[0].lexeme);
+    assert_eq!(Span::new(0, 100, 1, 1), tokens[0].span);
+    assert_eq!(Span::new(101, 102, 1, 102), tokens[1].span);
+}
+
+#[test]
+fn emit_the_last_token_at_the_end_of_the_input() {
+    let lexer = get_lexer();
+
+    let tokens = stream(&lexer, b"if x", 3);
+
+    assert_eq!(
+        vec![Tag::If, Tag::Id],
+        tokens
+            .into_iter()
+            .map(|token| token.value)
+            .collect::<Vec<Tag>>()
+    );
+    assert_eq!(0, stream(&lexer, b"", 3).len());
+}
+
+#[test]
+fn stop_at_lex_errors() {
+    let lexer = LexerSpec::new()
+        .rule("[a-z]+", |_| Tag::Id)
+        .rule("\n", |_| Tag::Other)
+        .build()
+        .unwrap();
+
+    let mut tokens = lexer.stream_tokens(&b"ab\ncd!ef"[..]).with_block_size(2);
+    assert_eq!(Tag::Id, tokens.next().unwrap().unwrap().value);
+    assert_eq!(Tag::Other, tokens.next().unwrap().unwrap().value);
+    assert_eq!(Tag::Id, tokens.next().unwrap().unwrap().value);
+
+    match tokens.next() {
+        Some(Err(StreamError::Lex(error))) => assert_eq!(
+            LexError {
+                span: Span::new(5, 6, 2, 3),
+                text: String::from("!"),
+            },
+            error
+        ),
+        other => panic!("Expected a lex error, got {:?}", other),
+    }
+    assert!(tokens.next().is_none());
+}
+
+#[test]
+fn report_invalid_utf8() {
+    let lexer = get_lexer();
+
+    let errors: Vec<ErrorKind> = [&b"ab \xff"[..], &b"ab \xc3"[..]]
+        .iter()
+        .map(|text| {
+            match lexer
+                .stream_tokens(*text)
+                .with_block_size(2)
+                .find_map(|token| token.err())
+            {
+                Some(StreamError::Io(error)) => error.kind(),
+                other => panic!("Expected an IO error, got {:?}", other),
+            }
+        })
+        .collect();
+
+    assert_eq!(
+        vec![ErrorKind::InvalidData, ErrorKind::UnexpectedEof],
+        errors
+    );
+}
@@ -0,0 +1,61 @@
+use lexer_with_dynamic_state_machine::SymbolTable;
+
+#[test]
+fn intern_names_only_once() {
+    let mut symbol_table = SymbolTable::new();
+
+    let hello = symbol_table.intern("hello");
+    let world = symbol_table.intern("world");
+    assert_eq!(hello, symbol_table.intern("hello"));
+    assert_eq!(0, hello.index());
+    assert_eq!(1, world.index());
+    assert_eq!(2, symbol_table.len());
+}
+
+#[test]
+fn look_up_names_of_ids() {
+    let mut symbol_table = SymbolTable::new();
+    let id = symbol_table.intern("hello");
+
+    assert_eq!("hello", symbol_table.get_name(id));
+    assert_eq!(Some(id), symbol_table.get("hello"));
+    assert_eq!(None, symbol_table.get("world"));
+    assert_eq!(1, symbol_table.len());
+}
+
+#[test]
+fn flag_keywords() {
+    let mut symbol_table = SymbolTable::with_keywords(&["if", "then", "else"]);
+    let hello = symbol_table.intern("hello");
+    let if_id = symbol_table.intern("if");
+
+    assert_eq!(0, if_id.index());
+    assert_eq!(true, symbol_table.is_keyword(if_id));
+    assert_eq!(false, symbol_table.is_keyword(hello));
+    assert_eq!(3, hello.index());
+}
+
+#[test]
+fn iterate_in_interning_order() {
+    let mut symbol_table = SymbolTable::new();
+    for name in &["c", "a", "b", "a"] {
+        symbol_table.intern(name);
+    }
+
+    let names: Vec<&str> = symbol_table.iter().map(|(_, name)| name).collect();
+    assert_eq!(vec!["c", "a", "b"], names);
+}
+
+#[test]
+fn intern_many_names() {
+    let mut symbol_table = SymbolTable::new();
+    let ids: Vec<_> = (0..10_000)
+        .map(|index| symbol_table.intern(&format!("id{}", index)))
+        .collect();
+
+    assert_eq!(10_000, symbol_table.len());
+    for (index, id) in ids.into_iter().enumerate() {
+        assert_eq!(index, id.index());
+        assert_eq!(format!("id{}", index), symbol_table.get_name(id));
+    }
+}
@@ -0,0 +1,139 @@
+use lexer_with_dynamic_state_machine::unescape_string;
+use lexer_with_dynamic_state_machine::ConversionError;
+use lexer_with_dynamic_state_machine::Lexer;
+use lexer_with_dynamic_state_machine::LexerSpec;
+use lexer_with_dynamic_state_machine::Span;
+use lexer_with_dynamic_state_machine::StreamError;
+use lexer_with_dynamic_state_machine::SymbolId;
+use lexer_with_dynamic_state_machine::TokenError;
+use lexer_with_dynamic_state_machine::UnescapeError;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Integer(u8),
+    Float(f64),
+    Str(String),
+    Id(SymbolId),
+}
+
+fn get_lexer() -> Lexer<Token> {
+    return LexerSpec::new()
+        .define("LNUM", "[0-9]+")
+        .define("DNUM", "([0-9]*\".\"[0-9]+)|([0-9]+\".\"[0-9]*)")
+        .define("EXPONENT_DNUM", "(({LNUM}|{DNUM})[eE][+-]?{LNUM})")
+        .rule_with_conversion("{LNUM}", |lexeme| lexeme.parse().map(Token::Integer))
+        .rule_with_conversion("{DNUM}|{EXPONENT_DNUM}", |lexeme| {
+            lexeme.parse().map(Token::Float)
+        })
+        .rule_with_conversion(r#"["]([^"\x5c\n]|[\x5c][^\n])*["]"#, |lexeme| {
+            unescape_string(lexeme).map(Token::Str)
+        })
+        .rule_with_actions("[a-z][a-z0-9]*", |actions, lexeme| {
+            Some(Token::Id(actions.intern(lexeme)))
+        })
+        .rule_with_actions(" +", |_, _| None)
+        .build()
+        .unwrap();
+}
+
+#[test]
+fn convert_lexemes_into_values() {
+    let lexer = get_lexer();
+    let mut tokens = lexer.tokens(r#"42 1.5 .5e3 2E-2 "a\"b\n" x y x"#);
+
+    let values: Vec<Token> = tokens.by_ref().map(|token| token.unwrap().value).collect();
+    let symbol_table = tokens.symbol_table();
+    let x = symbol_table.get("x").unwrap();
+    let y = symbol_table.get("y").unwrap();
+
+    assert_eq!(
+        vec![
+            Token::Integer(42),
+            Token::Float(1.5),
+            Token::Float(500.0),
+            Token::Float(0.02),
+            Token::Str(String::from("a\"b\n")),
+            Token::Id(x),
+            Token::Id(y),
+            Token::Id(x),
+        ],
+        values
+    );
+}
+
+#[test]
+fn report_conversion_errors_with_the_span() {
+    let lexer = get_lexer();
+
+    let tokens: Vec<Result<Token, TokenError>> = lexer
+        .tokens("1 300 \"\\q\" 2")
+        .map(|token| token.map(|token| token.value))
+        .collect();
+
+    assert_eq!(
+        vec![
+            Ok(Token::Integer(1)),
+            Err(TokenError::Conversion(ConversionError {
+                span: Span::new(2, 5, 1, 3),
+                text: String::from("300"),
+                message: String::from("number too large to fit in target type"),
+            })),
+            Err(TokenError::Conversion(ConversionError {
+                span: Span::new(6, 10, 1, 7),
+                text: String::from("\"\\q\""),
+                message: UnescapeError::UnknownEscape('q', 1).to_string(),
+            })),
+            Ok(Token::Integer(2)),
+        ],
+        tokens
+    );
+}
+
+#[test]
+fn report_conversion_errors_of_streams() {
+    let lexer = get_lexer();
+
+    let tokens: Vec<Result<Token, String>> = lexer
+        .stream_tokens(&b"256 7"[..])
+        .map(|token| match token {
+            Ok(token) => Ok(token.value),
+            Err(StreamError::Conversion(error)) => Err(error.text),
+            Err(error) => panic!("Unexpected error {}", error),
+        })
+        .collect();
+
+    assert_eq!(
+        vec![Err(String::from("256")), Ok(Token::Integer(7))],
+        tokens
+    );
+}
+
+#[test]
+fn unescape_string_literals() {
+    assert_eq!(
+        Ok(String::from("tab\t quote' \u{e9}")),
+        unescape_string(r#""tab\t quote\' \u{e9}""#)
+    );
+    assert_eq!(Ok(String::from("a\"")), unescape_string(r#"'a"'"#));
+    assert_eq!(Ok(String::new()), unescape_string(r#""""#));
+
+    assert_eq!(Err(UnescapeError::MissingQuotes), unescape_string("abc"));
+    assert_eq!(Err(UnescapeError::MissingQuotes), unescape_string("\"abc'"));
+    assert_eq!(Err(UnescapeError::MissingQuotes), unescape_string("\""));
+    assert_eq!(
+        Err(UnescapeError::UnterminatedEscape(2)),
+        unescape_string(r#""a\""#)
+    );
+    assert_eq!(
+        Err(UnescapeError::InvalidUnicodeEscape(1)),
+        unescape_string(r#""\u{d800}""#)
+    );
+    assert_eq!(
+        Err(UnescapeError::InvalidUnicodeEscape(1)),
+        unescape_string(r#""\u{}""#)
+    );
+    assert_eq!(
+        Err(UnescapeError::InvalidUnicodeEscape(1)),
+        unescape_string(r#""\u{+41}""#)
+    );
+}
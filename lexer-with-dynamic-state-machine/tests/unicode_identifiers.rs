@@ -0,0 +1,90 @@
+use lexer_with_dynamic_state_machine::is_identifier_continue;
+use lexer_with_dynamic_state_machine::is_identifier_start;
+use lexer_with_dynamic_state_machine::normalize_identifier;
+use lexer_with_dynamic_state_machine::Lexer;
+use lexer_with_dynamic_state_machine::LexerSpec;
+use lexer_with_dynamic_state_machine::Normalization;
+use lexer_with_dynamic_state_machine::SymbolId;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    If,
+    Id(SymbolId),
+    Number,
+    Unknown,
+}
+
+fn get_lexer(normalization: Normalization) -> Lexer<Token> {
+    return LexerSpec::new()
+        .rule("if", |_| Token::If)
+        .unicode_identifiers(normalization, Token::Id)
+        .rule("[0-9]+", |_| Token::Number)
+        .skip("[ \t\n]+")
+        .build()
+        .unwrap();
+}
+
+/// Returns the names of the identifiers, keywords and numbers are returned as their variant.
+fn get_names(lexer: &Lexer<Token>, text: &str) -> Vec<String> {
+    let mut tokens = lexer.tokens(text);
+    let values: Vec<Token> = tokens.by_ref().map(|token| token.unwrap().value).collect();
+    let symbol_table = tokens.symbol_table();
+
+    return values
+        .into_iter()
+        .map(|value| match value {
+            Token::Id(id) => symbol_table.get_name(id).to_string(),
+            other => format!("{:?}", other),
+        })
+        .collect();
+}
+
+#[test]
+fn lex_identifiers_with_unicode_characters() {
+    let lexer = get_lexer(Normalization::None);
+
+    assert_eq!(
+        vec!["größe", "If", "iffy", "名前", "x_1", "Number", "Δt"],
+        get_names(&lexer, "größe if iffy 名前 x_1 2 Δt")
+    );
+}
+
+#[test]
+fn follow_the_identifier_syntax_of_uax_31() {
+    assert!(is_identifier_start('ö'));
+    assert!(is_identifier_start('名'));
+    assert!(!is_identifier_start('1'));
+    assert!(!is_identifier_start('_'));
+    assert!(is_identifier_continue('_'));
+    assert!(is_identifier_continue('\u{301}'));
+    assert!(!is_identifier_continue('-'));
+
+    let lexer = get_lexer(Normalization::None);
+    let tokens: Vec<(bool, &str)> = lexer
+        .tokens("a-b")
+        .recover_from_errors(|_| Token::Unknown)
+        .map(|token| token.unwrap())
+        .map(|token| (token.value == Token::Unknown, token.lexeme))
+        .collect();
+
+    assert_eq!(vec![(false, "a"), (true, "-"), (false, "b")], tokens);
+}
+
+#[test]
+fn normalize_identifiers_before_interning() {
+    let composed = "caf\u{e9}";
+    let decomposed = "cafe\u{301}";
+
+    assert_eq!(composed, normalize_identifier(decomposed));
+    assert_eq!(composed, normalize_identifier(composed));
+
+    let text = format!("{} {}", composed, decomposed);
+    assert_eq!(
+        vec![composed, composed],
+        get_names(&get_lexer(Normalization::Nfc), &text)
+    );
+    assert_eq!(
+        vec![composed, decomposed],
+        get_names(&get_lexer(Normalization::None), &text)
+    );
+}
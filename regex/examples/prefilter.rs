@@ -0,0 +1,26 @@
+use regex::get_regex_engine;
+use regex::RegexEngine;
+use std::time::Instant;
+
+fn count_matches(name: &str, regex_engine: &RegexEngine, haystack: &str) {
+    let start = Instant::now();
+    let count = regex_engine.find_iter(haystack).count();
+
+    println!("{:<20} {:>6} matches in {:?}", name, count, start.elapsed());
+}
+
+fn main() {
+    let line = "2020-01-01 12:00:00 INFO request handled in 12ms by worker 7\n";
+    let mut haystack = line.repeat(50_000);
+    haystack.push_str("2020-01-01 12:00:01 ERROR: disk full\n");
+
+    for regex in &["ERROR:[ a-z]*", "\"GET /\"[a-z]*", "[E#][A-Z]+"] {
+        println!("{}", regex);
+        count_matches("with prefilter", &get_regex_engine(regex), &haystack);
+        count_matches(
+            "without prefilter",
+            &get_regex_engine(regex).without_prefilter(),
+            &haystack,
+        );
+    }
+}
@@ -0,0 +1,12 @@
+use regex::get_regex_syntax_tree_with_spans;
+use regex::get_syntax_tree_dot_graph;
+use std::env;
+
+// Prints the syntax tree of the regex given as first argument, e.g.
+// `cargo run --example syntax_tree_dot -- "(ab)*c" | dot -Tpng > tree.png`
+fn main() {
+    let regex = env::args().nth(1).unwrap_or_else(|| String::from("(ab)*c"));
+    let (ast, spans) = get_regex_syntax_tree_with_spans(&regex);
+
+    print!("{}", get_syntax_tree_dot_graph(&ast, &spans, &regex));
+}
@@ -1,114 +1,190 @@
 use crate::MatchingGroup;
 use crate::RegexAstElements;
+use crate::Span;
+use crate::SpanTree;
 use crate::State;
 
+/// Builds the ast for the concatenation list together with the spans of its nodes. An empty list
+/// results in `RegexAstElements::None` with an empty span at `empty_offset`.
 fn get_partial_ast_for_concatenation_list(
-    stack: &Vec<State>,
-    concatenation_list: &Vec<usize>,
-) -> RegexAstElements {
+    stack: &[State],
+    concatenation_list: &[usize],
+    empty_offset: usize,
+) -> (RegexAstElements, SpanTree) {
     let mut ast = RegexAstElements::None;
+    let mut spans = SpanTree::new(Span::new(empty_offset, empty_offset), Vec::new());
 
-    for index in 0..concatenation_list.len() {
-        let next_state_index = concatenation_list[index];
-        let state = &stack[next_state_index];
+    for next_state_index in concatenation_list {
+        let state = &stack[*next_state_index];
 
         // The operator_character is used to allow for escaped operators. Therefore the
         // operator_character should not be used when constructing an ast!
-        let operator_character;
-        if state.is_escaped {
-            operator_character = &MatchingGroup::Character('a');
+        let operator_character = if state.is_escaped {
+            &MatchingGroup::Character('a')
         } else {
-            operator_character = &state.matching_group;
-        }
-        match operator_character {
+            &state.matching_group
+        };
+        let (element_ast, element_spans) = match operator_character {
             MatchingGroup::Character('*') => {
-                let left_list = match state.left_next {
-                    Some(ref list) => list,
-                    None => panic!("This can't be happening"),
-                };
-                let zero_or_more_ast = RegexAstElements::ZeroOrMore(Box::new(
-                    get_partial_ast_for_concatenation_list(&stack, left_list),
-                ));
-
-                match ast {
-                    RegexAstElements::None => ast = zero_or_more_ast,
-                    _ => {
-                        ast = RegexAstElements::Concatenation(
-                            Box::new(ast),
-                            Box::new(zero_or_more_ast),
-                        )
-                    }
-                }
+                let (child_ast, child_spans) = get_partial_ast_for_concatenation_list(
+                    stack,
+                    get_left_list(state),
+                    state.span.end,
+                );
+
+                (
+                    RegexAstElements::ZeroOrMore(Box::new(child_ast)),
+                    SpanTree::new(state.span, vec![child_spans]),
+                )
             }
             MatchingGroup::Character('?') => {
-                let left_list = match state.left_next {
-                    Some(ref list) => list,
-                    None => panic!("This can't be happening"),
-                };
-                let zero_or_one_ast = RegexAstElements::ZeroOrOne(Box::new(
-                    get_partial_ast_for_concatenation_list(&stack, left_list),
-                ));
-
-                match ast {
-                    RegexAstElements::None => ast = zero_or_one_ast,
-                    _ => {
-                        ast = RegexAstElements::Concatenation(
-                            Box::new(ast),
-                            Box::new(zero_or_one_ast),
-                        )
-                    }
-                }
+                let (child_ast, child_spans) = get_partial_ast_for_concatenation_list(
+                    stack,
+                    get_left_list(state),
+                    state.span.end,
+                );
+
+                (
+                    RegexAstElements::ZeroOrOne(Box::new(child_ast)),
+                    SpanTree::new(state.span, vec![child_spans]),
+                )
+            }
+            // Groups are only kept as a separate element if they are repeated. Their elements
+            // are concatenated like all other elements.
+            MatchingGroup::Character('(') => {
+                get_partial_ast_for_concatenation_list(stack, get_left_list(state), state.span.end)
             }
             MatchingGroup::Character('|') => {
-                let left_list = match state.left_next {
-                    Some(ref list) => list,
-                    None => panic!("This can't be happening"),
-                };
                 let right_list = match state.right_next {
                     Some(ref list) => list,
                     None => panic!("This can't be happening"),
                 };
-
-                let alternation_ast = RegexAstElements::Alternation(
-                    Box::new(get_partial_ast_for_concatenation_list(stack, left_list)),
-                    Box::new(get_partial_ast_for_concatenation_list(stack, right_list)),
+                let (left_ast, left_spans) = get_partial_ast_for_concatenation_list(
+                    stack,
+                    get_left_list(state),
+                    state.span.start,
                 );
+                let (right_ast, right_spans) =
+                    get_partial_ast_for_concatenation_list(stack, right_list, state.span.end);
 
-                match ast {
-                    RegexAstElements::None => ast = alternation_ast,
-                    _ => {
-                        ast = RegexAstElements::Concatenation(
-                            Box::new(ast),
-                            Box::new(alternation_ast),
-                        )
-                    }
-                }
+                (
+                    RegexAstElements::Alternation(Box::new(left_ast), Box::new(right_ast)),
+                    SpanTree::new(state.span, vec![left_spans, right_spans]),
+                )
+            }
+            _ => (
+                RegexAstElements::Leaf(state.matching_group.clone()),
+                SpanTree::new(state.span, Vec::new()),
+            ),
+        };
+
+        match ast {
+            RegexAstElements::None => {
+                ast = element_ast;
+                spans = element_spans;
+            }
+            _ => {
+                ast = RegexAstElements::Concatenation(Box::new(ast), Box::new(element_ast));
+                spans = SpanTree::new(
+                    spans.span.union(element_spans.span),
+                    vec![spans, element_spans],
+                );
             }
-            _ => match ast {
-                RegexAstElements::None => {
-                    ast = RegexAstElements::Leaf(state.matching_group.clone())
-                }
-                _ => {
-                    ast = RegexAstElements::Concatenation(
-                        Box::new(ast),
-                        Box::new(RegexAstElements::Leaf(state.matching_group.clone())),
-                    );
-                }
-            },
         }
     }
 
-    return ast;
+    return (ast, spans);
 }
 
+fn get_left_list(state: &State) -> &Vec<usize> {
+    return match state.left_next {
+        Some(ref list) => list,
+        None => panic!("This can't be happening"),
+    };
+}
+
+/// Builds the ast for the whole regex and adds the accepted state at the end. The accepted state
+/// has an empty span at the end of the regex.
 pub(crate) fn get_ast_for_concatenation_list(
-    stack: &Vec<State>,
-    concatenation_list: &Vec<usize>,
-) -> RegexAstElements {
-    let ast = get_partial_ast_for_concatenation_list(stack, concatenation_list);
-
-    return RegexAstElements::Concatenation(
-        Box::new(ast),
-        Box::new(RegexAstElements::Leaf(MatchingGroup::AcceptedState)),
+    stack: &[State],
+    concatenation_list: &[usize],
+    regex_length: usize,
+) -> (RegexAstElements, SpanTree) {
+    let (ast, spans) = get_partial_ast_for_concatenation_list(stack, concatenation_list, 0);
+    let accepted_spans = SpanTree::new(Span::new(regex_length, regex_length), Vec::new());
+
+    return (
+        RegexAstElements::Concatenation(
+            Box::new(ast),
+            Box::new(RegexAstElements::Leaf(MatchingGroup::AcceptedState)),
+        ),
+        SpanTree::new(
+            spans.span.union(accepted_spans.span),
+            vec![spans, accepted_spans],
+        ),
     );
 }
+
+/// Splits a chain of concatenations into its parts. Concatenations are nested on the left side,
+/// so the chain is walked without recursion to support long patterns.
+pub(crate) fn get_concatenated_elements<T>(ast: &RegexAstElements<T>) -> Vec<&RegexAstElements<T>> {
+    let mut elements = Vec::new();
+    let mut current = ast;
+
+    while let RegexAstElements::Concatenation(ref left, ref right) = current {
+        elements.push(right.as_ref());
+        current = left;
+    }
+    elements.push(current);
+    elements.reverse();
+
+    return elements;
+}
+
+/// Returns an ast which matches every string of the given ast backwards. The accepted state stays
+/// at the end of the ast.
+pub(crate) fn get_reversed_ast<T: Clone + PartialEq>(
+    ast: &RegexAstElements<T>,
+) -> RegexAstElements<T> {
+    return match ast {
+        RegexAstElements::Concatenation(_, ref right)
+            if right.as_ref() == &RegexAstElements::Leaf(MatchingGroup::AcceptedState) =>
+        {
+            let mut elements = get_concatenated_elements(ast);
+            elements.pop();
+
+            RegexAstElements::Concatenation(
+                Box::new(get_reversed_concatenation(&elements)),
+                Box::new(RegexAstElements::Leaf(MatchingGroup::AcceptedState)),
+            )
+        }
+        RegexAstElements::Concatenation(_, _) => {
+            get_reversed_concatenation(&get_concatenated_elements(ast))
+        }
+        RegexAstElements::Alternation(ref left, ref right) => RegexAstElements::Alternation(
+            Box::new(get_reversed_ast(left)),
+            Box::new(get_reversed_ast(right)),
+        ),
+        RegexAstElements::Leaf(ref group) => RegexAstElements::Leaf(group.clone()),
+        RegexAstElements::None => RegexAstElements::None,
+        RegexAstElements::ZeroOrMore(ref child) => {
+            RegexAstElements::ZeroOrMore(Box::new(get_reversed_ast(child)))
+        }
+        RegexAstElements::ZeroOrOne(ref child) => {
+            RegexAstElements::ZeroOrOne(Box::new(get_reversed_ast(child)))
+        }
+    };
+}
+
+fn get_reversed_concatenation<T: Clone + PartialEq>(
+    elements: &[&RegexAstElements<T>],
+) -> RegexAstElements<T> {
+    let mut reversed_elements = elements.iter().rev();
+    let mut ast = get_reversed_ast(reversed_elements.next().unwrap());
+
+    for element in reversed_elements {
+        ast = RegexAstElements::Concatenation(Box::new(ast), Box::new(get_reversed_ast(element)));
+    }
+
+    return ast;
+}
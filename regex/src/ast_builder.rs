@@ -0,0 +1,82 @@
+use crate::MatchingGroup;
+use crate::MatchingGroupElements;
+use crate::RegexAstElements;
+
+/// Functions to build a syntax tree without parsing a regex. This allows regexes over any kind
+/// of symbol, e.g. `Id (Comma Id)*` over the tokens of a lexer:
+///
+/// ```
+/// use regex::RegexAstElements;
+///
+/// let ast = RegexAstElements::symbol("Id").then(
+///     RegexAstElements::symbol("Comma")
+///         .then(RegexAstElements::symbol("Id"))
+///         .zero_or_more(),
+/// );
+/// let regex_engine = regex::get_regex_engine_for_ast(ast);
+///
+/// assert!(regex_engine.matches_symbols(&["Id", "Comma", "Id"]));
+/// ```
+impl<T: Clone> RegexAstElements<T> {
+    /// Matches exactly the given symbol.
+    pub fn symbol(symbol: T) -> Self {
+        return RegexAstElements::Leaf(MatchingGroup::Character(symbol));
+    }
+
+    /// Matches the given symbols one after another. An empty sequence matches the empty string.
+    pub fn sequence(symbols: Vec<T>) -> Self {
+        let mut ast = RegexAstElements::None;
+
+        for symbol in symbols {
+            ast = ast.then(RegexAstElements::symbol(symbol));
+        }
+
+        return ast;
+    }
+
+    /// Matches any one of the given symbols.
+    pub fn one_of(symbols: Vec<T>) -> Self {
+        return RegexAstElements::Leaf(MatchingGroup::Group(
+            symbols
+                .into_iter()
+                .map(MatchingGroupElements::Character)
+                .collect(),
+        ));
+    }
+
+    /// Matches any single symbol except the given ones.
+    pub fn none_of(symbols: Vec<T>) -> Self {
+        return RegexAstElements::Leaf(MatchingGroup::NegativeGroup(
+            symbols
+                .into_iter()
+                .map(MatchingGroupElements::Character)
+                .collect(),
+        ));
+    }
+
+    pub fn then(self, next: RegexAstElements<T>) -> Self {
+        return match self {
+            RegexAstElements::None => next,
+            _ => RegexAstElements::Concatenation(Box::new(self), Box::new(next)),
+        };
+    }
+
+    pub fn or(self, alternative: RegexAstElements<T>) -> Self {
+        return RegexAstElements::Alternation(Box::new(self), Box::new(alternative));
+    }
+
+    pub fn zero_or_more(self) -> Self {
+        return RegexAstElements::ZeroOrMore(Box::new(self));
+    }
+
+    pub fn zero_or_one(self) -> Self {
+        return RegexAstElements::ZeroOrOne(Box::new(self));
+    }
+
+    /// Same as `+` in a regex, the element followed by any amount of repetitions.
+    pub fn one_or_more(self) -> Self {
+        let repetitions = self.clone().zero_or_more();
+
+        return self.then(repetitions);
+    }
+}
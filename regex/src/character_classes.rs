@@ -0,0 +1,223 @@
+use crate::MatchingGroup;
+use crate::MatchingGroupElements;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Splits all symbols into classes of symbols which are contained in exactly the same matching
+/// groups. The state machine has one transition per class, therefore a symbol which is part of
+/// several overlapping matching groups, e.g. `a` in `a|[a-z]`, follows all of them.
+///
+/// The classes are built from the boundaries of all groups. Every boundary symbol is an atom of
+/// its own and so is the space between two neighbouring boundaries. Atoms which are contained in
+/// the same groups belong to the same class.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CharacterClasses<T = char> {
+    boundaries: Vec<T>,
+    atom_classes: Vec<Option<usize>>,
+    class_matching_groups: Vec<Vec<usize>>,
+}
+impl<T> CharacterClasses<T> {
+    pub fn len(&self) -> usize {
+        return self.class_matching_groups.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.class_matching_groups.is_empty();
+    }
+
+    /// Returns the indices of all matching groups which contain the symbols of the class.
+    pub fn get_matching_groups(&self, class: usize) -> &[usize] {
+        return &self.class_matching_groups[class];
+    }
+}
+
+impl<T: Clone + PartialOrd> CharacterClasses<T> {
+    pub(crate) fn new(matching_groups: &[MatchingGroup<T>]) -> Self {
+        let mut boundaries = Vec::new();
+        for matching_group in matching_groups {
+            match matching_group {
+                MatchingGroup::Character(symbol) => boundaries.push(symbol.clone()),
+                MatchingGroup::Group(elements) | MatchingGroup::NegativeGroup(elements) => {
+                    for element in elements {
+                        match element {
+                            MatchingGroupElements::Character(symbol) => {
+                                boundaries.push(symbol.clone())
+                            }
+                            MatchingGroupElements::Range(start_symbol, end_symbol) => {
+                                boundaries.push(start_symbol.clone());
+                                boundaries.push(end_symbol.clone());
+                            }
+                        }
+                    }
+                }
+                MatchingGroup::AcceptedState => {}
+            }
+        }
+        boundaries.sort_by(compare);
+        boundaries.dedup_by(|first, second| compare(first, second) == Ordering::Equal);
+
+        // Classes are numbered by their matching groups, therefore regexes without overlapping
+        // groups get the same numbers for their classes and groups.
+        let mut atom_groups = Vec::with_capacity(boundaries.len() * 2 + 1);
+        for atom in 0..boundaries.len() * 2 + 1 {
+            let groups: Vec<usize> = (0..matching_groups.len())
+                .filter(|group| atom_is_in_group(&boundaries, atom, &matching_groups[*group]))
+                .collect();
+            atom_groups.push(groups);
+        }
+
+        let mut class_matching_groups: Vec<Vec<usize>> = atom_groups
+            .iter()
+            .filter(|groups| !groups.is_empty())
+            .cloned()
+            .collect();
+        class_matching_groups.sort();
+        class_matching_groups.dedup();
+
+        let class_ids: HashMap<&Vec<usize>, usize> = class_matching_groups
+            .iter()
+            .enumerate()
+            .map(|(class, groups)| (groups, class))
+            .collect();
+        let atom_classes = atom_groups
+            .iter()
+            .map(|groups| class_ids.get(groups).cloned())
+            .collect();
+
+        return CharacterClasses {
+            boundaries,
+            atom_classes,
+            class_matching_groups,
+        };
+    }
+
+    /// Returns the class of the symbol or `None` if no matching group contains it.
+    pub fn get_class(&self, symbol: &T) -> Option<usize> {
+        let atom = match self
+            .boundaries
+            .binary_search_by(|boundary| compare(boundary, symbol))
+        {
+            Ok(index) => index * 2 + 1,
+            Err(index) => index * 2,
+        };
+
+        return self.atom_classes[atom];
+    }
+}
+
+impl CharacterClasses<char> {
+    /// Returns the sorted ranges of all characters in the class, e.g. to generate code for the
+    /// state machine. Neighbouring ranges are merged.
+    pub fn get_character_ranges(&self, class: usize) -> Vec<(char, char)> {
+        let mut ranges: Vec<(char, char)> = Vec::new();
+
+        for (atom, atom_class) in self.atom_classes.iter().enumerate() {
+            if *atom_class != Some(class) {
+                continue;
+            }
+
+            let range = if atom % 2 == 1 {
+                Some((self.boundaries[atom / 2], self.boundaries[atom / 2]))
+            } else {
+                let start = match atom {
+                    0 => Some('\0'),
+                    _ => get_next_character(self.boundaries[atom / 2 - 1]),
+                };
+                let end = match self.boundaries.get(atom / 2) {
+                    Some(boundary) => get_previous_character(*boundary),
+                    None => Some(char::MAX),
+                };
+
+                match (start, end) {
+                    (Some(start), Some(end)) if start <= end => Some((start, end)),
+                    // Neighbouring boundaries have no characters between them.
+                    _ => None,
+                }
+            };
+
+            if let Some((start, end)) = range {
+                match ranges.last_mut() {
+                    Some(last_range) if get_next_character(last_range.1) == Some(start) => {
+                        last_range.1 = end
+                    }
+                    _ => ranges.push((start, end)),
+                }
+            }
+        }
+
+        return ranges;
+    }
+}
+
+/// Characters skip the surrogates, therefore the next character is not always the next code
+/// point.
+pub(crate) fn get_next_character(character: char) -> Option<char> {
+    return match character {
+        '\u{D7FF}' => Some('\u{E000}'),
+        _ => std::char::from_u32(character as u32 + 1),
+    };
+}
+
+fn get_previous_character(character: char) -> Option<char> {
+    return match character {
+        '\0' => None,
+        '\u{E000}' => Some('\u{D7FF}'),
+        _ => std::char::from_u32(character as u32 - 1),
+    };
+}
+
+fn compare<T: PartialOrd>(first: &T, second: &T) -> Ordering {
+    return first.partial_cmp(second).unwrap_or(Ordering::Equal);
+}
+
+/// Odd atoms are the boundary at `atom / 2`, even atoms are all symbols between the boundaries
+/// next to them.
+fn atom_is_in_group<T: PartialOrd>(
+    boundaries: &[T],
+    atom: usize,
+    matching_group: &MatchingGroup<T>,
+) -> bool {
+    let elements = match matching_group {
+        MatchingGroup::Character(symbol) => {
+            return atom % 2 == 1 && &boundaries[atom / 2] == symbol;
+        }
+        MatchingGroup::Group(elements) => elements,
+        MatchingGroup::NegativeGroup(elements) => {
+            return !elements_contain_atom(boundaries, atom, elements);
+        }
+        MatchingGroup::AcceptedState => return false,
+    };
+
+    return elements_contain_atom(boundaries, atom, elements);
+}
+
+fn elements_contain_atom<T: PartialOrd>(
+    boundaries: &[T],
+    atom: usize,
+    elements: &[MatchingGroupElements<T>],
+) -> bool {
+    if atom % 2 == 1 {
+        let symbol = &boundaries[atom / 2];
+
+        return elements.iter().any(|element| match element {
+            MatchingGroupElements::Character(element_symbol) => element_symbol == symbol,
+            MatchingGroupElements::Range(start_symbol, end_symbol) => {
+                start_symbol <= symbol && symbol <= end_symbol
+            }
+        });
+    }
+
+    // The space before the first or after the last boundary can not be part of a range.
+    if atom == 0 || atom / 2 == boundaries.len() {
+        return false;
+    }
+    let lower_boundary = &boundaries[atom / 2 - 1];
+    let upper_boundary = &boundaries[atom / 2];
+
+    return elements.iter().any(|element| match element {
+        MatchingGroupElements::Character(_) => false,
+        MatchingGroupElements::Range(start_symbol, end_symbol) => {
+            start_symbol <= lower_boundary && upper_boundary <= end_symbol
+        }
+    });
+}
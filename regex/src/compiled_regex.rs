@@ -0,0 +1,184 @@
+use crate::CharacterClasses;
+use crate::MatchingGroup;
+use crate::Prefilter;
+use crate::RegexEngine;
+use crate::StateMachine;
+use crate::TransitionForCharacterClass;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ElementType {
+    Alternation,
+    Concatenation,
+    /// Matches the empty string, e.g. an empty sequence of the ast builder.
+    Empty,
+    Leaf,
+    ZeroOrMore,
+    ZeroOrOne,
+}
+
+/// The calculations for a single node of the syntax tree. The index of a node is the position
+/// which is used in `first_pos`, `last_pos` and `follow_pos`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompiledPosition {
+    pub index: usize,
+    pub element_type: ElementType,
+    pub parent_index: Option<usize>,
+    pub left_child_index: Option<usize>,
+    pub right_child_index: Option<usize>,
+    pub matching_group_index: Option<usize>,
+    pub is_nullable: bool,
+    pub first_pos: Vec<usize>,
+    pub last_pos: Vec<usize>,
+    pub follow_pos: Vec<usize>,
+    pub accepted: bool,
+}
+
+/// A state of the deterministic state machine together with the positions it was built from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompiledState {
+    pub index: usize,
+    pub positions: Vec<usize>,
+    /// Pairs of character class and the index of the next state, sorted by character class.
+    pub transitions: Vec<(usize, usize)>,
+    pub is_accepted: bool,
+}
+
+/// Everything that is calculated while compiling a regex. It is only created on request and
+/// can be turned into a `RegexEngine`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompiledRegex<T = char> {
+    pub positions: Vec<CompiledPosition>,
+    pub states: Vec<CompiledState>,
+    pub matching_groups: Vec<MatchingGroup<T>>,
+    pub character_classes: CharacterClasses<T>,
+    /// Prefilters are only extracted for regexes over characters.
+    pub prefilter: Prefilter,
+    /// The state machine which can start a match at every character, which finds the end of the
+    /// first match in a text.
+    pub unanchored: Option<Box<CompiledRegex<T>>>,
+    /// The state machine for the reversed regex, which finds the start of a match from its end.
+    pub reverse: Option<Box<CompiledRegex<T>>>,
+}
+impl<T: Eq + Hash + Clone + PartialOrd> CompiledRegex<T> {
+    pub fn into_regex_engine(self) -> RegexEngine<T> {
+        let mut transitions: StateMachine = HashMap::with_capacity(self.states.len());
+        // Regexes which are combined into one state machine each end with their own accepted
+        // state. They are numbered in the order of their positions, so that the first regex wins
+        // if a state accepts several of them.
+        let accepted_positions: Vec<usize> = self
+            .positions
+            .iter()
+            .filter(|position| position.accepted)
+            .map(|position| position.index)
+            .collect();
+
+        for state in self.states {
+            let mut transition_map: TransitionForCharacterClass =
+                HashMap::with_capacity(state.transitions.len());
+            transition_map.extend(state.transitions);
+
+            let accepted_regex = state
+                .positions
+                .iter()
+                .find_map(|position| accepted_positions.binary_search(position).ok());
+            transitions.insert(state.index, (transition_map, accepted_regex));
+        }
+
+        let unanchored_engine = self
+            .unanchored
+            .map(|unanchored| Box::new(unanchored.into_regex_engine()));
+        let reverse_engine = self
+            .reverse
+            .map(|reverse| Box::new(reverse.into_regex_engine()));
+
+        return RegexEngine::new_with_values(
+            self.character_classes,
+            transitions,
+            self.prefilter,
+            unanchored_engine,
+            reverse_engine,
+        );
+    }
+}
+
+impl<T: fmt::Debug> CompiledRegex<T> {
+    /// Describes a character class by all matching groups which contain it.
+    fn format_character_class(&self, class: usize) -> String {
+        return self
+            .character_classes
+            .get_matching_groups(class)
+            .iter()
+            .map(|matching_group_index| {
+                format!("{:?}", self.matching_groups[*matching_group_index])
+            })
+            .collect::<Vec<String>>()
+            .join(" & ");
+    }
+}
+
+impl<T: fmt::Debug> fmt::Display for CompiledRegex<T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(formatter, "Matching groups:")?;
+        for (index, matching_group) in self.matching_groups.iter().enumerate() {
+            writeln!(formatter, "  {}: {:?}", index, matching_group)?;
+        }
+
+        writeln!(formatter, "Character classes:")?;
+        for class in 0..self.character_classes.len() {
+            writeln!(
+                formatter,
+                "  {}: {}",
+                class,
+                self.format_character_class(class)
+            )?;
+        }
+
+        writeln!(formatter, "Prefilter: {:?}", self.prefilter)?;
+
+        writeln!(formatter, "Positions:")?;
+        for position in &self.positions {
+            writeln!(
+                formatter,
+                "  {}: {:?} nullable={} firstpos={:?} lastpos={:?} followpos={:?}",
+                position.index,
+                position.element_type,
+                position.is_nullable,
+                position.first_pos,
+                position.last_pos,
+                position.follow_pos,
+            )?;
+        }
+
+        writeln!(formatter, "States:")?;
+        for state in &self.states {
+            write!(formatter, "  {}: {:?}", state.index, state.positions)?;
+            if state.is_accepted {
+                write!(formatter, " accepted")?;
+            }
+            writeln!(formatter)?;
+
+            for (class, next_state) in &state.transitions {
+                writeln!(
+                    formatter,
+                    "    {} -> {}",
+                    self.format_character_class(*class),
+                    next_state
+                )?;
+            }
+        }
+
+        if let Some(unanchored) = &self.unanchored {
+            writeln!(formatter, "Unanchored state machine:")?;
+            write!(formatter, "{}", unanchored)?;
+        }
+        if let Some(reverse) = &self.reverse {
+            writeln!(formatter, "Reverse state machine:")?;
+            write!(formatter, "{}", reverse)?;
+        }
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,41 @@
+use crate::Span;
+use std::error::Error;
+use std::fmt;
+
+/// Errors found while parsing a regex. The span points at the part of the regex which caused
+/// the error.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RegexError {
+    /// A `{NAME}` reference to a definition which does not exist.
+    UndefinedDefinition(String, Span),
+    /// Definitions which reference each other in a cycle, starting and ending with the same name.
+    RecursiveDefinition(Vec<String>, Span),
+}
+impl RegexError {
+    pub fn span(&self) -> Span {
+        return match self {
+            RegexError::UndefinedDefinition(_, span) => *span,
+            RegexError::RecursiveDefinition(_, span) => *span,
+        };
+    }
+}
+
+impl fmt::Display for RegexError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            RegexError::UndefinedDefinition(name, span) => write!(
+                formatter,
+                "The definition {{{}}} does not exist (at {})",
+                name, span
+            ),
+            RegexError::RecursiveDefinition(names, span) => write!(
+                formatter,
+                "The definitions reference each other recursively: {} (at {})",
+                names.join(" -> "),
+                span
+            ),
+        };
+    }
+}
+
+impl Error for RegexError {}
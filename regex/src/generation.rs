@@ -0,0 +1,249 @@
+use crate::RegexEngine;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+// Characters which are preferred as examples of a class, ordered by how readable they are.
+const FALLBACK_CHARACTERS: &str =
+    "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 _-.,;:!?#$%&/()=+*<>@";
+
+// Large ranges are cut off, a few characters of every range are enough to pick from.
+const CHARACTERS_PER_RANGE: usize = 64;
+
+impl RegexEngine<char> {
+    /// Returns the shortest string accepted by the regex. If several strings have the same
+    /// length, the lexicographically smallest one is returned.
+    pub fn shortest_match(&self) -> Option<String> {
+        return self.examples(1).pop();
+    }
+
+    /// Returns the first `count` accepted strings in length-lexicographic order. Every matching
+    /// group contributes a single representative character.
+    pub fn examples(&self, count: usize) -> Vec<String> {
+        let mut examples = Vec::with_capacity(count);
+        if count == 0 {
+            return examples;
+        }
+
+        let characters = self.get_candidate_characters();
+        let representatives = get_representative_characters(&characters);
+        let distances = self.get_distances_to_accepted_state(&characters);
+        if !distances.contains_key(&0) {
+            return examples;
+        }
+
+        let mut frontier = vec![(0, String::new())];
+        while !frontier.is_empty() {
+            let mut next_frontier = Vec::new();
+
+            for (state, prefix) in frontier {
+                let (transitions, accepted_regex) = &self.transitions[&state];
+                if accepted_regex.is_some() {
+                    examples.push(prefix.clone());
+
+                    if examples.len() == count {
+                        return examples;
+                    }
+                }
+
+                for (character, class) in &representatives {
+                    let next_state = match transitions.get(class) {
+                        Some(next_state) => *next_state,
+                        None => continue,
+                    };
+
+                    if distances.contains_key(&next_state) {
+                        let mut next_prefix = prefix.clone();
+                        next_prefix.push(*character);
+                        next_frontier.push((next_state, next_prefix));
+                    }
+                }
+            }
+
+            frontier = next_frontier;
+        }
+
+        return examples;
+    }
+
+    /// Returns `count` random accepted strings which are at most `max_length` characters long.
+    /// The same seed always produces the same samples. If the regex does not accept any string
+    /// within the length bound, no samples are returned.
+    pub fn random_samples(&self, count: usize, max_length: usize, seed: u64) -> Vec<String> {
+        let mut samples = Vec::with_capacity(count);
+        let characters = self.get_candidate_characters();
+        let distances = self.get_distances_to_accepted_state(&characters);
+        match distances.get(&0) {
+            Some(distance) if *distance <= max_length => {}
+            _ => return samples,
+        }
+
+        let mut random = RandomNumberGenerator::new(seed);
+        for _ in 0..count {
+            match self.get_random_sample(&characters, &distances, max_length, &mut random) {
+                Some(sample) => samples.push(sample),
+                None => break,
+            }
+        }
+
+        return samples;
+    }
+
+    /// Walks randomly from the start state until it decides to stop in an accepted state. Only
+    /// transitions which still reach an accepted state within `max_length` are taken. Returns
+    /// `None` if a state has no such transition and does not accept either.
+    fn get_random_sample(
+        &self,
+        characters: &HashMap<usize, Vec<char>>,
+        distances: &HashMap<usize, usize>,
+        max_length: usize,
+        random: &mut RandomNumberGenerator,
+    ) -> Option<String> {
+        let mut sample = String::new();
+        let mut state = 0;
+        let mut length = 0;
+
+        loop {
+            let (transitions, accepted_regex) = &self.transitions[&state];
+            let mut options: Vec<(usize, usize)> = Vec::new();
+            for (class, next_state) in transitions {
+                if !characters.contains_key(class) {
+                    continue;
+                }
+
+                match distances.get(next_state) {
+                    Some(distance) if length + 1 + distance <= max_length => {
+                        options.push((*class, *next_state))
+                    }
+                    _ => {}
+                }
+            }
+            // The transitions are stored in a hash map, sorting them keeps the samples
+            // reproducible for a given seed.
+            options.sort();
+
+            let option_count = options.len() + if accepted_regex.is_some() { 1 } else { 0 };
+            if option_count == 0 {
+                return None;
+            }
+            let choice = random.next_below(option_count);
+            if choice == options.len() {
+                return Some(sample);
+            }
+
+            let (class, next_state) = options[choice];
+            let class_characters = &characters[&class];
+            sample.push(class_characters[random.next_below(class_characters.len())]);
+
+            state = next_state;
+            length += 1;
+        }
+    }
+
+    /// Calculates for every state the minimal amount of characters needed to reach an accepted
+    /// state. Only transitions of classes with candidate characters are followed, because no
+    /// string can be generated for the others. States which can never reach an accepted state
+    /// are not part of the map.
+    fn get_distances_to_accepted_state(
+        &self,
+        characters: &HashMap<usize, Vec<char>>,
+    ) -> HashMap<usize, usize> {
+        let mut reverse_transitions: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut distances = HashMap::new();
+        let mut queue = VecDeque::new();
+
+        for (state, (transitions, accepted_regex)) in &self.transitions {
+            for (class, next_state) in transitions {
+                if characters.contains_key(class) {
+                    reverse_transitions
+                        .entry(*next_state)
+                        .or_default()
+                        .push(*state);
+                }
+            }
+
+            if accepted_regex.is_some() {
+                distances.insert(*state, 0);
+                queue.push_back(*state);
+            }
+        }
+
+        while let Some(state) = queue.pop_front() {
+            let distance = distances[&state];
+
+            if let Some(previous_states) = reverse_transitions.get(&state) {
+                for previous_state in previous_states {
+                    if !distances.contains_key(previous_state) {
+                        distances.insert(*previous_state, distance + 1);
+                        queue.push_back(*previous_state);
+                    }
+                }
+            }
+        }
+
+        return distances;
+    }
+
+    /// Collects characters for every character class from the ranges of the class. Readable
+    /// characters come first: the fallback characters in their order, then all other characters
+    /// which are not control characters. Every range contributes at most
+    /// `CHARACTERS_PER_RANGE` characters from its start, so that large classes like the ones
+    /// of negative groups stay cheap.
+    fn get_candidate_characters(&self) -> HashMap<usize, Vec<char>> {
+        let mut candidates = HashMap::new();
+
+        for class in 0..self.character_classes.len() {
+            let mut characters: Vec<char> = FALLBACK_CHARACTERS
+                .chars()
+                .filter(|character| self.character_classes.get_class(character) == Some(class))
+                .collect();
+
+            let mut range_characters: Vec<char> = Vec::new();
+            for (start, end) in self.character_classes.get_character_ranges(class) {
+                range_characters.extend((start..=end).take(CHARACTERS_PER_RANGE));
+            }
+            range_characters.retain(|character| !characters.contains(character));
+            // The sort is stable, the characters stay in order within both parts.
+            range_characters.sort_by_key(|character| character.is_control());
+            characters.append(&mut range_characters);
+
+            if !characters.is_empty() {
+                candidates.insert(class, characters);
+            }
+        }
+
+        return candidates;
+    }
+}
+
+/// Picks the first candidate character of every character class, sorted by the character.
+fn get_representative_characters(characters: &HashMap<usize, Vec<char>>) -> Vec<(char, usize)> {
+    let mut representatives: Vec<(char, usize)> = characters
+        .iter()
+        .map(|(class, characters)| (characters[0], *class))
+        .collect();
+    representatives.sort();
+
+    return representatives;
+}
+
+/// A small xorshift generator. Test data only needs to be reproducible, not cryptographically
+/// secure.
+struct RandomNumberGenerator {
+    state: u64,
+}
+impl RandomNumberGenerator {
+    pub fn new(seed: u64) -> Self {
+        RandomNumberGenerator {
+            // A state of zero would only ever produce zeros.
+            state: seed ^ 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+
+    pub fn next_below(&mut self, bound: usize) -> usize {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+
+        return (self.state % bound as u64) as usize;
+    }
+}
@@ -1,34 +1,66 @@
 mod ast;
+mod ast_builder;
+mod character_classes;
+mod compiled_regex;
+mod error;
+mod generation;
 mod parsing;
+mod position_set;
+mod prefilter;
+mod regex_builder;
+mod regex_set;
+mod search;
+mod span;
 mod state_machine_builder;
 
 use ast::get_ast_for_concatenation_list;
+pub use character_classes::CharacterClasses;
+pub use compiled_regex::CompiledPosition;
+pub use compiled_regex::CompiledRegex;
+pub use compiled_regex::CompiledState;
+pub use compiled_regex::ElementType;
+pub use error::RegexError;
 use parsing::calculate_concatenation_list;
+use parsing::ParsingContext;
+use position_set::PositionSet;
+pub use prefilter::Prefilter;
+pub use regex_builder::RegexBuilder;
+pub use regex_set::RegexSet;
+pub use search::FindMatches;
+pub use search::Match;
+pub use span::get_syntax_tree_dot_graph;
+pub use span::Span;
+pub use span::SpanTree;
 use state_machine_builder::StateMachineBuilder;
 use std::collections::HashMap;
+use std::hash::Hash;
 
-#[derive(Debug, PartialEq)]
-pub enum RegexAstElements {
-    Alternation(Box<RegexAstElements>, Box<RegexAstElements>),
-    Concatenation(Box<RegexAstElements>, Box<RegexAstElements>),
-    Leaf(MatchingGroup),
+/// The syntax tree of a regex. Regexes parsed from a string match characters, but the tree can
+/// also be built for any other kind of symbol, e.g. the tokens of a lexer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RegexAstElements<T = char> {
+    Alternation(Box<RegexAstElements<T>>, Box<RegexAstElements<T>>),
+    Concatenation(Box<RegexAstElements<T>>, Box<RegexAstElements<T>>),
+    Leaf(MatchingGroup<T>),
     None,
-    ZeroOrMore(Box<RegexAstElements>),
-    ZeroOrOne(Box<RegexAstElements>),
+    ZeroOrMore(Box<RegexAstElements<T>>),
+    ZeroOrOne(Box<RegexAstElements<T>>),
 }
 
-#[derive(Clone, Debug, PartialEq)]
-pub enum MatchingGroup {
-    Character(char),
-    Group(Vec<MatchingGroupElements>),
-    NegativeGroup(Vec<MatchingGroupElements>),
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum MatchingGroup<T = char> {
+    Character(T),
+    Group(Vec<MatchingGroupElements<T>>),
+    NegativeGroup(Vec<MatchingGroupElements<T>>),
     AcceptedState,
 }
 
-#[derive(Clone, Debug, PartialEq)]
-pub enum MatchingGroupElements {
-    Character(char),
-    Range(char, char),
+/// Ranges can only be matched for symbols with an order, for characters this is the order of
+/// their code points.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum MatchingGroupElements<T = char> {
+    Character(T),
+    Range(T, T),
 }
 
 struct ConcatenationList {
@@ -50,18 +82,21 @@ struct State {
     pub left_next: Option<Vec<usize>>,
     pub right_next: Option<Vec<usize>>,
     pub is_escaped: bool,
+    pub span: Span,
 }
 impl State {
     pub fn new(
         matching_group: MatchingGroup,
         left_next: Option<Vec<usize>>,
         right_next: Option<Vec<usize>>,
+        span: Span,
     ) -> Self {
         State {
             matching_group,
             left_next,
             right_next,
             is_escaped: false,
+            span,
         }
     }
 
@@ -69,121 +104,147 @@ impl State {
         matching_group: MatchingGroup,
         left_next: Option<Vec<usize>>,
         right_next: Option<Vec<usize>>,
+        span: Span,
     ) -> Self {
         State {
             matching_group,
             left_next,
             right_next,
             is_escaped: true,
+            span,
         }
     }
 }
 
-type TransitionForMatchingGroup = HashMap<usize, usize>;
-type StateMachine = HashMap<usize, (TransitionForMatchingGroup, bool)>;
-pub struct RegexEngine {
-    matching_groups: Vec<MatchingGroup>,
+type TransitionForCharacterClass = HashMap<usize, usize>;
+type StateMachine = HashMap<usize, (TransitionForCharacterClass, Option<usize>)>;
+pub struct RegexEngine<T = char> {
+    character_classes: CharacterClasses<T>,
     transitions: StateMachine,
+    prefilter: Prefilter,
+    unanchored_engine: Option<Box<RegexEngine<T>>>,
+    reverse_engine: Option<Box<RegexEngine<T>>>,
 }
 impl RegexEngine {
     pub(crate) fn new(ast: &RegexAstElements) -> Self {
-        return StateMachineBuilder::create_regex_engine(ast);
+        let mut compiled_regex = StateMachineBuilder::create_compiled_regex(ast);
+        compiled_regex.prefilter = Prefilter::from_ast(ast);
+
+        return compiled_regex.into_regex_engine();
     }
 
+    /// Returns the engine without its prefilter, so that searches try every position. This is
+    /// mostly useful to compare the speed of searches.
+    pub fn without_prefilter(mut self) -> Self {
+        self.prefilter = Prefilter::None;
+
+        return self;
+    }
+
+    pub fn matches(&self, string: &str) -> bool {
+        let characters: Vec<char> = string.chars().collect();
+
+        return self.matches_symbols(&characters);
+    }
+}
+impl<T: Eq + Hash + Clone + PartialOrd> RegexEngine<T> {
     pub(crate) fn new_with_values(
-        matching_groups: Vec<MatchingGroup>,
+        character_classes: CharacterClasses<T>,
         transitions: StateMachine,
+        prefilter: Prefilter,
+        unanchored_engine: Option<Box<RegexEngine<T>>>,
+        reverse_engine: Option<Box<RegexEngine<T>>>,
     ) -> Self {
         RegexEngine {
-            matching_groups,
+            character_classes,
             transitions,
+            prefilter,
+            unanchored_engine,
+            reverse_engine,
         }
     }
 
-    pub fn matches(&self, string: &str) -> bool {
+    /// Returns whether the regex matches the whole sequence of symbols.
+    pub fn matches_symbols(&self, symbols: &[T]) -> bool {
         let mut current_state = 0;
-        let characters: Vec<char> = string.chars().collect();
-
-        for character in characters {
-            let (matching_group_transitions, _) = match self.transitions.get(&current_state) {
-                Some(transitions) => transitions,
-                None => return false,
-            };
-
-            let matching_group_index = match self.get_matching_group_index(character) {
-                Some(index) => index,
-                None => return false,
-            };
 
-            match matching_group_transitions.get(&matching_group_index) {
-                Some(new_state) => {
-                    current_state = *new_state;
-                }
+        for symbol in symbols {
+            match self.get_next_state(current_state, symbol) {
+                Some(new_state) => current_state = new_state,
                 None => return false,
             }
         }
 
-        match self.transitions.get(&current_state) {
-            Some((_, is_accepted)) => return *is_accepted,
-            None => return false,
-        }
+        return self.is_accepted_state(current_state);
     }
 
-    fn get_matching_group_index(&self, character: char) -> Option<usize> {
-        for i in 0..self.matching_groups.len() {
-            match &self.matching_groups[i] {
-                MatchingGroup::AcceptedState => {}
-                MatchingGroup::Character(matching_character) => {
-                    if *matching_character == character {
-                        return Some(i);
-                    }
-                }
-                MatchingGroup::Group(ref elements) => {
-                    for element in elements {
-                        match element {
-                            MatchingGroupElements::Character(matching_character) => {
-                                if *matching_character == character {
-                                    return Some(i);
-                                }
-                            }
-                            MatchingGroupElements::Range(start_character, end_character) => {
-                                if *start_character <= character && *end_character >= character {
-                                    return Some(i);
-                                }
-                            }
-                        }
-                    }
-                }
-                MatchingGroup::NegativeGroup(ref elements) => {
-                    for element in elements {
-                        match element {
-                            MatchingGroupElements::Character(matching_character) => {
-                                if *matching_character == character {
-                                    return None;
-                                }
-                            }
-                            MatchingGroupElements::Range(start_character, end_character) => {
-                                if *start_character <= character && *end_character >= character {
-                                    return None;
-                                }
-                            }
-                        }
-                    }
-
-                    return Some(i);
-                }
-            }
-        }
+    pub(crate) fn get_next_state(&self, state: usize, symbol: &T) -> Option<usize> {
+        let (class_transitions, _) = self.transitions.get(&state)?;
+        let class = self.character_classes.get_class(symbol)?;
 
-        return None;
+        return class_transitions.get(&class).cloned();
+    }
+
+    pub(crate) fn is_accepted_state(&self, state: usize) -> bool {
+        return self.get_accepted_regex(state).is_some();
+    }
+
+    /// Returns the index of the regex which is accepted in the state. Only state machines built
+    /// for several regexes at once can accept any other regex than the first one.
+    pub(crate) fn get_accepted_regex(&self, state: usize) -> Option<usize> {
+        return match self.transitions.get(&state) {
+            Some((_, accepted_regex)) => *accepted_regex,
+            None => None,
+        };
     }
 }
 
 pub fn get_regex_syntax_tree(regex: &str) -> RegexAstElements {
+    let (ast, _) = get_regex_syntax_tree_with_spans(regex);
+
+    return ast;
+}
+
+/// Returns the syntax tree together with the spans of the regex its nodes were parsed from.
+pub fn get_regex_syntax_tree_with_spans(regex: &str) -> (RegexAstElements, SpanTree) {
     let mut stack = Vec::with_capacity(regex.len() + 1);
-    let concatenation_list = calculate_concatenation_list(&mut stack, regex);
+    // Without definitions braces are normal characters, therefore parsing can not fail.
+    let mut context = ParsingContext::new(None, false);
+    let concatenation_list = match calculate_concatenation_list(&mut stack, regex, &mut context) {
+        Ok(concatenation_list) => concatenation_list,
+        Err(error) => panic!("{}", error),
+    };
+
+    return get_ast_for_concatenation_list(&stack, &concatenation_list.list, regex.len());
+}
+
+/// Compiles the regex and returns all intermediate calculations, e.g. for debugging or
+/// visualising the construction of the state machine.
+pub fn get_compiled_regex(regex: &str) -> CompiledRegex {
+    let ast = get_regex_syntax_tree(regex);
+    let mut compiled_regex = StateMachineBuilder::create_compiled_regex(&ast);
+    compiled_regex.prefilter = Prefilter::from_ast(&ast);
+
+    return compiled_regex;
+}
+
+/// Compiles a syntax tree which was built without parsing a regex. The accepted state is added
+/// to the end of the tree.
+pub fn get_compiled_regex_for_ast<T: Eq + Hash + Clone + PartialOrd>(
+    ast: RegexAstElements<T>,
+) -> CompiledRegex<T> {
+    let ast = RegexAstElements::Concatenation(
+        Box::new(ast),
+        Box::new(RegexAstElements::Leaf(MatchingGroup::AcceptedState)),
+    );
+
+    return StateMachineBuilder::create_compiled_regex(&ast);
+}
 
-    return get_ast_for_concatenation_list(&stack, &concatenation_list.list);
+pub fn get_regex_engine_for_ast<T: Eq + Hash + Clone + PartialOrd>(
+    ast: RegexAstElements<T>,
+) -> RegexEngine<T> {
+    return get_compiled_regex_for_ast(ast).into_regex_engine();
 }
 
 pub fn get_regex_engine(regex: &str) -> RegexEngine {
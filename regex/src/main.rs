@@ -0,0 +1,272 @@
+use regex::get_compiled_regex;
+use regex::get_regex_engine;
+use regex::Match;
+use regex::RegexEngine;
+use std::env;
+use std::fs;
+use std::io;
+use std::io::BufWriter;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process;
+
+const USAGE: &str = "Usage: regex [OPTIONS] PATTERN [PATH...]
+
+Searches the files, or stdin if no path is given, for lines matching the pattern.
+
+Options:
+    -c, --count          Print the number of matching lines per file
+    -o, --only-matching  Print only the matched parts of each line
+    -v, --invert-match   Select the lines which do not match
+    -n, --line-number    Print the line number before each line
+    -r, --recursive      Search directories recursively
+        --dump-dfa       Print the compiled state machines of the pattern and exit
+    -h, --help           Print this help";
+
+#[derive(Debug, Default)]
+struct Options {
+    pattern: String,
+    paths: Vec<PathBuf>,
+    count: bool,
+    only_matching: bool,
+    invert_match: bool,
+    line_number: bool,
+    recursive: bool,
+    dump_dfa: bool,
+}
+
+fn parse_arguments(arguments: Vec<String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut pattern = None;
+    let mut only_positional_arguments = false;
+
+    for argument in arguments {
+        if only_positional_arguments || !argument.starts_with('-') || argument == "-" {
+            match pattern {
+                None => pattern = Some(argument),
+                Some(_) => options.paths.push(PathBuf::from(argument)),
+            }
+
+            continue;
+        }
+
+        match argument.as_str() {
+            "--" => only_positional_arguments = true,
+            "--count" => options.count = true,
+            "--only-matching" => options.only_matching = true,
+            "--invert-match" => options.invert_match = true,
+            "--line-number" => options.line_number = true,
+            "--recursive" => options.recursive = true,
+            "--dump-dfa" => options.dump_dfa = true,
+            "--help" => return Err(String::new()),
+            _ if argument.starts_with("--") => {
+                return Err(format!("Unknown option {}", argument));
+            }
+            // Short options can be combined, e.g. `-cv`.
+            _ => {
+                for flag in argument.chars().skip(1) {
+                    match flag {
+                        'c' => options.count = true,
+                        'o' => options.only_matching = true,
+                        'v' => options.invert_match = true,
+                        'n' => options.line_number = true,
+                        'r' => options.recursive = true,
+                        'h' => return Err(String::new()),
+                        _ => return Err(format!("Unknown option -{}", flag)),
+                    }
+                }
+            }
+        }
+    }
+
+    options.pattern = match pattern {
+        Some(pattern) => pattern,
+        None => return Err(String::from("No pattern given")),
+    };
+
+    return Ok(options);
+}
+
+/// Searches files and writes the selected lines. Errors for single files are reported, but do
+/// not stop the search.
+struct Searcher<'a, W: Write> {
+    options: &'a Options,
+    regex_engine: RegexEngine,
+    output: W,
+    print_file_names: bool,
+    has_selected_lines: bool,
+    has_errors: bool,
+}
+impl<'a, W: Write> Searcher<'a, W> {
+    fn search_path(&mut self, path: &Path) -> io::Result<()> {
+        if path == Path::new("-") {
+            let mut text = String::new();
+            match io::stdin().read_to_string(&mut text) {
+                Ok(_) => return self.search_text("(standard input)", &text),
+                Err(error) => {
+                    self.report_error(path, &error);
+                    return Ok(());
+                }
+            }
+        }
+
+        if path.is_dir() {
+            if !self.options.recursive {
+                self.report_error(path, &"Is a directory");
+                return Ok(());
+            }
+
+            let mut entries = match fs::read_dir(path) {
+                Ok(entries) => entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .collect::<Vec<PathBuf>>(),
+                Err(error) => {
+                    self.report_error(path, &error);
+                    return Ok(());
+                }
+            };
+            // Directory entries come in no particular order, sorting them keeps the output stable.
+            entries.sort();
+
+            for entry in entries {
+                self.search_path(&entry)?;
+            }
+
+            return Ok(());
+        }
+
+        return match fs::read(path) {
+            // Files which are not valid UTF-8 are searched anyway, the invalid bytes can never
+            // be part of a match.
+            Ok(bytes) => self.search_text(
+                &path.display().to_string(),
+                &String::from_utf8_lossy(&bytes),
+            ),
+            Err(error) => {
+                self.report_error(path, &error);
+                Ok(())
+            }
+        };
+    }
+
+    fn search_text(&mut self, name: &str, text: &str) -> io::Result<()> {
+        let mut count = 0;
+
+        for (line_index, line) in text.lines().enumerate() {
+            if self.regex_engine.is_match(line) == self.options.invert_match {
+                continue;
+            }
+
+            count += 1;
+            self.has_selected_lines = true;
+            if self.options.count {
+                continue;
+            }
+
+            if self.options.only_matching {
+                // Inverted lines have no matches which could be printed.
+                if self.options.invert_match {
+                    continue;
+                }
+
+                let matches: Vec<Match> = self.regex_engine.find_iter(line).collect();
+                for found_match in matches {
+                    if found_match.start < found_match.end {
+                        self.write_prefix(name, line_index)?;
+                        writeln!(self.output, "{}", found_match.as_str(line))?;
+                    }
+                }
+            } else {
+                self.write_prefix(name, line_index)?;
+                writeln!(self.output, "{}", line)?;
+            }
+        }
+
+        if self.options.count {
+            if self.print_file_names {
+                write!(self.output, "{}:", name)?;
+            }
+            writeln!(self.output, "{}", count)?;
+        }
+
+        return Ok(());
+    }
+
+    fn write_prefix(&mut self, name: &str, line_index: usize) -> io::Result<()> {
+        if self.print_file_names {
+            write!(self.output, "{}:", name)?;
+        }
+
+        if self.options.line_number {
+            write!(self.output, "{}:", line_index + 1)?;
+        }
+
+        return Ok(());
+    }
+
+    fn report_error(&mut self, path: &Path, error: &dyn std::fmt::Display) {
+        eprintln!("regex: {}: {}", path.display(), error);
+        self.has_errors = true;
+    }
+}
+
+fn main() {
+    let mut options = match parse_arguments(env::args().skip(1).collect()) {
+        Ok(options) => options,
+        Err(message) if message.is_empty() => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(message) => {
+            eprintln!("regex: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+
+    if options.dump_dfa {
+        print!("{}", get_compiled_regex(&options.pattern));
+        return;
+    }
+
+    if options.paths.is_empty() {
+        options.paths.push(PathBuf::from("-"));
+    }
+
+    let stdout = io::stdout();
+    let mut searcher = Searcher {
+        options: &options,
+        regex_engine: get_regex_engine(&options.pattern),
+        output: BufWriter::new(stdout.lock()),
+        print_file_names: options.paths.len() > 1 || options.recursive,
+        has_selected_lines: false,
+        has_errors: false,
+    };
+
+    let mut result = Ok(());
+    for path in &options.paths {
+        result = searcher.search_path(path);
+        if result.is_err() {
+            break;
+        }
+    }
+    let result = result.and_then(|_| searcher.output.flush());
+
+    match result {
+        Ok(()) => {}
+        // The output was closed early, e.g. by `head`.
+        Err(ref error) if error.kind() == io::ErrorKind::BrokenPipe => process::exit(0),
+        Err(error) => {
+            eprintln!("regex: {}", error);
+            process::exit(2);
+        }
+    }
+
+    // Like grep, the exit code tells whether any line was selected.
+    if searcher.has_errors {
+        process::exit(2);
+    } else if !searcher.has_selected_lines {
+        process::exit(1);
+    }
+}
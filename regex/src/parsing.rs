@@ -3,8 +3,11 @@ mod characters;
 use crate::ConcatenationList;
 use crate::MatchingGroup;
 use crate::MatchingGroupElements;
+use crate::RegexError;
+use crate::Span;
 use crate::State;
 use characters::get_character_array;
+use std::collections::HashMap;
 use std::convert::From;
 
 impl From<MatchingGroup> for MatchingGroupElements {
@@ -31,13 +34,101 @@ impl CharacterGroupCalculation {
     }
 }
 
+/// The definitions which can be referenced through `{NAME}` together with the names which are
+/// currently being expanded. Without definitions braces are matched as normal characters.
+pub(crate) struct ParsingContext<'a> {
+    definitions: Option<&'a HashMap<String, String>>,
+    expanded_definitions: Vec<String>,
+    is_verbose: bool,
+    // Definitions are parsed from their own text, their elements and errors therefore point at the
+    // reference in the regex that is being parsed.
+    reference_span: Span,
+}
+impl<'a> ParsingContext<'a> {
+    pub fn new(definitions: Option<&'a HashMap<String, String>>, is_verbose: bool) -> Self {
+        ParsingContext {
+            definitions,
+            expanded_definitions: Vec::new(),
+            is_verbose,
+            reference_span: Span::new(0, 0),
+        }
+    }
+
+    fn expand_definition(
+        &mut self,
+        stack: &mut Vec<State>,
+        name: &str,
+        span: Span,
+    ) -> Result<Vec<usize>, RegexError> {
+        let is_outermost_reference = self.expanded_definitions.is_empty();
+        if is_outermost_reference {
+            self.reference_span = span;
+        }
+
+        if let Some(index) = self
+            .expanded_definitions
+            .iter()
+            .position(|expanded| expanded == name)
+        {
+            let mut cycle = self.expanded_definitions[index..].to_vec();
+            cycle.push(name.to_string());
+
+            return Err(RegexError::RecursiveDefinition(cycle, self.reference_span));
+        }
+
+        let definitions = self.definitions;
+        let regex = match definitions.and_then(|definitions| definitions.get(name)) {
+            Some(regex) => regex,
+            None => {
+                return Err(RegexError::UndefinedDefinition(
+                    name.to_string(),
+                    self.reference_span,
+                ))
+            }
+        };
+
+        let first_state_index = stack.len();
+        self.expanded_definitions.push(name.to_string());
+        let concatenation_list = calculate_concatenation_list(stack, regex, self)?;
+        self.expanded_definitions.pop();
+
+        if is_outermost_reference {
+            for state in &mut stack[first_state_index..] {
+                state.span = span;
+            }
+        }
+
+        return Ok(concatenation_list.list);
+    }
+}
+
 pub(crate) fn calculate_concatenation_list(
     stack: &mut Vec<State>,
     regex: &str,
-) -> ConcatenationList {
-    let regex_characters = get_character_array(regex);
+    context: &mut ParsingContext,
+) -> Result<ConcatenationList, RegexError> {
+    let regex_characters = get_character_array(regex, context.is_verbose);
+
+    return calculate_concatenation_list_for_characters(
+        stack,
+        &regex_characters.characters,
+        &regex_characters.spans,
+        context,
+    );
+}
+
+fn calculate_concatenation_list_for_characters(
+    stack: &mut Vec<State>,
+    regex_characters: &[MatchingGroup],
+    spans: &[Span],
+    context: &mut ParsingContext,
+) -> Result<ConcatenationList, RegexError> {
     let mut character_is_in_quotes = false;
     let mut concatenation_list = Vec::new();
+    // Groups and definitions add multiple elements to the concatenation list. Repetitions need
+    // to know where the last of these elements starts, to repeat all of them.
+    let mut last_element_start = 0;
+    let mut last_element_span = Span::new(0, 0);
 
     let mut index = 0;
     loop {
@@ -45,13 +136,16 @@ pub(crate) fn calculate_concatenation_list(
             // Here the index is NOT one short of the amount of characters we have consumed, since
             // at the end of the last iteration it was increased by one and we have not yet
             // consumed another character.
-            return ConcatenationList::new(concatenation_list, index);
+            return Ok(ConcatenationList::new(concatenation_list, index));
         }
         let mut character = &regex_characters[index];
+        let mut span = spans[index];
 
         if character_is_in_quotes && character != &MatchingGroup::Character('"') {
+            last_element_start = concatenation_list.len();
+            last_element_span = span;
             concatenation_list.push(stack.len());
-            stack.push(State::new_escaped(character.clone(), None, None));
+            stack.push(State::new_escaped(character.clone(), None, None, span));
 
             index += 1;
 
@@ -63,69 +157,143 @@ pub(crate) fn calculate_concatenation_list(
             character_group = get_character_group(&regex_characters[index + 1..]);
             character = &character_group.group;
             index += character_group.consumed_characters;
+            span = span.union(spans[index]);
         }
 
         match character {
             MatchingGroup::Character('*') => {
+                group_last_element(
+                    stack,
+                    &mut concatenation_list,
+                    last_element_start,
+                    last_element_span,
+                );
+
                 let list_length = concatenation_list.len();
                 let last_state_index = concatenation_list[list_length - 1];
+                last_element_span = last_element_span.union(span);
                 concatenation_list[list_length - 1] = stack.len();
                 stack.push(State::new(
                     MatchingGroup::Character('*'),
                     Some(vec![last_state_index]),
                     None,
+                    last_element_span,
                 ));
+                last_element_start = list_length - 1;
             }
             MatchingGroup::Character('?') => {
+                group_last_element(
+                    stack,
+                    &mut concatenation_list,
+                    last_element_start,
+                    last_element_span,
+                );
+
                 let list_length = concatenation_list.len();
                 let last_state_index = concatenation_list[list_length - 1];
+                last_element_span = last_element_span.union(span);
                 concatenation_list[list_length - 1] = stack.len();
                 stack.push(State::new(
                     MatchingGroup::Character('?'),
                     Some(vec![last_state_index]),
                     None,
+                    last_element_span,
                 ));
+                last_element_start = list_length - 1;
             }
             MatchingGroup::Character('+') => {
+                group_last_element(
+                    stack,
+                    &mut concatenation_list,
+                    last_element_start,
+                    last_element_span,
+                );
+
                 let list_length = concatenation_list.len();
                 let last_state_index = concatenation_list[list_length - 1];
+                last_element_span = last_element_span.union(span);
                 concatenation_list.push(stack.len());
                 stack.push(State::new(
                     MatchingGroup::Character('*'),
                     Some(vec![last_state_index]),
                     None,
+                    last_element_span,
                 ));
+                last_element_start = list_length;
             }
             MatchingGroup::Character('(') => {
-                let mut group_list = calculate_concatenation_list(stack, &regex[index + 1..]);
+                let mut group_list = calculate_concatenation_list_for_characters(
+                    stack,
+                    &regex_characters[index + 1..],
+                    &spans[index + 1..],
+                    context,
+                )?;
+                last_element_start = concatenation_list.len();
                 concatenation_list.append(&mut group_list.list);
                 index += group_list.consumed_characters;
+
+                // The closing parenthesis is missing if the group is never closed.
+                last_element_span = match spans.get(index) {
+                    Some(closing_span) => span.union(*closing_span),
+                    None => span.union(spans[index - 1]),
+                };
             }
             MatchingGroup::Character(')') => {
                 // The index is always 1 short of how many characters we have consumed.
-                return ConcatenationList::new(concatenation_list, index + 1);
+                return Ok(ConcatenationList::new(concatenation_list, index + 1));
             }
             MatchingGroup::Character('|') => {
                 let first_list = concatenation_list;
-                let second_concatenation_list =
-                    calculate_concatenation_list(stack, &regex[index + 1..]);
+                let second_concatenation_list = calculate_concatenation_list_for_characters(
+                    stack,
+                    &regex_characters[index + 1..],
+                    &spans[index + 1..],
+                    context,
+                )?;
                 let second_list = second_concatenation_list.list;
+                let alternation_span =
+                    get_list_span(stack, &second_list, get_list_span(stack, &first_list, span));
                 stack.push(State::new(
                     MatchingGroup::Character('|'),
                     Some(first_list),
                     Some(second_list),
+                    alternation_span,
                 ));
 
-                return ConcatenationList::new(
+                return Ok(ConcatenationList::new(
                     vec![stack.len() - 1],
                     // The index is always 1 short of how many characters we have consumed.
                     index + 1 + second_concatenation_list.consumed_characters,
-                );
+                ));
             }
             MatchingGroup::Character('"') => character_is_in_quotes = !character_is_in_quotes,
+            MatchingGroup::Character('{') if context.definitions.is_some() => {
+                match get_definition_name(&regex_characters[index + 1..]) {
+                    Some(name) => {
+                        // The name and the closing brace.
+                        let reference_length = name.chars().count() + 1;
+                        let reference_span = span.union(spans[index + reference_length]);
+                        let mut definition_list =
+                            context.expand_definition(stack, &name, reference_span)?;
+
+                        last_element_start = concatenation_list.len();
+                        last_element_span = reference_span;
+                        concatenation_list.append(&mut definition_list);
+                        index += reference_length;
+                    }
+                    None => {
+                        last_element_start = concatenation_list.len();
+                        last_element_span = span;
+                        concatenation_list.push(stack.len());
+                        stack.push(State::new(character.clone(), None, None, span));
+                    }
+                }
+            }
             _ => {
+                last_element_start = concatenation_list.len();
+                last_element_span = span;
                 concatenation_list.push(stack.len());
-                stack.push(State::new(character.clone(), None, None));
+                stack.push(State::new(character.clone(), None, None, span));
             }
         }
 
@@ -133,6 +301,53 @@ pub(crate) fn calculate_concatenation_list(
     }
 }
 
+/// Extends the span to cover all elements of the list.
+fn get_list_span(stack: &[State], list: &[usize], span: Span) -> Span {
+    return list.iter().fold(span, |list_span, state_index| {
+        list_span.union(stack[*state_index].span)
+    });
+}
+
+/// Combines the elements of a group or definition into a single element, so that a following
+/// repetition applies to all of them.
+fn group_last_element(
+    stack: &mut Vec<State>,
+    concatenation_list: &mut Vec<usize>,
+    last_element_start: usize,
+    last_element_span: Span,
+) {
+    if concatenation_list.len() - last_element_start > 1 {
+        let group_list = concatenation_list.split_off(last_element_start);
+        concatenation_list.push(stack.len());
+        stack.push(State::new(
+            MatchingGroup::Character('('),
+            Some(group_list),
+            None,
+            last_element_span,
+        ));
+    }
+}
+
+/// Returns the name of a definition reference like `{NAME}`, the characters start after the
+/// opening brace. Names consist of letters, digits and underscores.
+fn get_definition_name(characters: &[MatchingGroup]) -> Option<String> {
+    let mut name = String::new();
+
+    for character in characters {
+        match character {
+            MatchingGroup::Character('}') if !name.is_empty() => return Some(name),
+            MatchingGroup::Character(character)
+                if character.is_ascii_alphanumeric() || *character == '_' =>
+            {
+                name.push(*character)
+            }
+            _ => return None,
+        }
+    }
+
+    return None;
+}
+
 fn get_character_group(characters: &[MatchingGroup]) -> CharacterGroupCalculation {
     let mut index = 0;
     let mut state = 0;
@@ -1,58 +1,128 @@
 use crate::MatchingGroup;
+use crate::Span;
 use std::convert::TryFrom;
 
-pub fn get_character_array(regex: &str) -> Vec<MatchingGroup> {
-    let input_characters: Vec<char> = regex.chars().collect();
+const VERBOSE_FLAG: &str = "(?x)";
+
+/// The characters of a regex together with the span of the pattern each character was read from.
+/// Escape sequences span all of their characters.
+pub struct CharacterArray {
+    pub characters: Vec<MatchingGroup>,
+    pub spans: Vec<Span>,
+}
+impl CharacterArray {
+    fn with_capacity(capacity: usize) -> Self {
+        CharacterArray {
+            characters: Vec::with_capacity(capacity),
+            spans: Vec::with_capacity(capacity),
+        }
+    }
+
+    fn push(&mut self, character: char, start: usize, end: usize) {
+        self.characters.push(MatchingGroup::Character(character));
+        self.spans.push(Span::new(start, end));
+    }
+}
+
+/// Splits the regex into characters and resolves escape sequences. In verbose mode, which is
+/// enabled by the option or a leading `(?x)`, whitespace is ignored and `#` starts a comment until
+/// the end of the line. Whitespace in quotes, in character groups or after a `\` is kept.
+pub fn get_character_array(regex: &str, is_verbose: bool) -> CharacterArray {
+    let (prefix_length, is_verbose) = match regex.strip_prefix(VERBOSE_FLAG) {
+        Some(_) => (VERBOSE_FLAG.len(), true),
+        None => (0, is_verbose),
+    };
     let mut first_hexa_character = ' ';
-    let mut output_characters = Vec::with_capacity(input_characters.len() + 1);
+    let mut output_characters = CharacterArray::with_capacity(regex.len() + 1);
     let mut state = 0;
+    let mut is_in_quotes = false;
+    let mut is_in_group = false;
+    let mut is_in_comment = false;
+    // The offsets of the current escape sequence and its first hex character.
+    let mut escape_start = 0;
+    let mut hexa_start = 0;
 
-    for index in 0..input_characters.len() {
-        let current_character = input_characters[index];
+    for (offset, current_character) in regex[prefix_length..].char_indices() {
+        let offset = prefix_length + offset;
+        let end = offset + current_character.len_utf8();
+        if is_in_comment {
+            is_in_comment = current_character != '\n';
+            continue;
+        }
+
+        let is_ignorable = is_verbose && !is_in_quotes && !is_in_group;
         match state {
             0 => match current_character {
-                '\\' => state = 1,
-                _ => output_characters.push(MatchingGroup::Character(current_character)),
+                '\\' => {
+                    escape_start = offset;
+                    state = 1;
+                }
+                '#' if is_ignorable => is_in_comment = true,
+                _ if is_ignorable && current_character.is_whitespace() => {}
+                _ => {
+                    match current_character {
+                        '"' if !is_in_group => is_in_quotes = !is_in_quotes,
+                        '[' if !is_in_quotes => is_in_group = true,
+                        ']' => is_in_group = false,
+                        _ => {}
+                    }
+
+                    output_characters.push(current_character, offset, end);
+                }
             },
             // The previous character was '\'
             1 => match current_character {
                 'r' => {
-                    output_characters.push(MatchingGroup::Character('\r'));
+                    output_characters.push('\r', escape_start, end);
                     state = 0;
                 }
                 'n' => {
-                    output_characters.push(MatchingGroup::Character('\n'));
+                    output_characters.push('\n', escape_start, end);
                     state = 0;
                 }
                 't' => {
-                    output_characters.push(MatchingGroup::Character('\t'));
+                    output_characters.push('\t', escape_start, end);
                     state = 0;
                 }
                 'x' => {
                     state = 2;
                 }
-                '\\' => output_characters.push(MatchingGroup::Character('\\')),
+                // Escaped whitespace and comment starts are matched literally in verbose mode.
+                '#' if is_verbose => {
+                    output_characters.push(current_character, escape_start, end);
+                    state = 0;
+                }
+                _ if is_verbose && current_character.is_whitespace() => {
+                    output_characters.push(current_character, escape_start, end);
+                    state = 0;
+                }
+                '\\' => {
+                    output_characters.push('\\', escape_start, end);
+                    escape_start = offset;
+                }
                 _ => {
-                    output_characters.push(MatchingGroup::Character('\\'));
-                    output_characters.push(MatchingGroup::Character(current_character));
+                    output_characters.push('\\', escape_start, escape_start + 1);
+                    output_characters.push(current_character, offset, end);
                 }
             },
             // The previous characters where `\x`
             2 => match current_character {
                 '0'..='9' | 'a'..='f' | 'A'..='F' => {
                     first_hexa_character = current_character;
+                    hexa_start = offset;
                     state = 3;
                 }
                 '\\' => {
-                    output_characters.push(MatchingGroup::Character('\\'));
-                    output_characters.push(MatchingGroup::Character('x'));
+                    output_characters.push('\\', escape_start, escape_start + 1);
+                    output_characters.push('x', escape_start + 1, escape_start + 2);
 
+                    escape_start = offset;
                     state = 1;
                 }
                 _ => {
-                    output_characters.push(MatchingGroup::Character('\\'));
-                    output_characters.push(MatchingGroup::Character('x'));
-                    output_characters.push(MatchingGroup::Character(current_character));
+                    output_characters.push('\\', escape_start, escape_start + 1);
+                    output_characters.push('x', escape_start + 1, escape_start + 2);
+                    output_characters.push(current_character, offset, end);
                 }
             },
             // The previous characters where `\x` and a hex character
@@ -63,26 +133,25 @@ pub fn get_character_array(regex: &str) -> Vec<MatchingGroup> {
                     let character_value = first_character_value * 16 + second_character_value;
 
                     match char::try_from(character_value) {
-                        Ok(character) => {
-                            output_characters.push(MatchingGroup::Character(character))
-                        }
+                        Ok(character) => output_characters.push(character, escape_start, end),
                         Err(_) => panic!("Invalid hex character found"),
                     }
 
                     state = 0;
                 }
                 '\\' => {
-                    output_characters.push(MatchingGroup::Character('\\'));
-                    output_characters.push(MatchingGroup::Character('x'));
-                    output_characters.push(MatchingGroup::Character(first_hexa_character));
+                    output_characters.push('\\', escape_start, escape_start + 1);
+                    output_characters.push('x', escape_start + 1, escape_start + 2);
+                    output_characters.push(first_hexa_character, hexa_start, hexa_start + 1);
 
+                    escape_start = offset;
                     state = 1;
                 }
                 _ => {
-                    output_characters.push(MatchingGroup::Character('\\'));
-                    output_characters.push(MatchingGroup::Character('x'));
-                    output_characters.push(MatchingGroup::Character(first_hexa_character));
-                    output_characters.push(MatchingGroup::Character(current_character));
+                    output_characters.push('\\', escape_start, escape_start + 1);
+                    output_characters.push('x', escape_start + 1, escape_start + 2);
+                    output_characters.push(first_hexa_character, hexa_start, hexa_start + 1);
+                    output_characters.push(current_character, offset, end);
 
                     state = 0;
                 }
@@ -92,15 +161,15 @@ pub fn get_character_array(regex: &str) -> Vec<MatchingGroup> {
     }
 
     if state > 0 {
-        output_characters.push(MatchingGroup::Character('\\'));
+        output_characters.push('\\', escape_start, escape_start + 1);
     }
 
     if state > 1 {
-        output_characters.push(MatchingGroup::Character('x'));
+        output_characters.push('x', escape_start + 1, escape_start + 2);
     }
 
     if state == 3 {
-        output_characters.push(MatchingGroup::Character(first_hexa_character));
+        output_characters.push(first_hexa_character, hexa_start, hexa_start + 1);
     }
 
     return output_characters;
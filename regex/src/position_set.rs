@@ -0,0 +1,82 @@
+/// A set of positions in the syntax tree, stored as a bitset.
+///
+/// The set never stores trailing empty blocks. Two sets containing the same positions therefore
+/// always have the same representation, which allows them to be compared and hashed directly.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub(crate) struct PositionSet {
+    blocks: Vec<u64>,
+}
+impl PositionSet {
+    pub fn new() -> Self {
+        PositionSet { blocks: Vec::new() }
+    }
+
+    pub fn from_position(position: usize) -> Self {
+        let mut set = PositionSet::new();
+        set.insert(position);
+
+        return set;
+    }
+
+    pub fn insert(&mut self, position: usize) {
+        let block_index = position / 64;
+        if block_index >= self.blocks.len() {
+            self.blocks.resize(block_index + 1, 0);
+        }
+
+        self.blocks[block_index] |= 1 << (position % 64);
+    }
+
+    pub fn union_with(&mut self, other: &PositionSet) {
+        if other.blocks.len() > self.blocks.len() {
+            self.blocks.resize(other.blocks.len(), 0);
+        }
+
+        for (block, other_block) in self.blocks.iter_mut().zip(&other.blocks) {
+            *block |= other_block;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.blocks.is_empty();
+    }
+
+    /// Returns the positions in ascending order.
+    pub fn iter(&self) -> PositionSetIterator<'_> {
+        PositionSetIterator {
+            blocks: &self.blocks,
+            block_index: 0,
+            current_block: self.blocks.first().cloned().unwrap_or(0),
+        }
+    }
+
+    pub fn to_vec(&self) -> Vec<usize> {
+        return self.iter().collect();
+    }
+}
+
+pub(crate) struct PositionSetIterator<'a> {
+    blocks: &'a [u64],
+    block_index: usize,
+    current_block: u64,
+}
+impl<'a> Iterator for PositionSetIterator<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.current_block == 0 {
+            self.block_index += 1;
+            if self.block_index >= self.blocks.len() {
+                return None;
+            }
+
+            self.current_block = self.blocks[self.block_index];
+        }
+
+        let bit = self.current_block.trailing_zeros() as usize;
+        // Clears the lowest set bit.
+        self.current_block &= self.current_block - 1;
+
+        return Some(self.block_index * 64 + bit);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::ast::get_concatenated_elements;
+use crate::MatchingGroup;
+use crate::MatchingGroupElements;
+use crate::RegexAstElements;
+
+/// Information extracted from the syntax tree which allows the search to skip over text that can
+/// never be the start of a match.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Prefilter {
+    None,
+    /// Every match starts with this literal.
+    Literal(String),
+    /// Every match starts with one of these characters.
+    FirstCharacters(Vec<MatchingGroupElements>),
+}
+impl Prefilter {
+    pub(crate) fn from_ast(ast: &RegexAstElements) -> Self {
+        let (prefix, _) = get_literal_prefix(ast);
+        if !prefix.is_empty() {
+            return Prefilter::Literal(prefix);
+        }
+
+        return match get_first_characters(ast) {
+            Some((characters, false)) if !characters.is_empty() => {
+                Prefilter::FirstCharacters(characters)
+            }
+            _ => Prefilter::None,
+        };
+    }
+
+    /// Returns the first byte offset at or after `start` at which a match could begin.
+    pub(crate) fn find_candidate(&self, haystack: &str, start: usize) -> Option<usize> {
+        return match self {
+            Prefilter::None => Some(start),
+            Prefilter::Literal(literal) => haystack[start..]
+                .find(literal.as_str())
+                .map(|offset| start + offset),
+            Prefilter::FirstCharacters(characters) => haystack[start..]
+                .char_indices()
+                .find(|(_, character)| contains_character(characters, *character))
+                .map(|(offset, _)| start + offset),
+        };
+    }
+}
+
+fn contains_character(elements: &[MatchingGroupElements], character: char) -> bool {
+    for element in elements {
+        match element {
+            MatchingGroupElements::Character(element_character) => {
+                if *element_character == character {
+                    return true;
+                }
+            }
+            MatchingGroupElements::Range(start_character, end_character) => {
+                if *start_character <= character && *end_character >= character {
+                    return true;
+                }
+            }
+        }
+    }
+
+    return false;
+}
+
+/// Returns the literal every match of the element starts with and whether the element matches
+/// exactly this literal and nothing else.
+fn get_literal_prefix(ast: &RegexAstElements) -> (String, bool) {
+    return match ast {
+        RegexAstElements::Concatenation(_, _) => {
+            let mut prefix = String::new();
+
+            for element in get_concatenated_elements(ast) {
+                let (element_prefix, is_complete) = get_literal_prefix(element);
+                prefix.push_str(&element_prefix);
+
+                if !is_complete {
+                    return (prefix, false);
+                }
+            }
+
+            (prefix, true)
+        }
+        RegexAstElements::Alternation(ref left, ref right) => {
+            let (left_prefix, left_is_complete) = get_literal_prefix(left);
+            let (right_prefix, right_is_complete) = get_literal_prefix(right);
+
+            let common_prefix: String = left_prefix
+                .chars()
+                .zip(right_prefix.chars())
+                .take_while(|(left_character, right_character)| left_character == right_character)
+                .map(|(character, _)| character)
+                .collect();
+            let is_complete = left_is_complete && right_is_complete && left_prefix == right_prefix;
+
+            (common_prefix, is_complete)
+        }
+        RegexAstElements::Leaf(MatchingGroup::Character(character)) => {
+            (character.to_string(), true)
+        }
+        RegexAstElements::Leaf(MatchingGroup::Group(ref elements)) => match elements.as_slice() {
+            [MatchingGroupElements::Character(character)] => (character.to_string(), true),
+            _ => (String::new(), false),
+        },
+        _ => (String::new(), false),
+    };
+}
+
+/// Returns the characters a match of the element can start with and whether the element can
+/// match the empty string. `None` means that any character could start a match.
+fn get_first_characters(ast: &RegexAstElements) -> Option<(Vec<MatchingGroupElements>, bool)> {
+    return match ast {
+        RegexAstElements::Concatenation(_, _) => {
+            let mut characters = Vec::new();
+
+            for element in get_concatenated_elements(ast) {
+                let (mut element_characters, is_nullable) = get_first_characters(element)?;
+                characters.append(&mut element_characters);
+
+                if !is_nullable {
+                    return Some((characters, false));
+                }
+            }
+
+            Some((characters, true))
+        }
+        RegexAstElements::Alternation(ref left, ref right) => {
+            let (mut characters, left_is_nullable) = get_first_characters(left)?;
+            let (mut right_characters, right_is_nullable) = get_first_characters(right)?;
+            characters.append(&mut right_characters);
+
+            Some((characters, left_is_nullable || right_is_nullable))
+        }
+        RegexAstElements::Leaf(MatchingGroup::Character(character)) => {
+            Some((vec![MatchingGroupElements::Character(*character)], false))
+        }
+        RegexAstElements::Leaf(MatchingGroup::Group(ref elements)) => {
+            Some((elements.clone(), false))
+        }
+        RegexAstElements::Leaf(MatchingGroup::NegativeGroup(_)) => None,
+        // The accepted state is only reached after the whole regex, if it is part of the first
+        // characters the regex matches the empty string.
+        RegexAstElements::Leaf(MatchingGroup::AcceptedState) => Some((Vec::new(), true)),
+        RegexAstElements::ZeroOrMore(ref child) | RegexAstElements::ZeroOrOne(ref child) => {
+            let (characters, _) = get_first_characters(child)?;
+
+            Some((characters, true))
+        }
+        RegexAstElements::None => Some((Vec::new(), true)),
+    };
+}
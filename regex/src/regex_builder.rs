@@ -0,0 +1,91 @@
+use crate::ast::get_ast_for_concatenation_list;
+use crate::parsing::calculate_concatenation_list;
+use crate::parsing::ParsingContext;
+use crate::CompiledRegex;
+use crate::Prefilter;
+use crate::RegexAstElements;
+use crate::RegexEngine;
+use crate::RegexError;
+use crate::SpanTree;
+use crate::StateMachineBuilder;
+use std::collections::HashMap;
+
+/// Compiles regexes with additional options. Definitions added to the builder can be referenced
+/// in regexes as `{NAME}`, just like the definitions section of a lex file:
+///
+/// ```
+/// use regex::RegexBuilder;
+///
+/// let builder = RegexBuilder::new()
+///     .define("LNUM", "[0-9]+")
+///     .define("EXPONENT", "[eE][+-]?{LNUM}");
+/// let regex_engine = builder.get_regex_engine("{LNUM}{EXPONENT}").unwrap();
+///
+/// assert!(regex_engine.matches("12e-3"));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct RegexBuilder {
+    definitions: HashMap<String, String>,
+    is_verbose: bool,
+}
+impl RegexBuilder {
+    pub fn new() -> Self {
+        RegexBuilder {
+            definitions: HashMap::new(),
+            is_verbose: false,
+        }
+    }
+
+    /// Adds a definition. Definitions can reference each other in any order, as long as they do
+    /// not reference themselves.
+    pub fn define(mut self, name: &str, regex: &str) -> Self {
+        self.definitions.insert(name.to_string(), regex.to_string());
+
+        return self;
+    }
+
+    /// In verbose mode whitespace is ignored and `#` starts a comment until the end of the line.
+    /// Whitespace can still be matched in quotes, in character groups or with a `\`. Regexes
+    /// starting with `(?x)` are always verbose.
+    pub fn verbose(mut self, is_verbose: bool) -> Self {
+        self.is_verbose = is_verbose;
+
+        return self;
+    }
+
+    pub fn get_regex_syntax_tree(&self, regex: &str) -> Result<RegexAstElements, RegexError> {
+        let (ast, _) = self.get_regex_syntax_tree_with_spans(regex)?;
+
+        return Ok(ast);
+    }
+
+    /// Returns the syntax tree together with the spans of the regex its nodes were parsed from.
+    pub fn get_regex_syntax_tree_with_spans(
+        &self,
+        regex: &str,
+    ) -> Result<(RegexAstElements, SpanTree), RegexError> {
+        let mut stack = Vec::with_capacity(regex.len() + 1);
+        let mut context = ParsingContext::new(Some(&self.definitions), self.is_verbose);
+        let concatenation_list = calculate_concatenation_list(&mut stack, regex, &mut context)?;
+
+        return Ok(get_ast_for_concatenation_list(
+            &stack,
+            &concatenation_list.list,
+            regex.len(),
+        ));
+    }
+
+    pub fn get_compiled_regex(&self, regex: &str) -> Result<CompiledRegex, RegexError> {
+        let ast = self.get_regex_syntax_tree(regex)?;
+        let mut compiled_regex = StateMachineBuilder::create_compiled_regex(&ast);
+        compiled_regex.prefilter = Prefilter::from_ast(&ast);
+
+        return Ok(compiled_regex);
+    }
+
+    pub fn get_regex_engine(&self, regex: &str) -> Result<RegexEngine, RegexError> {
+        let ast = self.get_regex_syntax_tree(regex)?;
+
+        return Ok(RegexEngine::new(&ast));
+    }
+}
@@ -0,0 +1,154 @@
+use crate::character_classes::get_next_character;
+use crate::RegexAstElements;
+use crate::RegexEngine;
+use crate::StateMachineBuilder;
+
+/// Several regexes compiled into a single state machine, which finds the longest match of any of
+/// them in one pass. If several regexes match the same text, the first one wins. This is what a
+/// lexer needs to find the next token.
+///
+/// The state machine can have several entry points, each of which only matches some of the
+/// regexes. Lexers use them for start conditions.
+pub struct RegexSet {
+    regex_engine: RegexEngine,
+    regex_count: usize,
+    start_states: Vec<usize>,
+}
+impl RegexSet {
+    /// The syntax trees have to end with the accepted state, like the trees returned by
+    /// `get_regex_syntax_tree`. The set has a single entry point for all regexes.
+    pub fn new(asts: Vec<RegexAstElements>) -> Self {
+        let entry_point = (0..asts.len()).collect();
+
+        return RegexSet::with_entry_points(asts, vec![entry_point]);
+    }
+
+    /// Every entry point lists the indices of the regexes it matches. Without any entry points,
+    /// the set gets a single entry point for all regexes like `new`.
+    pub fn with_entry_points(asts: Vec<RegexAstElements>, entry_points: Vec<Vec<usize>>) -> Self {
+        let entry_points = match entry_points.is_empty() {
+            true => vec![(0..asts.len()).collect()],
+            false => entry_points,
+        };
+        let (compiled_regex, start_states) =
+            StateMachineBuilder::compile_with_entry_points(&asts, &entry_points);
+
+        return RegexSet {
+            regex_engine: compiled_regex.into_regex_engine(),
+            regex_count: asts.len(),
+            start_states,
+        };
+    }
+
+    pub fn len(&self) -> usize {
+        return self.regex_count;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.regex_count == 0;
+    }
+
+    /// Returns the index of the regex with the longest match starting at the byte offset `start`
+    /// together with the end of the match. Matches can be empty.
+    pub fn longest_match_at(&self, haystack: &str, start: usize) -> Option<(usize, usize)> {
+        return self.longest_match_from_entry_point(0, haystack, start);
+    }
+
+    /// Like `longest_match_at`, but only matches the regexes of the entry point.
+    pub fn longest_match_from_entry_point(
+        &self,
+        entry_point: usize,
+        haystack: &str,
+        start: usize,
+    ) -> Option<(usize, usize)> {
+        let mut current_state = self.get_start_state(entry_point);
+        let mut longest_match = self
+            .get_accepted_regex(current_state)
+            .map(|regex_index| (regex_index, start));
+
+        for (offset, character) in haystack[start..].char_indices() {
+            current_state = match self.get_next_state(current_state, character) {
+                Some(next_state) => next_state,
+                None => break,
+            };
+
+            if let Some(regex_index) = self.get_accepted_regex(current_state) {
+                longest_match = Some((regex_index, start + offset + character.len_utf8()));
+            }
+        }
+
+        return longest_match;
+    }
+
+    /// The state machine can also be run one character at a time, e.g. when the text is not
+    /// available as a whole.
+    pub fn get_start_state(&self, entry_point: usize) -> usize {
+        return self.start_states[entry_point];
+    }
+
+    /// The start state of the first entry point, which is the only one of sets created by `new`.
+    pub fn start_state(&self) -> usize {
+        return self.get_start_state(0);
+    }
+
+    pub fn get_next_state(&self, state: usize, character: char) -> Option<usize> {
+        return self.regex_engine.get_next_state(state, &character);
+    }
+
+    /// Returns the transitions of the state as ranges of characters together with the next
+    /// state, sorted by the ranges. This allows e.g. generating code for the state machine.
+    pub fn get_transitions(&self, state: usize) -> Vec<(char, char, usize)> {
+        let mut transitions = Vec::new();
+        let class_transitions = match self.regex_engine.transitions.get(&state) {
+            Some((class_transitions, _)) => class_transitions,
+            None => return transitions,
+        };
+
+        let mut ranges = Vec::new();
+        for (class, next_state) in class_transitions {
+            for (start, end) in self
+                .regex_engine
+                .character_classes
+                .get_character_ranges(*class)
+            {
+                ranges.push((start, end, *next_state));
+            }
+        }
+        ranges.sort();
+
+        // Different classes can lead to the same state, their neighbouring ranges are merged.
+        for (start, end, next_state) in ranges {
+            match transitions.last_mut() {
+                Some((_, last_end, last_next_state))
+                    if *last_next_state == next_state
+                        && get_next_character(*last_end) == Some(start) =>
+                {
+                    *last_end = end
+                }
+                _ => transitions.push((start, end, next_state)),
+            }
+        }
+
+        return transitions;
+    }
+
+    pub fn get_state_count(&self) -> usize {
+        return self.regex_engine.transitions.len();
+    }
+
+    /// Returns the number of transitions of all states, where every character class of a state
+    /// counts once. Together with `get_state_count`, this is the size of the transition table.
+    pub fn get_transition_count(&self) -> usize {
+        return self
+            .regex_engine
+            .transitions
+            .values()
+            .map(|(class_transitions, _)| class_transitions.len())
+            .sum();
+    }
+
+    /// Returns the index of the regex which is accepted in the state.
+    pub fn get_accepted_regex(&self, state: usize) -> Option<usize> {
+        return self.regex_engine.get_accepted_regex(state);
+    }
+}
@@ -0,0 +1,234 @@
+use crate::RegexEngine;
+
+/// A match within a searched text. `start` and `end` are byte offsets, the match is
+/// `&haystack[start..end]`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Match {
+    pub start: usize,
+    pub end: usize,
+}
+impl Match {
+    pub fn new(start: usize, end: usize) -> Self {
+        Match { start, end }
+    }
+
+    pub fn as_str<'a>(&self, haystack: &'a str) -> &'a str {
+        return &haystack[self.start..self.end];
+    }
+}
+
+impl RegexEngine<char> {
+    /// Returns whether the regex matches any part of the text. The search stops where the first
+    /// match ends.
+    pub fn is_match(&self, haystack: &str) -> bool {
+        let candidate = match self.prefilter.find_candidate(haystack, 0) {
+            Some(candidate) => candidate,
+            None => return false,
+        };
+
+        return match self.unanchored_engine {
+            Some(ref unanchored_engine) => unanchored_engine
+                .get_first_match_end(haystack, candidate)
+                .is_some(),
+            None => self.find_at(haystack, candidate).is_some(),
+        };
+    }
+
+    /// Returns the leftmost match in the text. If several matches start at the same position,
+    /// the longest one is returned.
+    pub fn find(&self, haystack: &str) -> Option<Match> {
+        return self.find_at(haystack, 0);
+    }
+
+    /// Returns the leftmost match which starts at or after the byte offset `start`.
+    ///
+    /// A single forward pass finds the end of the leftmost longest match, the reverse state
+    /// machine then runs back from that end to where the match starts. The forward pass reads
+    /// the text up to where the last match which could start before the found one fails, the
+    /// reverse pass only reads the match itself.
+    pub fn find_at(&self, haystack: &str, start: usize) -> Option<Match> {
+        let candidate = self.prefilter.find_candidate(haystack, start)?;
+        let end = self.get_leftmost_longest_end(haystack, candidate)?;
+
+        return match self.reverse_engine {
+            Some(ref reverse_engine) => Some(Match::new(
+                reverse_engine.get_leftmost_start(haystack, candidate, end),
+                end,
+            )),
+            // Without the reverse state machine every position up to the end is tried instead.
+            None => self.find_forward(haystack, candidate, end),
+        };
+    }
+
+    /// Returns an iterator over all non overlapping matches in the text.
+    pub fn find_iter<'r, 'h>(&'r self, haystack: &'h str) -> FindMatches<'r, 'h> {
+        FindMatches {
+            regex_engine: self,
+            haystack,
+            position: Some(0),
+        }
+    }
+
+    /// Runs the unanchored state machine from the byte offset `start` and returns the offset at
+    /// which the first match ends.
+    fn get_first_match_end(&self, haystack: &str, start: usize) -> Option<usize> {
+        let mut current_state = 0;
+        if self.is_accepted_state(current_state) {
+            return Some(start);
+        }
+
+        for (offset, character) in haystack[start..].char_indices() {
+            // Transitions which would end all matches are left out of the unanchored state
+            // machine, they lead back to the start state.
+            current_state = self.get_next_state(current_state, &character).unwrap_or(0);
+            if self.is_accepted_state(current_state) {
+                return Some(start + offset + character.len_utf8());
+            }
+        }
+
+        return None;
+    }
+
+    /// Runs the reverse state machine from the byte offset `end` back towards `start` and returns
+    /// the leftmost offset at which a match ending at `end` starts. There has to be such a match.
+    fn get_leftmost_start(&self, haystack: &str, start: usize, end: usize) -> usize {
+        let mut current_state = 0;
+        let mut leftmost_start = end;
+
+        for (offset, character) in haystack[start..end].char_indices().rev() {
+            current_state = match self.get_next_state(current_state, &character) {
+                Some(next_state) => next_state,
+                None => break,
+            };
+            if self.is_accepted_state(current_state) {
+                leftmost_start = start + offset;
+            }
+        }
+
+        return leftmost_start;
+    }
+
+    /// Runs the state machine from every position after the byte offset `start` at once and
+    /// returns the end of the leftmost longest match.
+    ///
+    /// Every position starts a thread in the start state. The threads are kept in the order of
+    /// their starts and threads which reach the same state are merged into the earlier one, so
+    /// there are never more threads than states. Once a thread accepts, no more threads are
+    /// started and all threads which started after it are dropped, only earlier threads can
+    /// still find a match which starts further left.
+    fn get_leftmost_longest_end(&self, haystack: &str, start: usize) -> Option<usize> {
+        let mut threads: Vec<usize> = Vec::new();
+        let mut match_end = None;
+        let positions = haystack[start..]
+            .char_indices()
+            .map(|(offset, character)| (start + offset, Some(character)))
+            .chain(std::iter::once((haystack.len(), None)));
+
+        for (position, character) in positions {
+            if match_end.is_none() && !threads.contains(&0) {
+                threads.push(0);
+            }
+            if let Some(index) = threads
+                .iter()
+                .position(|state| self.is_accepted_state(*state))
+            {
+                match_end = Some(position);
+                threads.truncate(index + 1);
+            }
+
+            let character = match character {
+                Some(character) => character,
+                None => break,
+            };
+            let mut next_threads: Vec<usize> = Vec::with_capacity(threads.len());
+            for state in &threads {
+                if let Some(next_state) = self.get_next_state(*state, &character) {
+                    if !next_threads.contains(&next_state) {
+                        next_threads.push(next_state);
+                    }
+                }
+            }
+            threads = next_threads;
+
+            if threads.is_empty() && match_end.is_some() {
+                break;
+            }
+        }
+
+        return match_end;
+    }
+
+    /// Tries the forward state machine at every position from `start` to `last_start` and returns
+    /// the longest match at the first position which has one.
+    fn find_forward(&self, haystack: &str, start: usize, last_start: usize) -> Option<Match> {
+        let starts = haystack[start..last_start]
+            .char_indices()
+            .map(|(offset, _)| start + offset)
+            .chain(std::iter::once(last_start));
+
+        for match_start in starts {
+            if let Some(end) = self.get_longest_match_end(haystack, match_start) {
+                return Some(Match::new(match_start, end));
+            }
+        }
+
+        return None;
+    }
+
+    /// Runs the state machine starting at the byte offset `start` and returns the end of the
+    /// longest match, if there is any.
+    fn get_longest_match_end(&self, haystack: &str, start: usize) -> Option<usize> {
+        let mut current_state = 0;
+        let mut match_end = None;
+
+        for (offset, character) in haystack[start..].char_indices() {
+            if self.is_accepted_state(current_state) {
+                match_end = Some(start + offset);
+            }
+
+            match self.get_next_state(current_state, &character) {
+                Some(next_state) => current_state = next_state,
+                None => return match_end,
+            }
+        }
+
+        if self.is_accepted_state(current_state) {
+            return Some(haystack.len());
+        }
+
+        return match_end;
+    }
+}
+
+pub struct FindMatches<'r, 'h> {
+    regex_engine: &'r RegexEngine,
+    haystack: &'h str,
+    position: Option<usize>,
+}
+impl<'r, 'h> Iterator for FindMatches<'r, 'h> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Match> {
+        let position = self.position?;
+        let found_match = match self.regex_engine.find_at(self.haystack, position) {
+            Some(found_match) => found_match,
+            None => {
+                self.position = None;
+                return None;
+            }
+        };
+
+        if found_match.start == found_match.end {
+            // Empty matches would be found over and over again, therefore the search continues
+            // after the next character.
+            self.position = self.haystack[found_match.end..]
+                .chars()
+                .next()
+                .map(|character| found_match.end + character.len_utf8());
+        } else {
+            self.position = Some(found_match.end);
+        }
+
+        return Some(found_match);
+    }
+}
@@ -0,0 +1,138 @@
+use crate::MatchingGroup;
+use crate::RegexAstElements;
+use std::fmt;
+
+/// A part of a regex, `start` and `end` are byte offsets, the part is `&regex[start..end]`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    /// Returns the smallest span covering both spans.
+    pub fn union(self, other: Span) -> Self {
+        return Span::new(self.start.min(other.start), self.end.max(other.end));
+    }
+
+    pub fn contains(&self, offset: usize) -> bool {
+        return self.start <= offset && offset < self.end;
+    }
+
+    pub fn as_str<'a>(&self, regex: &'a str) -> &'a str {
+        return &regex[self.start..self.end];
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        return write!(formatter, "{}..{}", self.start, self.end);
+    }
+}
+
+/// The spans of a syntax tree. The tree has the same shape as the syntax tree it was parsed
+/// with, the children are in the same order as the children of the syntax tree node.
+///
+/// Elements of a definition all point at the `{NAME}` reference, since they were not parsed from
+/// the regex itself. The accepted state has an empty span at the end of the regex.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpanTree {
+    pub span: Span,
+    pub children: Vec<SpanTree>,
+}
+impl SpanTree {
+    pub fn new(span: Span, children: Vec<SpanTree>) -> Self {
+        SpanTree { span, children }
+    }
+
+    /// Returns the spans of all nodes containing the offset, from the root to the innermost
+    /// node. This allows e.g. an editor to highlight the element under the cursor.
+    pub fn get_spans_at(&self, offset: usize) -> Vec<Span> {
+        let mut spans = Vec::new();
+        let mut current = Some(self);
+
+        while let Some(tree) = current {
+            if !tree.span.contains(offset) {
+                break;
+            }
+
+            spans.push(tree.span);
+            current = tree
+                .children
+                .iter()
+                .find(|child| child.span.contains(offset));
+        }
+
+        return spans;
+    }
+}
+
+/// Returns a graph of the syntax tree in the DOT language of Graphviz. Every node is labeled with
+/// the part of the regex it was parsed from.
+pub fn get_syntax_tree_dot_graph(ast: &RegexAstElements, spans: &SpanTree, regex: &str) -> String {
+    let mut graph = String::from("digraph syntax_tree {\n    node [shape=box];\n");
+    let mut node_count = 0;
+    // Pairs of the parent node id, the ast node and its spans.
+    let mut stack = vec![(None, ast, spans)];
+
+    while let Some((parent_id, ast, spans)) = stack.pop() {
+        let node_id = node_count;
+        node_count += 1;
+
+        let (name, children): (String, Vec<&RegexAstElements>) = match ast {
+            RegexAstElements::Alternation(ref left, ref right) => {
+                (String::from("Alternation"), vec![left, right])
+            }
+            RegexAstElements::Concatenation(ref left, ref right) => {
+                (String::from("Concatenation"), vec![left, right])
+            }
+            RegexAstElements::Leaf(MatchingGroup::AcceptedState) => {
+                (String::from("AcceptedState"), Vec::new())
+            }
+            RegexAstElements::Leaf(_) => (String::from("Leaf"), Vec::new()),
+            RegexAstElements::None => (String::from("None"), Vec::new()),
+            RegexAstElements::ZeroOrMore(ref child) => (String::from("ZeroOrMore"), vec![child]),
+            RegexAstElements::ZeroOrOne(ref child) => (String::from("ZeroOrOne"), vec![child]),
+        };
+
+        graph.push_str(&format!(
+            "    node{} [label=\"{}\\n{} {}\"];\n",
+            node_id,
+            name,
+            spans.span,
+            escape_dot_label(spans.span.as_str(regex))
+        ));
+        if let Some(parent_id) = parent_id {
+            graph.push_str(&format!("    node{} -> node{};\n", parent_id, node_id));
+        }
+
+        // Children are pushed in reverse, so that the left child gets the lower id.
+        for (child, child_spans) in children.into_iter().zip(&spans.children).rev() {
+            stack.push((Some(node_id), child, child_spans));
+        }
+    }
+
+    graph.push_str("}\n");
+
+    return graph;
+}
+
+fn escape_dot_label(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for character in text.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\\\n"),
+            '\r' => escaped.push_str("\\\\r"),
+            '\t' => escaped.push_str("\\\\t"),
+            _ => escaped.push(character),
+        }
+    }
+
+    return escaped;
+}
@@ -1,18 +1,17 @@
+use crate::ast::get_reversed_ast;
+use crate::CharacterClasses;
+use crate::CompiledPosition;
+use crate::CompiledRegex;
+use crate::CompiledState;
+use crate::ElementType;
 use crate::MatchingGroup;
+use crate::PositionSet;
+use crate::Prefilter;
 use crate::RegexAstElements;
-use crate::RegexEngine;
-use crate::StateMachine;
-use crate::TransitionForMatchingGroup;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
-
-#[derive(Debug, PartialEq)]
-enum ElementType {
-    Alternation,
-    Concatenation,
-    Leaf,
-    ZeroOrMore,
-    ZeroOrOne,
-}
+use std::collections::VecDeque;
+use std::hash::Hash;
 
 #[derive(Debug, PartialEq)]
 struct StateCalculations {
@@ -21,9 +20,9 @@ struct StateCalculations {
     pub(self) left_child_index: Option<usize>,
     pub(self) right_child_index: Option<usize>,
     pub(self) is_nullable: bool,
-    pub(self) first_pos: Vec<usize>,
-    pub(self) last_pos: Vec<usize>,
-    pub(self) follow_pos: Vec<usize>,
+    pub(self) first_pos: PositionSet,
+    pub(self) last_pos: PositionSet,
+    pub(self) follow_pos: PositionSet,
     pub(self) element_type: ElementType,
     pub(self) matching_group_index: Option<usize>,
     pub(self) accepted: bool,
@@ -36,8 +35,8 @@ impl StateCalculations {
         element_type: ElementType,
         matching_group_index: Option<usize>,
         is_nullable: bool,
-        first_pos: Vec<usize>,
-        last_pos: Vec<usize>,
+        first_pos: PositionSet,
+        last_pos: PositionSet,
         accepted: bool,
     ) -> StateCalculations {
         StateCalculations {
@@ -48,7 +47,7 @@ impl StateCalculations {
             is_nullable,
             first_pos,
             last_pos,
-            follow_pos: Vec::new(),
+            follow_pos: PositionSet::new(),
             element_type,
             matching_group_index,
             accepted,
@@ -56,48 +55,139 @@ impl StateCalculations {
     }
 }
 
-pub(super) struct StateMachineBuilder {
+pub(super) struct StateMachineBuilder<T> {
     stack: Vec<StateCalculations>,
-    matching_groups: Vec<MatchingGroup>,
+    matching_groups: Vec<MatchingGroup<T>>,
+    matching_group_indices: HashMap<MatchingGroup<T>, usize>,
+    is_unanchored: bool,
 }
-impl StateMachineBuilder {
-    pub(super) fn create_regex_engine(ast: &RegexAstElements) -> RegexEngine {
-        println!("{:#?}", ast);
+impl<T: Eq + Hash + Clone + PartialOrd> StateMachineBuilder<T> {
+    pub(super) fn create_compiled_regex(ast: &RegexAstElements<T>) -> CompiledRegex<T> {
+        let mut compiled_regex = StateMachineBuilder::compile(ast, false);
+
+        // The unanchored state machine stops at the first match end for `is_match`. Searches run
+        // the reverse state machine back from the end of the leftmost match to where it begins.
+        compiled_regex.unanchored = Some(Box::new(StateMachineBuilder::compile(ast, true)));
+        let reversed_ast = get_reversed_ast(ast);
+        compiled_regex.reverse = Some(Box::new(StateMachineBuilder::compile(&reversed_ast, false)));
+
+        return compiled_regex;
+    }
+
+    fn compile(ast: &RegexAstElements<T>, is_unanchored: bool) -> CompiledRegex<T> {
+        let mut builder = StateMachineBuilder::new(is_unanchored);
+        let root_index = builder.create_calculation_stack_for_element(ast);
+        builder.caclulate_follow_pos_for_stack();
+        let start_positions = builder.stack[root_index].first_pos.clone();
+
+        let (compiled_regex, _) = builder.convert_to_compiled_regex(&[start_positions]);
 
-        let mut builder = StateMachineBuilder::new();
-        builder.create_calculation_stack_for_element(ast);
+        return compiled_regex;
+    }
+
+    /// Compiles several regexes into one state machine with several start states. Every entry
+    /// point lists the regexes which can be matched from its start state. The start states are
+    /// returned in the order of the entry points.
+    pub(super) fn compile_with_entry_points(
+        asts: &[RegexAstElements<T>],
+        entry_points: &[Vec<usize>],
+    ) -> (CompiledRegex<T>, Vec<usize>) {
+        let mut builder = StateMachineBuilder::new(false);
+        let root_indices: Vec<usize> = asts
+            .iter()
+            .map(|ast| builder.create_calculation_stack_for_element(ast))
+            .collect();
         builder.caclulate_follow_pos_for_stack();
-        println!("{:#?}", builder.stack);
 
-        return builder.convert_to_regex_engine();
+        let start_positions: Vec<PositionSet> = entry_points
+            .iter()
+            .map(|regex_indices| {
+                let mut start_positions = PositionSet::new();
+                for regex_index in regex_indices {
+                    start_positions
+                        .union_with(&builder.stack[root_indices[*regex_index]].first_pos);
+                }
+
+                start_positions
+            })
+            .collect();
+
+        return builder.convert_to_compiled_regex(&start_positions);
     }
 
-    fn new() -> StateMachineBuilder {
+    fn new(is_unanchored: bool) -> StateMachineBuilder<T> {
         StateMachineBuilder {
             stack: Vec::with_capacity(100),
             matching_groups: Vec::with_capacity(100),
+            matching_group_indices: HashMap::with_capacity(100),
+            is_unanchored,
         }
     }
 
-    fn add_matching_group(&mut self, matching_group: &MatchingGroup) -> usize {
-        let index = self
-            .matching_groups
-            .iter()
-            .position(|group| group == matching_group);
-
-        match index {
-            Some(index) => return index,
+    fn add_matching_group(&mut self, matching_group: &MatchingGroup<T>) -> usize {
+        match self.matching_group_indices.get(matching_group) {
+            Some(index) => return *index,
             None => {
+                let index = self.matching_groups.len();
                 self.matching_groups.push(matching_group.clone());
+                self.matching_group_indices
+                    .insert(matching_group.clone(), index);
 
-                return self.matching_groups.len() - 1;
+                return index;
             }
         }
     }
 
-    fn create_calculation_stack_for_element(&mut self, ast: &RegexAstElements) -> usize {
-        let mut left_child_index = None;
-        let mut right_child_index = None;
+    fn create_calculation_stack_for_element(&mut self, ast: &RegexAstElements<T>) -> usize {
+        // Long patterns result in very deep trees, therefore the tree is walked without recursion.
+        // Every element is visited twice, the second time all of its children are calculated.
+        let mut elements = vec![(ast, false)];
+        let mut child_indices = Vec::new();
+
+        while let Some((element, children_are_calculated)) = elements.pop() {
+            if !children_are_calculated {
+                elements.push((element, true));
+
+                match element {
+                    RegexAstElements::Alternation(ref left, ref right)
+                    | RegexAstElements::Concatenation(ref left, ref right) => {
+                        elements.push((right, false));
+                        elements.push((left, false));
+                    }
+                    RegexAstElements::ZeroOrMore(ref child)
+                    | RegexAstElements::ZeroOrOne(ref child) => elements.push((child, false)),
+                    _ => {}
+                }
+
+                continue;
+            }
+
+            let index = match element {
+                RegexAstElements::Alternation(_, _) | RegexAstElements::Concatenation(_, _) => {
+                    let right_index = child_indices.pop();
+                    let left_index = child_indices.pop();
+
+                    self.add_calculation_for_element(element, left_index, right_index)
+                }
+                RegexAstElements::ZeroOrMore(_) | RegexAstElements::ZeroOrOne(_) => {
+                    let child_index = child_indices.pop();
+
+                    self.add_calculation_for_element(element, child_index, None)
+                }
+                _ => self.add_calculation_for_element(element, None, None),
+            };
+            child_indices.push(index);
+        }
+
+        return child_indices.pop().unwrap();
+    }
+
+    fn add_calculation_for_element(
+        &mut self,
+        ast: &RegexAstElements<T>,
+        left_child_index: Option<usize>,
+        right_child_index: Option<usize>,
+    ) -> usize {
         let element_type;
         let mut matching_group_index = None;
         let current_index;
@@ -107,49 +197,43 @@ impl StateMachineBuilder {
         let is_accepted_state;
 
         match ast {
-            RegexAstElements::Alternation(ref left, ref right) => {
+            RegexAstElements::Alternation(_, _) => {
                 element_type = ElementType::Alternation;
 
-                let left_index = self.create_calculation_stack_for_element(left);
-                let right_index = self.create_calculation_stack_for_element(right);
+                let left_index = left_child_index.unwrap();
+                let right_index = right_child_index.unwrap();
 
                 current_index = self.stack.len();
                 self.stack[left_index].parent_index = Some(current_index);
                 self.stack[right_index].parent_index = Some(current_index);
 
-                left_child_index = Some(left_index);
-                right_child_index = Some(right_index);
-
                 is_nullable =
                     self.stack[left_index].is_nullable || self.stack[right_index].is_nullable;
                 first_pos = self.stack[left_index].first_pos.clone();
-                first_pos.append(&mut self.stack[right_index].first_pos.clone());
+                first_pos.union_with(&self.stack[right_index].first_pos);
                 last_pos = self.stack[right_index].last_pos.clone();
-                last_pos.append(&mut self.stack[left_index].last_pos.clone());
+                last_pos.union_with(&self.stack[left_index].last_pos);
                 is_accepted_state = false;
             }
-            RegexAstElements::Concatenation(ref left, ref right) => {
+            RegexAstElements::Concatenation(_, _) => {
                 element_type = ElementType::Concatenation;
 
-                let left_index = self.create_calculation_stack_for_element(left);
-                let right_index = self.create_calculation_stack_for_element(right);
+                let left_index = left_child_index.unwrap();
+                let right_index = right_child_index.unwrap();
 
                 current_index = self.stack.len();
                 self.stack[left_index].parent_index = Some(current_index);
                 self.stack[right_index].parent_index = Some(current_index);
 
-                left_child_index = Some(left_index);
-                right_child_index = Some(right_index);
-
                 is_nullable =
                     self.stack[left_index].is_nullable && self.stack[right_index].is_nullable;
                 first_pos = self.stack[left_index].first_pos.clone();
                 if self.stack[left_index].is_nullable {
-                    first_pos.append(&mut self.stack[right_index].first_pos.clone());
+                    first_pos.union_with(&self.stack[right_index].first_pos);
                 }
                 last_pos = self.stack[right_index].last_pos.clone();
                 if self.stack[right_index].is_nullable {
-                    last_pos.append(&mut self.stack[left_index].last_pos.clone());
+                    last_pos.union_with(&self.stack[left_index].last_pos);
                 }
                 is_accepted_state = false;
             }
@@ -159,41 +243,45 @@ impl StateMachineBuilder {
 
                 current_index = self.stack.len();
                 is_nullable = false;
-                first_pos = vec![current_index];
-                last_pos = vec![current_index];
+                first_pos = PositionSet::from_position(current_index);
+                last_pos = PositionSet::from_position(current_index);
                 is_accepted_state = group == &MatchingGroup::AcceptedState;
             }
-            RegexAstElements::ZeroOrMore(ref child) => {
+            RegexAstElements::ZeroOrMore(_) => {
                 element_type = ElementType::ZeroOrMore;
 
-                let child_index = self.create_calculation_stack_for_element(child);
+                let child_index = left_child_index.unwrap();
 
                 current_index = self.stack.len();
                 self.stack[child_index].parent_index = Some(current_index);
 
-                left_child_index = Some(child_index);
-
                 is_nullable = true;
                 first_pos = self.stack[child_index].first_pos.clone();
                 last_pos = self.stack[child_index].last_pos.clone();
                 is_accepted_state = false;
             }
-            RegexAstElements::ZeroOrOne(ref child) => {
+            RegexAstElements::ZeroOrOne(_) => {
                 element_type = ElementType::ZeroOrOne;
 
-                let child_index = self.create_calculation_stack_for_element(child);
+                let child_index = left_child_index.unwrap();
 
                 current_index = self.stack.len();
                 self.stack[child_index].parent_index = Some(current_index);
 
-                left_child_index = Some(child_index);
-
                 is_nullable = true;
                 first_pos = self.stack[child_index].first_pos.clone();
                 last_pos = self.stack[child_index].last_pos.clone();
                 is_accepted_state = false;
             }
-            _ => panic!("Unknown ast element"),
+            RegexAstElements::None => {
+                element_type = ElementType::Empty;
+
+                current_index = self.stack.len();
+                is_nullable = true;
+                first_pos = PositionSet::new();
+                last_pos = PositionSet::new();
+                is_accepted_state = false;
+            }
         }
 
         self.stack.push(StateCalculations::new(
@@ -213,127 +301,128 @@ impl StateMachineBuilder {
 
     fn caclulate_follow_pos_for_stack(&mut self) {
         for i in 0..self.stack.len() {
-            match self.stack[i].element_type {
-                ElementType::Concatenation => {
-                    let last_pos_left = &self.stack[self.stack[i].left_child_index.unwrap()]
+            let (last_pos, first_pos) = match self.stack[i].element_type {
+                ElementType::Concatenation => (
+                    self.stack[self.stack[i].left_child_index.unwrap()]
                         .last_pos
-                        .clone();
-                    let first_pos_right = &self.stack[self.stack[i].right_child_index.unwrap()]
+                        .clone(),
+                    self.stack[self.stack[i].right_child_index.unwrap()]
                         .first_pos
-                        .clone();
-
-                    for position in last_pos_left {
-                        self.stack[*position]
-                            .follow_pos
-                            .append(&mut first_pos_right.clone());
-                    }
-                }
+                        .clone(),
+                ),
                 ElementType::ZeroOrMore => {
-                    let last_pos_child = &self.stack[self.stack[i].left_child_index.unwrap()]
-                        .last_pos
-                        .clone();
-                    let first_pos_child = &self.stack[self.stack[i].left_child_index.unwrap()]
-                        .first_pos
-                        .clone();
+                    let child = &self.stack[self.stack[i].left_child_index.unwrap()];
 
-                    for position in last_pos_child {
-                        self.stack[*position]
-                            .follow_pos
-                            .append(&mut first_pos_child.clone())
-                    }
+                    (child.last_pos.clone(), child.first_pos.clone())
                 }
-                _ => {}
+                _ => continue,
+            };
+
+            for position in last_pos.iter() {
+                self.stack[position].follow_pos.union_with(&first_pos);
             }
         }
     }
 
-    fn convert_to_regex_engine(self) -> RegexEngine {
-        let mut deterministic_transitions: StateMachine = HashMap::new();
-        let mut deterministic_states = Vec::with_capacity(100);
-        let tree_root = &self.stack[self.stack.len() - 1];
-        deterministic_states.push(DeterministicState::new(0, tree_root.first_pos.clone()));
-
-        while let Some(unmarked_state_index) =
-            self.get_next_unmarked_state_index(&deterministic_states)
-        {
-            deterministic_states[unmarked_state_index].is_marked = true;
-
-            for matching_group_index in 0..self.matching_groups.len() {
-                let mut transition = Vec::new();
-
-                for position in &deterministic_states[unmarked_state_index].non_deterministic_states
-                {
-                    let non_deterministic_state = &self.stack[position.clone()];
-                    if non_deterministic_state.matching_group_index == Some(matching_group_index) {
-                        transition.append(&mut non_deterministic_state.follow_pos.clone());
-                    }
-                }
-
-                if transition.len() != 0 {
-                    let state_id = self.get_state_id(&mut deterministic_states, transition);
-
-                    match deterministic_transitions.get_mut(&unmarked_state_index) {
-                        Some((transitions, _)) => {
-                            transitions.insert(matching_group_index, state_id);
-                        }
-                        None => {
-                            let mut transition_map: TransitionForMatchingGroup =
-                                HashMap::with_capacity(10);
-                            transition_map.insert(matching_group_index, state_id);
-
-                            let is_accepted = self.contains_accepting_states(
-                                &deterministic_states[unmarked_state_index]
-                                    .non_deterministic_states,
-                            );
-                            deterministic_transitions
-                                .insert(unmarked_state_index, (transition_map, is_accepted));
-                        }
-                    }
-                }
+    fn convert_to_compiled_regex(
+        self,
+        start_position_sets: &[PositionSet],
+    ) -> (CompiledRegex<T>, Vec<usize>) {
+        let mut deterministic_states = DeterministicStates::new();
+        let mut compiled_states = Vec::with_capacity(100);
+        // The first start state always gets the id 0.
+        let start_states: Vec<usize> = start_position_sets
+            .iter()
+            .map(|start_positions| deterministic_states.get_state_id(start_positions))
+            .collect();
+        let start_positions = &start_position_sets[0];
+
+        let character_classes = CharacterClasses::new(&self.matching_groups);
+        let mut matching_group_classes = vec![Vec::new(); self.matching_groups.len()];
+        for class in 0..character_classes.len() {
+            for matching_group_index in character_classes.get_matching_groups(class) {
+                matching_group_classes[*matching_group_index].push(class);
             }
         }
 
-        for index in 0..deterministic_states.len() {
-            match deterministic_transitions.get(&index) {
-                None => {
-                    let is_accepted = self.contains_accepting_states(
-                        &deterministic_states[index].non_deterministic_states,
-                    );
-                    deterministic_transitions.insert(index, (HashMap::new(), is_accepted));
+        while let Some(unmarked_state_index) = deterministic_states.unmarked_states.pop_front() {
+            let positions = &deterministic_states.states[unmarked_state_index];
+
+            // Collecting the follow positions per character class in one pass over the positions
+            // avoids looping over all positions for every class.
+            let mut transition_positions: BTreeMap<usize, PositionSet> = BTreeMap::new();
+            for position in positions.iter() {
+                let non_deterministic_state = &self.stack[position];
+                if let Some(matching_group_index) = non_deterministic_state.matching_group_index {
+                    for class in &matching_group_classes[matching_group_index] {
+                        transition_positions
+                            .entry(*class)
+                            .or_default()
+                            .union_with(&non_deterministic_state.follow_pos);
+                    }
                 }
-                _ => {}
             }
-        }
 
-        println!("{:#?}", deterministic_transitions);
-
-        return RegexEngine::new_with_values(self.matching_groups, deterministic_transitions);
-    }
+            let is_accepted = self.contains_accepting_states(positions);
+            let positions = positions.to_vec();
+
+            let mut transitions = Vec::with_capacity(transition_positions.len());
+            for (class, mut transition) in transition_positions {
+                if !transition.is_empty() {
+                    // An unanchored state machine can start a new match at every character.
+                    // Transitions which would end all matches lead back to the start state and
+                    // are therefore left out.
+                    if self.is_unanchored {
+                        transition.union_with(start_positions);
+                    }
 
-    fn get_next_unmarked_state_index(&self, states: &Vec<DeterministicState>) -> Option<usize> {
-        for index in 0..states.len() {
-            if states[index].is_marked == false {
-                return Some(index);
+                    let state_id = deterministic_states.get_state_id(&transition);
+                    transitions.push((class, state_id));
+                }
             }
-        }
-
-        return None;
-    }
 
-    fn get_state_id(&self, states: &mut Vec<DeterministicState>, transition: Vec<usize>) -> usize {
-        for i in 0..states.len() {
-            if states[i].non_deterministic_states == transition {
-                return i;
-            }
+            compiled_states.push(CompiledState {
+                index: unmarked_state_index,
+                positions,
+                transitions,
+                is_accepted,
+            });
         }
 
-        states.push(DeterministicState::new(states.len(), transition));
-        return states.len() - 1;
+        let positions = self
+            .stack
+            .into_iter()
+            .map(|state| CompiledPosition {
+                index: state.index,
+                element_type: state.element_type,
+                parent_index: state.parent_index,
+                left_child_index: state.left_child_index,
+                right_child_index: state.right_child_index,
+                matching_group_index: state.matching_group_index,
+                is_nullable: state.is_nullable,
+                first_pos: state.first_pos.to_vec(),
+                last_pos: state.last_pos.to_vec(),
+                follow_pos: state.follow_pos.to_vec(),
+                accepted: state.accepted,
+            })
+            .collect();
+
+        let compiled_regex = CompiledRegex {
+            positions,
+            states: compiled_states,
+            matching_groups: self.matching_groups,
+            character_classes,
+            prefilter: Prefilter::None,
+            unanchored: None,
+            reverse: None,
+        };
+
+        return (compiled_regex, start_states);
     }
 
-    fn contains_accepting_states(&self, non_deterministic_states: &Vec<usize>) -> bool {
-        for state_id in non_deterministic_states {
-            if self.stack[*state_id].accepted {
+    fn contains_accepting_states(&self, non_deterministic_states: &PositionSet) -> bool {
+        for state_id in non_deterministic_states.iter() {
+            if self.stack[state_id].accepted {
                 return true;
             }
         }
@@ -342,17 +431,34 @@ impl StateMachineBuilder {
     }
 }
 
-struct DeterministicState {
-    pub(self) index: usize,
-    pub(self) non_deterministic_states: Vec<usize>,
-    pub(self) is_marked: bool,
+/// The states of the deterministic state machine during the subset construction. Every state is
+/// identified by the set of positions it was created from.
+struct DeterministicStates {
+    pub(self) states: Vec<PositionSet>,
+    pub(self) state_ids: HashMap<PositionSet, usize>,
+    pub(self) unmarked_states: VecDeque<usize>,
 }
-impl DeterministicState {
-    pub fn new(index: usize, non_deterministic_states: Vec<usize>) -> Self {
-        DeterministicState {
-            index,
-            non_deterministic_states,
-            is_marked: false,
+impl DeterministicStates {
+    pub fn new() -> Self {
+        DeterministicStates {
+            states: Vec::with_capacity(100),
+            state_ids: HashMap::with_capacity(100),
+            unmarked_states: VecDeque::new(),
         }
     }
+
+    /// Returns the id of the state for the given positions. Unknown states are created and
+    /// queued to calculate their transitions.
+    pub fn get_state_id(&mut self, positions: &PositionSet) -> usize {
+        if let Some(state_id) = self.state_ids.get(positions) {
+            return *state_id;
+        }
+
+        let state_id = self.states.len();
+        self.states.push(positions.clone());
+        self.state_ids.insert(positions.clone(), state_id);
+        self.unmarked_states.push_back(state_id);
+
+        return state_id;
+    }
 }
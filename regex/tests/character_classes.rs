@@ -0,0 +1,87 @@
+use regex::get_compiled_regex;
+use regex::get_regex_engine;
+use regex::get_regex_engine_for_ast;
+use regex::Match;
+use regex::MatchingGroup;
+use regex::MatchingGroupElements;
+use regex::RegexAstElements;
+
+#[test]
+fn match_overlapping_characters_and_ranges() {
+    let regex_engine = get_regex_engine("[a-j]*abc");
+
+    assert_eq!(true, regex_engine.matches("abc"));
+    assert_eq!(true, regex_engine.matches("cabc"));
+    assert_eq!(true, regex_engine.matches("jaabc"));
+    assert_eq!(false, regex_engine.matches("xabc"));
+}
+
+#[test]
+fn match_overlapping_negative_groups() {
+    let regex_engine = get_regex_engine("[^a-c]x");
+
+    assert_eq!(true, regex_engine.matches("dx"));
+    assert_eq!(true, regex_engine.matches("xx"));
+    assert_eq!(false, regex_engine.matches("ax"));
+}
+
+#[test]
+fn match_keyword_or_identifier() {
+    let regex_engine = get_regex_engine("if|[a-z]+");
+
+    assert_eq!(true, regex_engine.matches("if"));
+    assert_eq!(true, regex_engine.matches("i"));
+    assert_eq!(true, regex_engine.matches("iff"));
+    assert_eq!(true, regex_engine.matches("then"));
+
+    let haystack = "x = fn foo";
+    let found_match = get_regex_engine("fn [a-z_]+").find(haystack);
+    assert_eq!(Some(Match::new(4, 10)), found_match);
+}
+
+#[test]
+fn split_overlapping_groups_into_classes() {
+    let compiled_regex = get_compiled_regex("a|[a-c]");
+    let character_classes = &compiled_regex.character_classes;
+
+    assert_eq!(2, character_classes.len());
+    assert_eq!(Some(0), character_classes.get_class(&'a'));
+    assert_eq!(Some(1), character_classes.get_class(&'b'));
+    assert_eq!(Some(1), character_classes.get_class(&'c'));
+    assert_eq!(None, character_classes.get_class(&'d'));
+    assert_eq!(&[0, 1], character_classes.get_matching_groups(0));
+    assert_eq!(&[1], character_classes.get_matching_groups(1));
+}
+
+#[test]
+fn match_overlapping_ranges_of_other_symbols() {
+    let ast = RegexAstElements::Leaf(MatchingGroup::Group(vec![MatchingGroupElements::Range(
+        1, 5,
+    )]))
+    .then(RegexAstElements::symbol(3));
+    let regex_engine = get_regex_engine_for_ast(ast);
+
+    assert_eq!(true, regex_engine.matches_symbols(&[3, 3]));
+    assert_eq!(true, regex_engine.matches_symbols(&[5, 3]));
+    assert_eq!(false, regex_engine.matches_symbols(&[3, 4]));
+}
+
+#[test]
+fn get_the_characters_of_classes_as_ranges() {
+    let compiled_regex = get_compiled_regex("[a-z]|b|[^0-9]");
+    let character_classes = &compiled_regex.character_classes;
+
+    let class_of = |character| character_classes.get_class(&character).unwrap();
+    assert_eq!(
+        vec![('\0', '/'), (':', '`'), ('{', char::MAX)],
+        character_classes.get_character_ranges(class_of(' '))
+    );
+    assert_eq!(
+        vec![('a', 'a'), ('c', 'z')],
+        character_classes.get_character_ranges(class_of('a'))
+    );
+    assert_eq!(
+        vec![('b', 'b')],
+        character_classes.get_character_ranges(class_of('b'))
+    );
+}
@@ -0,0 +1,116 @@
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Output;
+use std::process::Stdio;
+
+fn run(arguments: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_regex"))
+        .args(arguments)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+
+    return child.wait_with_output().unwrap();
+}
+
+fn get_stdout(output: &Output) -> String {
+    return String::from_utf8(output.stdout.clone()).unwrap();
+}
+
+fn create_directory(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("regex-cli-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+
+    for (path, content) in files {
+        let path = directory.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    return directory;
+}
+
+const INPUT: &str = "apple pie\nbanana\ncherry pie\n";
+
+#[test]
+fn print_matching_lines() {
+    let output = run(&["pie"], INPUT);
+
+    assert_eq!("apple pie\ncherry pie\n", get_stdout(&output));
+    assert_eq!(Some(0), output.status.code());
+}
+
+#[test]
+fn print_line_numbers_of_inverted_matches() {
+    let output = run(&["-nv", "pie"], INPUT);
+
+    assert_eq!("2:banana\n", get_stdout(&output));
+}
+
+#[test]
+fn count_matching_lines() {
+    assert_eq!("2\n", get_stdout(&run(&["-c", "pie"], INPUT)));
+    assert_eq!("1\n", get_stdout(&run(&["-c", "-v", "pie"], INPUT)));
+}
+
+#[test]
+fn print_only_matching_parts() {
+    let output = run(&["-o", "b[an]+|ch"], INPUT);
+
+    assert_eq!("banana\nch\n", get_stdout(&output));
+}
+
+#[test]
+fn exit_with_one_without_selected_lines() {
+    let output = run(&["kiwi"], INPUT);
+
+    assert_eq!("", get_stdout(&output));
+    assert_eq!(Some(1), output.status.code());
+}
+
+#[test]
+fn search_directories_recursively() {
+    let directory = create_directory(
+        "recursive",
+        &[("b.txt", "no\nyes 2\n"), ("a/c.txt", "yes 1\n")],
+    );
+    let directory_name = directory.display().to_string();
+
+    let output = run(&["-r", "yes", &directory_name], "");
+    assert_eq!(
+        format!("{0}/a/c.txt:yes 1\n{0}/b.txt:yes 2\n", directory_name),
+        get_stdout(&output)
+    );
+
+    let output = run(&["yes", &directory_name], "");
+    assert_eq!("", get_stdout(&output));
+    assert_eq!(Some(2), output.status.code());
+
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn dump_state_machines() {
+    let output = run(&["--dump-dfa", "ab"], "");
+    let stdout = get_stdout(&output);
+
+    assert_eq!(true, stdout.starts_with("Matching groups:\n"));
+    assert_eq!(true, stdout.contains("Reverse state machine:\n"));
+}
+
+#[test]
+fn fail_on_unknown_option() {
+    let output = run(&["-z", "ab"], "");
+
+    assert_eq!(Some(2), output.status.code());
+}
@@ -0,0 +1,106 @@
+use regex::get_compiled_regex;
+use regex::CompiledState;
+use regex::ElementType;
+use regex::MatchingGroup;
+
+#[test]
+fn report_positions_of_concatenation() {
+    let compiled_regex = get_compiled_regex("ab");
+
+    let element_types: Vec<ElementType> = compiled_regex
+        .positions
+        .iter()
+        .map(|position| position.element_type.clone())
+        .collect();
+    assert_eq!(
+        vec![
+            ElementType::Leaf,
+            ElementType::Leaf,
+            ElementType::Concatenation,
+            ElementType::Leaf,
+            ElementType::Concatenation,
+        ],
+        element_types
+    );
+
+    let root = &compiled_regex.positions[4];
+    assert_eq!(false, root.is_nullable);
+    assert_eq!(vec![0], root.first_pos);
+    assert_eq!(vec![3], root.last_pos);
+    assert_eq!(None, root.parent_index);
+
+    assert_eq!(vec![1], compiled_regex.positions[0].follow_pos);
+    assert_eq!(vec![3], compiled_regex.positions[1].follow_pos);
+    assert_eq!(true, compiled_regex.positions[3].accepted);
+}
+
+#[test]
+fn report_nullable_positions() {
+    let compiled_regex = get_compiled_regex("a*b?");
+
+    let zero_or_more = &compiled_regex.positions[1];
+    assert_eq!(ElementType::ZeroOrMore, zero_or_more.element_type);
+    assert_eq!(true, zero_or_more.is_nullable);
+    assert_eq!(Some(0), zero_or_more.left_child_index);
+    assert_eq!(vec![0, 2, 5], compiled_regex.positions[0].follow_pos);
+}
+
+#[test]
+fn report_matching_groups() {
+    let compiled_regex = get_compiled_regex("aba");
+
+    assert_eq!(
+        vec![
+            MatchingGroup::Character('a'),
+            MatchingGroup::Character('b'),
+            MatchingGroup::AcceptedState,
+        ],
+        compiled_regex.matching_groups
+    );
+}
+
+#[test]
+fn report_deterministic_states() {
+    let compiled_regex = get_compiled_regex("ab");
+
+    assert_eq!(
+        vec![
+            CompiledState {
+                index: 0,
+                positions: vec![0],
+                transitions: vec![(0, 1)],
+                is_accepted: false,
+            },
+            CompiledState {
+                index: 1,
+                positions: vec![1],
+                transitions: vec![(1, 2)],
+                is_accepted: false,
+            },
+            CompiledState {
+                index: 2,
+                positions: vec![3],
+                transitions: vec![],
+                is_accepted: true,
+            },
+        ],
+        compiled_regex.states
+    );
+}
+
+#[test]
+fn compiled_regex_converts_into_regex_engine() {
+    let regex_engine = get_compiled_regex("a(b|c)*").into_regex_engine();
+
+    assert_eq!(true, regex_engine.matches("abcb"));
+    assert_eq!(false, regex_engine.matches("b"));
+}
+
+#[test]
+fn display_compiled_regex() {
+    let report = get_compiled_regex("a").to_string();
+
+    assert!(report.contains("Matching groups:\n  0: Character('a')\n"));
+    assert!(report.contains("  0: [0]\n    Character('a') -> 1\n"));
+    assert!(report.contains("  1: [1] accepted\n"));
+}
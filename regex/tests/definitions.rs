@@ -0,0 +1,129 @@
+use regex::get_regex_engine;
+use regex::get_regex_syntax_tree;
+use regex::RegexBuilder;
+use regex::RegexError;
+use regex::Span;
+
+fn get_number_builder() -> RegexBuilder {
+    return RegexBuilder::new()
+        .define("LNUM", "[0-9]+")
+        .define("DNUM", "([0-9]*\".\"[0-9]+)|([0-9]+\".\"[0-9]*)")
+        .define("EXPONENT_DNUM", "(({LNUM}|{DNUM})[eE][+-]?{LNUM})");
+}
+
+#[test]
+fn expand_definitions() {
+    let builder = get_number_builder();
+
+    let regex_engine = builder.get_regex_engine("{LNUM}").unwrap();
+    assert_eq!(true, regex_engine.matches("123"));
+    assert_eq!(false, regex_engine.matches("12a"));
+
+    let regex_engine = builder.get_regex_engine("{DNUM}").unwrap();
+    assert_eq!(true, regex_engine.matches("1.5"));
+    assert_eq!(true, regex_engine.matches(".5"));
+    assert_eq!(true, regex_engine.matches("1."));
+    assert_eq!(false, regex_engine.matches("."));
+}
+
+#[test]
+fn expand_nested_definitions() {
+    let regex_engine = get_number_builder()
+        .get_regex_engine("{EXPONENT_DNUM}")
+        .unwrap();
+
+    assert_eq!(true, regex_engine.matches("1e10"));
+    assert_eq!(true, regex_engine.matches("1.5E-3"));
+    assert_eq!(true, regex_engine.matches(".5e+3"));
+    assert_eq!(false, regex_engine.matches("1.5"));
+    assert_eq!(false, regex_engine.matches("1e"));
+}
+
+#[test]
+fn definitions_have_the_same_syntax_tree_as_inlined_regexes() {
+    let builder = RegexBuilder::new().define("DIGIT", "[0-9]");
+
+    assert_eq!(
+        get_regex_syntax_tree("a[0-9]b"),
+        builder.get_regex_syntax_tree("a{DIGIT}b").unwrap()
+    );
+}
+
+#[test]
+fn repeat_whole_definition() {
+    let regex_engine = RegexBuilder::new()
+        .define("PAIR", "ab")
+        .get_regex_engine("{PAIR}*c")
+        .unwrap();
+
+    assert_eq!(true, regex_engine.matches("c"));
+    assert_eq!(true, regex_engine.matches("ababc"));
+    assert_eq!(false, regex_engine.matches("abbc"));
+}
+
+#[test]
+fn repeat_whole_group() {
+    let regex_engine = get_regex_engine("(ab)*c");
+
+    assert_eq!(true, regex_engine.matches("c"));
+    assert_eq!(true, regex_engine.matches("ababc"));
+    assert_eq!(false, regex_engine.matches("abbc"));
+
+    let regex_engine = get_regex_engine("x(ab)+");
+    assert_eq!(true, regex_engine.matches("xab"));
+    assert_eq!(true, regex_engine.matches("xabab"));
+    assert_eq!(false, regex_engine.matches("x"));
+}
+
+#[test]
+fn braces_without_a_name_are_characters() {
+    let builder = RegexBuilder::new();
+
+    assert_eq!(
+        true,
+        builder.get_regex_engine("a{}").unwrap().matches("a{}")
+    );
+    assert_eq!(
+        true,
+        builder.get_regex_engine("{1,2").unwrap().matches("{1,2")
+    );
+    assert_eq!(true, get_regex_engine("{LNUM}").matches("{LNUM}"));
+}
+
+#[test]
+fn fail_on_undefined_definition() {
+    let error = get_number_builder().get_regex_engine("{LNUM}{FLOAT}").err();
+
+    assert_eq!(
+        Some(RegexError::UndefinedDefinition(
+            "FLOAT".to_string(),
+            Span::new(6, 13)
+        )),
+        error
+    );
+}
+
+#[test]
+fn fail_on_recursive_definitions() {
+    let builder = RegexBuilder::new()
+        .define("A", "a{B}")
+        .define("B", "b{C}")
+        .define("C", "c{A}");
+
+    assert_eq!(
+        Some(RegexError::RecursiveDefinition(
+            vec![
+                "A".to_string(),
+                "B".to_string(),
+                "C".to_string(),
+                "A".to_string(),
+            ],
+            Span::new(1, 4)
+        )),
+        builder.get_regex_engine("x{A}").err()
+    );
+    assert_eq!(
+        "The definitions reference each other recursively: A -> B -> C -> A (at 0..3)",
+        builder.get_regex_engine("{A}").err().unwrap().to_string()
+    );
+}
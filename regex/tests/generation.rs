@@ -0,0 +1,134 @@
+use regex::get_regex_engine;
+use regex::get_regex_engine_for_ast;
+use regex::MatchingGroup;
+use regex::MatchingGroupElements;
+use regex::RegexAstElements;
+
+#[test]
+fn shortest_match_of_single_character() {
+    let regex_engine = get_regex_engine("a");
+
+    assert_eq!(Some(String::from("a")), regex_engine.shortest_match());
+}
+
+#[test]
+fn shortest_match_skips_optional_parts() {
+    let regex_engine = get_regex_engine("ab*c?d");
+
+    assert_eq!(Some(String::from("ad")), regex_engine.shortest_match());
+}
+
+#[test]
+fn shortest_match_of_alternation_prefers_shorter_branch() {
+    let regex_engine = get_regex_engine("abc|de");
+
+    assert_eq!(Some(String::from("de")), regex_engine.shortest_match());
+}
+
+#[test]
+fn shortest_match_of_empty_negative_group() {
+    let regex_engine = get_regex_engine("[^]");
+
+    assert_eq!(Some(String::from("a")), regex_engine.shortest_match());
+}
+
+#[test]
+fn examples_are_in_length_lexicographic_order() {
+    let regex_engine = get_regex_engine("(b|a)*");
+
+    let expected = vec!["", "a", "b", "aa", "ab", "ba", "bb", "aaa"];
+    assert_eq!(expected, regex_engine.examples(8));
+}
+
+#[test]
+fn examples_of_finite_language() {
+    let regex_engine = get_regex_engine("ab|cd|ef");
+
+    assert_eq!(vec!["ab", "cd", "ef"], regex_engine.examples(10));
+}
+
+#[test]
+fn examples_use_group_representatives() {
+    let regex_engine = get_regex_engine("[a-z][0-9]");
+
+    assert_eq!(vec!["a0"], regex_engine.examples(5));
+}
+
+#[test]
+fn examples_of_negative_group_are_accepted() {
+    let regex_engine = get_regex_engine("x[^a-c]");
+
+    let examples = regex_engine.examples(1);
+    assert_eq!(vec!["xd"], examples);
+    assert_eq!(true, regex_engine.matches(&examples[0]));
+}
+
+#[test]
+fn random_samples_are_accepted_and_bounded() {
+    let regex_engine = get_regex_engine("a[0-9]*(b|cd)");
+
+    let samples = regex_engine.random_samples(50, 6, 42);
+    assert_eq!(50, samples.len());
+    for sample in samples {
+        assert!(sample.chars().count() <= 6, "{} is too long", sample);
+        assert_eq!(
+            true,
+            regex_engine.matches(&sample),
+            "{} is not accepted",
+            sample
+        );
+    }
+}
+
+#[test]
+fn random_samples_are_reproducible() {
+    let regex_engine = get_regex_engine("[a-c]*x");
+
+    assert_eq!(
+        regex_engine.random_samples(10, 20, 7),
+        regex_engine.random_samples(10, 20, 7)
+    );
+}
+
+#[test]
+fn random_samples_respect_unreachable_length_bound() {
+    let regex_engine = get_regex_engine("abc");
+
+    assert_eq!(Vec::<String>::new(), regex_engine.random_samples(5, 2, 1));
+}
+
+#[test]
+fn generate_from_negative_groups_without_ascii_characters() {
+    // The group excludes all printable ASCII characters, only the ranges of its class remain.
+    let regex_engine =
+        get_regex_engine_for_ast(RegexAstElements::Leaf(MatchingGroup::NegativeGroup(vec![
+            MatchingGroupElements::Range(' ', '~'),
+        ])));
+
+    let examples = regex_engine.examples(3);
+    assert_eq!(1, examples.len());
+    let samples = regex_engine.random_samples(20, 5, 0);
+    assert_eq!(20, samples.len());
+    for example in examples.iter().chain(samples.iter()) {
+        assert_eq!(true, regex_engine.matches(example), "{:?}", example);
+    }
+    assert_eq!(true, regex_engine.matches("é"));
+}
+
+#[test]
+fn generate_from_classes_beyond_the_start_of_a_range() {
+    // `[é-ü]` splits `[a-ü]` into classes, its characters are far from the start of the range.
+    let range = |start, end| {
+        RegexAstElements::Leaf(MatchingGroup::Group(vec![MatchingGroupElements::Range(
+            start, end,
+        )]))
+    };
+    let regex_engine = get_regex_engine_for_ast(range('a', 'ü').then(range('é', 'ü')));
+
+    assert_eq!(Some(String::from("aé")), regex_engine.shortest_match());
+    let samples = regex_engine.random_samples(20, 2, 3);
+    assert_eq!(20, samples.len());
+    for sample in samples {
+        assert_eq!(true, regex_engine.matches(&sample), "{:?}", sample);
+    }
+}
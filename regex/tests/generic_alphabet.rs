@@ -0,0 +1,144 @@
+use regex::get_compiled_regex_for_ast;
+use regex::get_regex_engine;
+use regex::get_regex_engine_for_ast;
+use regex::MatchingGroup;
+use regex::MatchingGroupElements;
+use regex::RegexAstElements;
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd)]
+enum Token {
+    Id,
+    Comma,
+    Number,
+    Semicolon,
+}
+
+fn get_list_ast() -> RegexAstElements<Token> {
+    return RegexAstElements::symbol(Token::Id).then(
+        RegexAstElements::symbol(Token::Comma)
+            .then(RegexAstElements::symbol(Token::Id))
+            .zero_or_more(),
+    );
+}
+
+#[test]
+fn build_ast_for_tokens() {
+    let expected_ast = RegexAstElements::Concatenation(
+        Box::new(RegexAstElements::Leaf(MatchingGroup::Character(Token::Id))),
+        Box::new(RegexAstElements::ZeroOrMore(Box::new(
+            RegexAstElements::Concatenation(
+                Box::new(RegexAstElements::Leaf(MatchingGroup::Character(
+                    Token::Comma,
+                ))),
+                Box::new(RegexAstElements::Leaf(MatchingGroup::Character(Token::Id))),
+            ),
+        ))),
+    );
+
+    assert_eq!(expected_ast, get_list_ast());
+}
+
+#[test]
+fn match_token_list() {
+    let regex_engine = get_regex_engine_for_ast(get_list_ast());
+
+    assert_eq!(true, regex_engine.matches_symbols(&[Token::Id]));
+    assert_eq!(
+        true,
+        regex_engine.matches_symbols(&[
+            Token::Id,
+            Token::Comma,
+            Token::Id,
+            Token::Comma,
+            Token::Id
+        ])
+    );
+    assert_eq!(false, regex_engine.matches_symbols(&[]));
+    assert_eq!(
+        false,
+        regex_engine.matches_symbols(&[Token::Id, Token::Comma])
+    );
+    assert_eq!(false, regex_engine.matches_symbols(&[Token::Id, Token::Id]));
+}
+
+#[test]
+fn match_token_groups() {
+    let ast = RegexAstElements::one_of(vec![Token::Id, Token::Number])
+        .one_or_more()
+        .then(RegexAstElements::none_of(vec![Token::Id, Token::Number]).zero_or_one());
+    let regex_engine = get_regex_engine_for_ast(ast);
+
+    assert_eq!(
+        true,
+        regex_engine.matches_symbols(&[Token::Number, Token::Id])
+    );
+    assert_eq!(
+        true,
+        regex_engine.matches_symbols(&[Token::Id, Token::Semicolon])
+    );
+    assert_eq!(false, regex_engine.matches_symbols(&[Token::Semicolon]));
+    assert_eq!(
+        false,
+        regex_engine.matches_symbols(&[Token::Id, Token::Comma, Token::Id])
+    );
+}
+
+#[test]
+fn match_symbol_ranges() {
+    let ast = RegexAstElements::Leaf(MatchingGroup::Group(vec![MatchingGroupElements::Range(
+        10, 20,
+    )]))
+    .then(RegexAstElements::symbol(0));
+    let regex_engine = get_regex_engine_for_ast(ast);
+
+    assert_eq!(true, regex_engine.matches_symbols(&[15, 0]));
+    assert_eq!(false, regex_engine.matches_symbols(&[21, 0]));
+}
+
+#[test]
+fn compile_token_regex() {
+    let compiled_regex = get_compiled_regex_for_ast(RegexAstElements::sequence(vec![
+        Token::Id,
+        Token::Semicolon,
+    ]));
+
+    assert_eq!(
+        vec![
+            MatchingGroup::Character(Token::Id),
+            MatchingGroup::Character(Token::Semicolon),
+            MatchingGroup::AcceptedState,
+        ],
+        compiled_regex.matching_groups
+    );
+    assert_eq!(3, compiled_regex.states.len());
+}
+
+#[test]
+fn empty_sequence_matches_empty_input() {
+    let regex_engine = get_regex_engine_for_ast(RegexAstElements::<Token>::sequence(vec![]));
+
+    assert_eq!(true, regex_engine.matches_symbols(&[]));
+    assert_eq!(false, regex_engine.matches_symbols(&[Token::Id]));
+
+    let optional_list = get_regex_engine_for_ast(
+        RegexAstElements::sequence(vec![])
+            .or(RegexAstElements::sequence(vec![Token::Id, Token::Comma])),
+    );
+    assert_eq!(true, optional_list.matches_symbols(&[]));
+    assert_eq!(
+        true,
+        optional_list.matches_symbols(&[Token::Id, Token::Comma])
+    );
+}
+
+#[test]
+fn built_character_ast_matches_parsed_regex() {
+    let ast =
+        RegexAstElements::sequence(vec!['a', 'b']).or(RegexAstElements::symbol('c').one_or_more());
+    let built_engine = get_regex_engine_for_ast(ast);
+    let parsed_engine = get_regex_engine("ab|c+");
+
+    for input in &["ab", "c", "ccc", "abc", "", "a"] {
+        assert_eq!(parsed_engine.matches(input), built_engine.matches(input));
+    }
+}
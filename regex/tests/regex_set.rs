@@ -0,0 +1,90 @@
+use regex::get_regex_syntax_tree;
+use regex::RegexSet;
+
+#[test]
+fn find_longest_match_of_regex_set() {
+    let regex_set = RegexSet::new(vec![
+        get_regex_syntax_tree("if"),
+        get_regex_syntax_tree("[a-z]+"),
+        get_regex_syntax_tree("[0-9]+"),
+    ]);
+
+    assert_eq!(3, regex_set.len());
+    assert_eq!(Some((0, 2)), regex_set.longest_match_at("if x", 0));
+    assert_eq!(Some((1, 3)), regex_set.longest_match_at("iff x", 0));
+    assert_eq!(Some((1, 6)), regex_set.longest_match_at("if x y", 5));
+    assert_eq!(Some((2, 4)), regex_set.longest_match_at("x 12", 2));
+    assert_eq!(None, regex_set.longest_match_at("x 12", 1));
+}
+
+#[test]
+fn first_regex_of_set_wins() {
+    let regex_set = RegexSet::new(vec![
+        get_regex_syntax_tree("[a-z]+"),
+        get_regex_syntax_tree("if"),
+    ]);
+
+    assert_eq!(Some((0, 2)), regex_set.longest_match_at("if", 0));
+    assert_eq!(None, RegexSet::new(Vec::new()).longest_match_at("if", 0));
+}
+
+#[test]
+fn match_only_regexes_of_entry_point() {
+    let regex_set = RegexSet::with_entry_points(
+        vec![
+            get_regex_syntax_tree("[a-z]+"),
+            get_regex_syntax_tree("'[a-z ]*'"),
+            get_regex_syntax_tree("[^']+"),
+        ],
+        vec![vec![0, 1], vec![2], vec![0, 1]],
+    );
+
+    assert_eq!(
+        Some((1, 5)),
+        regex_set.longest_match_from_entry_point(0, "'a b' c", 0)
+    );
+    assert_eq!(
+        Some((2, 4)),
+        regex_set.longest_match_from_entry_point(1, "'a b' c", 1)
+    );
+    assert_eq!(
+        None,
+        regex_set.longest_match_from_entry_point(1, "'a b' c", 0)
+    );
+    // Entry points with the same regexes share their start state.
+    assert_eq!(regex_set.get_start_state(0), regex_set.get_start_state(2));
+}
+
+#[test]
+fn match_all_regexes_without_entry_points() {
+    let regex_set = RegexSet::with_entry_points(
+        vec![get_regex_syntax_tree("a"), get_regex_syntax_tree("b+")],
+        Vec::new(),
+    );
+
+    assert_eq!(Some((1, 2)), regex_set.longest_match_at("bb", 0));
+    assert_eq!(regex_set.get_start_state(0), regex_set.start_state());
+    let state = regex_set.get_next_state(regex_set.start_state(), 'a');
+    assert_eq!(
+        Some(0),
+        state.and_then(|state| regex_set.get_accepted_regex(state))
+    );
+}
+
+#[test]
+fn get_transitions_as_character_ranges() {
+    let regex_set = RegexSet::new(vec![
+        get_regex_syntax_tree("[a-z]+"),
+        get_regex_syntax_tree("if"),
+    ]);
+    let start_state = regex_set.get_start_state(0);
+    let transitions = regex_set.get_transitions(start_state);
+
+    let ranges: Vec<(char, char)> = transitions
+        .iter()
+        .map(|(start, end, _)| (*start, *end))
+        .collect();
+    assert_eq!(vec![('a', 'h'), ('i', 'i'), ('j', 'z')], ranges);
+    assert_eq!(transitions[0].2, transitions[2].2);
+    assert_eq!(Some(0), regex_set.get_accepted_regex(transitions[0].2));
+}
@@ -0,0 +1,248 @@
+use regex::get_compiled_regex;
+use regex::get_regex_engine;
+use regex::get_regex_engine_for_ast;
+use regex::Match;
+use regex::MatchingGroup;
+use regex::MatchingGroupElements;
+use regex::Prefilter;
+use regex::RegexAstElements;
+
+#[test]
+fn find_leftmost_match() {
+    let regex_engine = get_regex_engine("ab+");
+
+    assert_eq!(Some(Match::new(3, 6)), regex_engine.find("xxxabbxab"));
+    assert_eq!(None, regex_engine.find("xxxa"));
+}
+
+#[test]
+fn find_longest_match_at_leftmost_position() {
+    let regex_engine = get_regex_engine("a|ab|abc");
+
+    let haystack = "xabcd";
+    let found_match = regex_engine.find(haystack).unwrap();
+    assert_eq!("abc", found_match.as_str(haystack));
+}
+
+#[test]
+fn find_at_offset() {
+    let regex_engine = get_regex_engine("ab");
+
+    assert_eq!(Some(Match::new(4, 6)), regex_engine.find_at("abxxab", 1));
+}
+
+#[test]
+fn find_all_matches() {
+    let regex_engine = get_regex_engine("[0-9]+");
+
+    let haystack = "a1 b22 c333";
+    let matches: Vec<&str> = regex_engine
+        .find_iter(haystack)
+        .map(|found_match| found_match.as_str(haystack))
+        .collect();
+    assert_eq!(vec!["1", "22", "333"], matches);
+}
+
+#[test]
+fn find_all_empty_matches() {
+    let regex_engine = get_regex_engine("a*");
+
+    let matches: Vec<Match> = regex_engine.find_iter("baa").collect();
+    assert_eq!(
+        vec![Match::new(0, 0), Match::new(1, 3), Match::new(3, 3)],
+        matches
+    );
+}
+
+#[test]
+fn find_in_multi_byte_text() {
+    let regex_engine = get_regex_engine("b");
+
+    let haystack = "äöb";
+    assert_eq!(Some(Match::new(4, 5)), regex_engine.find(haystack));
+}
+
+#[test]
+fn is_match_searches_whole_text() {
+    let regex_engine = get_regex_engine("ERROR:");
+
+    assert_eq!(true, regex_engine.is_match("12:00 ERROR: disk full"));
+    assert_eq!(false, regex_engine.is_match("12:00 INFO: all good"));
+    assert_eq!(false, regex_engine.matches("12:00 ERROR: disk full"));
+}
+
+#[test]
+fn extract_literal_prefix() {
+    let compiled_regex = get_compiled_regex("\"GET /\"[a-z]*");
+
+    assert_eq!(
+        Prefilter::Literal(String::from("GET /")),
+        compiled_regex.prefilter
+    );
+}
+
+#[test]
+fn extract_common_prefix_of_alternation() {
+    let compiled_regex = get_compiled_regex("abc|abd");
+
+    assert_eq!(
+        Prefilter::Literal(String::from("ab")),
+        compiled_regex.prefilter
+    );
+}
+
+#[test]
+fn extract_first_characters() {
+    let compiled_regex = get_compiled_regex("(x|[0-9])y");
+
+    assert_eq!(
+        Prefilter::FirstCharacters(vec![
+            MatchingGroupElements::Character('x'),
+            MatchingGroupElements::Range('0', '9'),
+        ]),
+        compiled_regex.prefilter
+    );
+}
+
+#[test]
+fn no_prefilter_for_nullable_regex() {
+    let compiled_regex = get_compiled_regex("a*");
+
+    assert_eq!(Prefilter::None, compiled_regex.prefilter);
+}
+
+#[test]
+fn no_prefilter_for_negative_group() {
+    let compiled_regex = get_compiled_regex("[^a]b");
+
+    assert_eq!(Prefilter::None, compiled_regex.prefilter);
+}
+
+#[test]
+fn prefilter_does_not_change_matches() {
+    let haystack = "GET /a GE GET /bc POST /d GET GET /".repeat(20);
+
+    for regex in &["\"GET /\"[a-z]*", "(G|P)[A-Z]*", "[a-d]+", "E|T"] {
+        let regex_engine = get_regex_engine(regex);
+        let expected: Vec<Match> = get_regex_engine(regex)
+            .without_prefilter()
+            .find_iter(&haystack)
+            .collect();
+
+        assert_eq!(
+            expected,
+            regex_engine.find_iter(&haystack).collect::<Vec<Match>>()
+        );
+    }
+}
+
+#[test]
+fn find_leftmost_match_ending_after_shorter_match() {
+    let regex_engine = get_regex_engine("abcd|c");
+
+    assert_eq!(Some(Match::new(0, 4)), regex_engine.find("abcd"));
+    assert_eq!(Some(Match::new(2, 3)), regex_engine.find("abce"));
+}
+
+#[test]
+fn reverse_state_machine_is_compiled() {
+    let compiled_regex = get_compiled_regex("ab");
+
+    let reverse = compiled_regex.reverse.unwrap();
+    assert_eq!(
+        vec![
+            MatchingGroup::Character('b'),
+            MatchingGroup::Character('a'),
+            MatchingGroup::AcceptedState
+        ],
+        reverse.matching_groups
+    );
+    assert_eq!(None, reverse.reverse);
+    assert_eq!(None, reverse.unanchored);
+    assert!(compiled_regex.unanchored.is_some());
+}
+
+#[test]
+fn search_without_reverse_state_machine() {
+    let regex_engine = get_regex_engine_for_ast(
+        RegexAstElements::symbol('a').then(RegexAstElements::symbol('b').one_or_more()),
+    );
+
+    assert_eq!(Some(Match::new(3, 6)), regex_engine.find("xxxabbxab"));
+    assert_eq!(true, regex_engine.is_match("xab"));
+    assert_eq!(false, regex_engine.is_match("xxxa"));
+    assert_eq!(
+        vec![Match::new(3, 6), Match::new(7, 9)],
+        regex_engine.find_iter("xxxabbxab").collect::<Vec<Match>>()
+    );
+}
+
+#[test]
+fn is_match_stops_after_the_first_match() {
+    let regex_engine = get_regex_engine("[a-z]+[0-9]");
+    let haystack = format!("ab1{}", "x".repeat(100_000));
+
+    assert_eq!(true, regex_engine.is_match(&haystack));
+    assert_eq!(Some(Match::new(0, 3)), regex_engine.find(&haystack));
+}
+
+#[test]
+fn find_in_linear_time() {
+    // Every `a` starts a match of `a+b` which only fails at the `c`. Trying each start on its
+    // own would read the text quadratically often.
+    let regex_engine = get_regex_engine("a+b|c");
+    let haystack = format!("{}c", "a".repeat(200_000));
+
+    assert_eq!(Some(Match::new(200_000, 200_001)), regex_engine.find(&haystack));
+    assert_eq!(
+        Some(Match::new(0, 200_001)),
+        regex_engine.find(&format!("{}b", "a".repeat(200_000)))
+    );
+}
+
+#[test]
+fn find_iter_matches_naive_search() {
+    let haystacks = ["", "abcabcab", "xaabbx", "cabbage", "aaaa", "bcbcb"];
+    let regexes = ["a", "ab|b", "a*b", "(ab|a)(bc|c)?", "b?c", "[a-c]x?", "ca*"];
+
+    for regex in &regexes {
+        let regex_engine = get_regex_engine(regex);
+
+        for haystack in &haystacks {
+            let expected = find_all_naively(regex, haystack);
+            let actual: Vec<Match> = regex_engine.find_iter(haystack).collect();
+
+            assert_eq!(expected, actual, "{} in {}", regex, haystack);
+        }
+    }
+}
+
+/// Tries every start and end position with the anchored state machine.
+fn find_all_naively(regex: &str, haystack: &str) -> Vec<Match> {
+    let regex_engine = get_regex_engine(regex);
+    let mut matches = Vec::new();
+    let mut position = 0;
+
+    while position <= haystack.len() {
+        let found_match = (position..=haystack.len()).find_map(|start| {
+            (start..=haystack.len())
+                .rev()
+                .find(|end| regex_engine.matches(&haystack[start..*end]))
+                .map(|end| Match::new(start, end))
+        });
+
+        match found_match {
+            Some(found_match) => {
+                matches.push(found_match);
+                position = if found_match.start == found_match.end {
+                    found_match.end + 1
+                } else {
+                    found_match.end
+                };
+            }
+            None => break,
+        }
+    }
+
+    return matches;
+}
@@ -0,0 +1,151 @@
+use regex::get_regex_syntax_tree;
+use regex::get_regex_syntax_tree_with_spans;
+use regex::get_syntax_tree_dot_graph;
+use regex::RegexBuilder;
+use regex::Span;
+use regex::SpanTree;
+
+fn leaf(start: usize, end: usize) -> SpanTree {
+    return SpanTree::new(Span::new(start, end), Vec::new());
+}
+
+fn node(start: usize, end: usize, children: Vec<SpanTree>) -> SpanTree {
+    return SpanTree::new(Span::new(start, end), children);
+}
+
+#[test]
+fn spans_of_concatenation() {
+    let (ast, spans) = get_regex_syntax_tree_with_spans("ab");
+
+    assert_eq!(get_regex_syntax_tree("ab"), ast);
+    assert_eq!(
+        node(
+            0,
+            2,
+            vec![node(0, 2, vec![leaf(0, 1), leaf(1, 2)]), leaf(2, 2)]
+        ),
+        spans
+    );
+}
+
+#[test]
+fn spans_of_operators() {
+    let (_, spans) = get_regex_syntax_tree_with_spans("a*|b?");
+
+    assert_eq!(
+        node(
+            0,
+            5,
+            vec![
+                node(
+                    0,
+                    5,
+                    vec![node(0, 2, vec![leaf(0, 1)]), node(3, 5, vec![leaf(3, 4)])]
+                ),
+                leaf(5, 5)
+            ]
+        ),
+        spans
+    );
+}
+
+#[test]
+fn spans_of_repeated_groups_include_the_parentheses() {
+    let (_, spans) = get_regex_syntax_tree_with_spans("x(ab)*");
+    let repetition = &spans.children[0].children[1];
+
+    assert_eq!(Span::new(1, 6), repetition.span);
+    assert_eq!(Span::new(2, 4), repetition.children[0].span);
+}
+
+#[test]
+fn spans_of_character_groups_and_escapes() {
+    let regex = "[a-z]\\n\"*\"";
+    let (_, spans) = get_regex_syntax_tree_with_spans(regex);
+    let concatenation = &spans.children[0];
+
+    assert_eq!(
+        "[a-z]",
+        concatenation.children[0].children[0].span.as_str(regex)
+    );
+    assert_eq!(
+        "\\n",
+        concatenation.children[0].children[1].span.as_str(regex)
+    );
+    assert_eq!("*", concatenation.children[1].span.as_str(regex));
+}
+
+#[test]
+fn spans_point_into_the_original_regex() {
+    let regex = "(?x) a  ä # comment\n b";
+    let (_, spans) = get_regex_syntax_tree_with_spans(regex);
+
+    assert_eq!(
+        "a",
+        spans.children[0].children[0].children[0].span.as_str(regex)
+    );
+    assert_eq!(
+        "ä",
+        spans.children[0].children[0].children[1].span.as_str(regex)
+    );
+    assert_eq!("b", spans.children[0].children[1].span.as_str(regex));
+}
+
+#[test]
+fn spans_of_definitions_point_at_the_reference() {
+    let regex = "x{DIGITS}";
+    let (_, spans) = RegexBuilder::new()
+        .define("DIGITS", "[0-9]+")
+        .get_regex_syntax_tree_with_spans(regex)
+        .unwrap();
+    let concatenation = &spans.children[0];
+
+    assert_eq!(
+        "x",
+        concatenation.children[0].children[0].span.as_str(regex)
+    );
+    assert_eq!(
+        "{DIGITS}",
+        concatenation.children[0].children[1].span.as_str(regex)
+    );
+    assert_eq!("{DIGITS}", concatenation.children[1].span.as_str(regex));
+}
+
+#[test]
+fn get_spans_at_offset() {
+    let (_, spans) = get_regex_syntax_tree_with_spans("ab*");
+
+    assert_eq!(
+        vec![
+            Span::new(0, 3),
+            Span::new(0, 3),
+            Span::new(1, 3),
+            Span::new(1, 2)
+        ],
+        spans.get_spans_at(1)
+    );
+    assert_eq!(Vec::<Span>::new(), spans.get_spans_at(3));
+}
+
+#[test]
+fn export_syntax_tree_as_dot_graph() {
+    let regex = "a|\"b\"";
+    let (ast, spans) = get_regex_syntax_tree_with_spans(regex);
+
+    assert_eq!(
+        "digraph syntax_tree {
+    node [shape=box];
+    node0 [label=\"Concatenation\\n0..5 a|\\\"b\\\"\"];
+    node1 [label=\"Alternation\\n0..4 a|\\\"b\"];
+    node0 -> node1;
+    node2 [label=\"Leaf\\n0..1 a\"];
+    node1 -> node2;
+    node3 [label=\"Leaf\\n3..4 b\"];
+    node1 -> node3;
+    node4 [label=\"AcceptedState\\n5..5 \"];
+    node0 -> node4;
+}
+",
+        get_syntax_tree_dot_graph(&ast, &spans, regex)
+    );
+}
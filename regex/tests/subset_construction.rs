@@ -0,0 +1,50 @@
+use regex::get_compiled_regex;
+
+#[test]
+fn nested_repetitions_reuse_equal_states() {
+    let compiled_regex = get_compiled_regex("(a*)*");
+
+    assert_eq!(1, compiled_regex.states.len());
+
+    let regex_engine = compiled_regex.into_regex_engine();
+    assert_eq!(true, regex_engine.matches(""));
+    assert_eq!(true, regex_engine.matches("aaaa"));
+    assert_eq!(false, regex_engine.matches("ab"));
+}
+
+#[test]
+fn consecutive_repetitions_reuse_equal_states() {
+    let compiled_regex = get_compiled_regex("a*a*a*");
+
+    assert_eq!(1, compiled_regex.states.len());
+
+    let regex_engine = compiled_regex.into_regex_engine();
+    assert_eq!(true, regex_engine.matches(""));
+    assert_eq!(true, regex_engine.matches("aaaaaaa"));
+}
+
+#[test]
+fn state_positions_are_sorted_without_duplicates() {
+    let compiled_regex = get_compiled_regex("(a|b)*(a*|b)c");
+
+    for state in compiled_regex.states {
+        let mut positions = state.positions.clone();
+        positions.sort();
+        positions.dedup();
+
+        assert_eq!(positions, state.positions);
+    }
+}
+
+#[test]
+fn determinize_pattern_with_thousands_of_positions() {
+    let literal = "abcdefghij".repeat(300);
+    let regex = format!("(x|y)*{}(x|y)*", literal);
+
+    let compiled_regex = get_compiled_regex(&regex);
+    assert_eq!(3001, compiled_regex.states.len());
+
+    let regex_engine = compiled_regex.into_regex_engine();
+    assert_eq!(true, regex_engine.matches(&format!("xy{}yyx", literal)));
+    assert_eq!(false, regex_engine.matches(&literal[1..]));
+}
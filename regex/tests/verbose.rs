@@ -0,0 +1,78 @@
+use regex::get_regex_engine;
+use regex::get_regex_syntax_tree;
+use regex::RegexBuilder;
+
+#[test]
+fn ignore_whitespace_and_comments() {
+    let builder = RegexBuilder::new().verbose(true);
+
+    let regex_engine = builder
+        .get_regex_engine(
+            "
+            [0-9]+      # integer part
+            (\".\" [0-9]*)? # optional fraction
+            ",
+        )
+        .unwrap();
+
+    assert_eq!(true, regex_engine.matches("12"));
+    assert_eq!(true, regex_engine.matches("12.5"));
+    assert_eq!(false, regex_engine.matches("12 .5"));
+    assert_eq!(
+        get_regex_syntax_tree("ab*c"),
+        builder.get_regex_syntax_tree("a b* # comment\n c").unwrap()
+    );
+}
+
+#[test]
+fn enable_verbose_mode_with_flag() {
+    let regex_engine = get_regex_engine("(?x) a b  c # comment");
+
+    assert_eq!(true, regex_engine.matches("abc"));
+    assert_eq!(false, regex_engine.matches(" a b  c # comment"));
+}
+
+#[test]
+fn keep_whitespace_without_verbose_mode() {
+    let regex_engine = get_regex_engine("a b#c");
+
+    assert_eq!(true, regex_engine.matches("a b#c"));
+    assert_eq!(false, regex_engine.matches("ab"));
+}
+
+#[test]
+fn match_quoted_whitespace_literally() {
+    let regex_engine = get_regex_engine("(?x) \"a b\" c \"#\"");
+
+    assert_eq!(true, regex_engine.matches("a bc#"));
+    assert_eq!(false, regex_engine.matches("abc#"));
+}
+
+#[test]
+fn match_escaped_whitespace_literally() {
+    let regex_engine = get_regex_engine("(?x) a\\ b \\# c");
+
+    assert_eq!(true, regex_engine.matches("a b#c"));
+    assert_eq!(false, regex_engine.matches("ab#c"));
+}
+
+#[test]
+fn match_whitespace_in_character_groups() {
+    let regex_engine = get_regex_engine("(?x) a [ #] b");
+
+    assert_eq!(true, regex_engine.matches("a b"));
+    assert_eq!(true, regex_engine.matches("a#b"));
+    assert_eq!(false, regex_engine.matches("ab"));
+}
+
+#[test]
+fn expand_definitions_in_verbose_mode() {
+    let regex_engine = RegexBuilder::new()
+        .verbose(true)
+        .define("DIGITS", "[0-9] +  # at least one digit")
+        .get_regex_engine("{DIGITS} x")
+        .unwrap();
+
+    assert_eq!(true, regex_engine.matches("123x"));
+    assert_eq!(false, regex_engine.matches("123 x"));
+}